@@ -0,0 +1,655 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use candid::Nat;
+use ic_agent::Agent;
+use once_cell::sync::OnceCell;
+use serde_json::{json, Value};
+use tokio::runtime::Handle;
+
+use crate::config::{get_admin_token, BridgeConfig};
+use crate::log::bridge_log_event;
+use crate::state::mirror_registry::{get_mirror, get_mirror_by_nftoken, list_mirrors, MirrorState};
+use crate::ic_trigger::fetch_bridged_token_supply;
+use crate::wrapped_xrp::{remint_wrap_deposit, retry_withdrawal_payout, withdraw_wrapped_xrp, wxrp_summary, WithdrawalRequest};
+use crate::xrpl::checks::{approve_check, cash_check, get_check, list_checks};
+use crate::xrpl::escrow::{cancel_escrow, create_deal_condition, get_escrow, list_escrows};
+use crate::xrpl::paychan::{accept_channel_claim, get_channel, list_channels, redeem_channel, ChannelClaim};
+use crate::xrpl::intents::{cancel_payment_intent, create_payment_intent, get_payment_intent, list_payment_intents, IntentRequest};
+use crate::xrpl::approvals::{approve_held_action, get_held_action, list_held_actions, reject_held_action, HeldStatus};
+use crate::xrpl::limits::{limit_usage, list_limit_rules, reload_limit_rules};
+use crate::xrpl::params::{parameter_status, sync_parameters};
+use crate::xrpl::pause::{pause_status, set_pause_override, PauseFlow};
+use crate::xrpl::reconciliation::{get_reconciliation, latest_reconciliation, reconcile, report_to_csv, ReconRange, ReconciliationReport};
+use crate::xrpl::reserves::{attest_reserves, latest_attestation};
+use crate::xrpl::linking::{get_account_link, issue_link_challenge, link_account, list_account_links, LinkProof};
+use crate::xrpl::issuer::{issue_iou, list_parked_redemptions, list_trust_lines, review_trust_line, supply_report};
+use crate::xrpl::refunds::{get_refund, list_refunds, release_refund};
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::screening::{
+    get_quarantined, list_quarantined, reject_quarantined, release_quarantined, reload_screening_lists, screening_summary,
+    QuarantineStatus,
+};
+use crate::xrpl::swap::{execute_swap, quote_swap, report_fill, Issue, SwapRequest};
+use crate::xrpl::types::XRPLAmount;
+use crate::xrpl::token_mirroring::{burn_xrpl_mirrored_token, list_parked_sales, reconcile_mirrors, BurnRequest};
+
+/// Largest request body the admin API will read.
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// IC agent shared with the admin API once the core loop has built it.
+static ADMIN_AGENT: OnceCell<Agent> = OnceCell::new();
+
+/// Makes the IC agent available to admin routes that call canisters.
+pub fn set_admin_agent(agent: Agent) {
+    let _ = ADMIN_AGENT.set(agent);
+}
+
+/// Parsed admin HTTP request.
+#[derive(Debug, Clone)]
+pub struct AdminRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub token: Option<String>,
+    /// `Content-Type` header, lowercased without parameters.
+    pub content_type: Option<String>,
+    /// `Origin` header; only browsers send one, and the admin API never serves them.
+    pub origin: Option<String>,
+    /// `X-Approver-Token`: identifies the approver voting on a held action.
+    pub approver_token: Option<String>,
+    pub body: Value,
+}
+
+/// Admin HTTP response (JSON, or CSV for report exports).
+#[derive(Debug, Clone)]
+pub struct AdminResponse {
+    pub status: u16,
+    pub body: Value,
+    pub content_type: &'static str,
+}
+
+impl AdminResponse {
+    pub fn ok(body: Value) -> Self {
+        Self { status: 200, body, content_type: "application/json" }
+    }
+
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self { status, body: json!({ "error": message.into() }), content_type: "application/json" }
+    }
+
+    pub fn csv(text: String) -> Self {
+        Self { status: 200, body: Value::String(text), content_type: "text/csv" }
+    }
+}
+
+/// What routes need besides the request itself.
+#[derive(Clone)]
+pub struct AdminContext {
+    pub config: BridgeConfig,
+    pub runtime: Handle,
+}
+
+impl AdminContext {
+    /// Runs an async route body on the bridge runtime from the admin thread.
+    pub fn block_on<F: std::future::Future>(&self, fut: F) -> F::Output {
+        self.runtime.block_on(fut)
+    }
+
+    pub fn agent(&self) -> Option<&Agent> {
+        ADMIN_AGENT.get()
+    }
+}
+
+/// Starts the operator API on 127.0.0.1 only. Must be called from inside the tokio runtime.
+///
+/// Refuses to start without `BRIDGE_ADMIN_TOKEN`: several routes move funds.
+pub fn start_admin_server(port: u16, config: BridgeConfig) {
+    if get_admin_token().is_none() {
+        bridge_log_event("error", "❌ BRIDGE_ADMIN_TOKEN is not set; admin API disabled".to_string());
+        return;
+    }
+    let ctx = AdminContext { config, runtime: Handle::current() };
+
+    thread::spawn(move || {
+        let listener = match TcpListener::bind(("127.0.0.1", port)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                bridge_log_event("error", format!("❌ Failed to bind admin port {}: {}", port, e));
+                return;
+            }
+        };
+        bridge_log_event("info", format!("✅ Admin API listening on 127.0.0.1:{}", port));
+
+        for stream in listener.incoming().flatten() {
+            let ctx = ctx.clone();
+            thread::spawn(move || handle_connection(stream, &ctx));
+        }
+    });
+}
+
+fn handle_connection(mut stream: TcpStream, ctx: &AdminContext) {
+    let response = match read_request(&stream) {
+        Some(req) => authorize(&req)
+            .or_else(|| reject_browser_request(&req))
+            .unwrap_or_else(|| route_admin_request(&req, ctx)),
+        None => AdminResponse::error(400, "Malformed request"),
+    };
+
+    let body = match &response.body {
+        Value::String(text) if response.content_type != "application/json" => text.clone(),
+        body => body.to_string(),
+    };
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    let http_response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(http_response.as_bytes());
+}
+
+fn read_request(stream: &TcpStream) -> Option<AdminRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_uppercase();
+    let target = parts.next()?;
+
+    let mut content_length = 0usize;
+    let mut token = None;
+    let mut content_type = None;
+    let mut origin = None;
+    let mut approver_token = None;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.trim().parse().ok()?,
+            "authorization" => token = value.trim().strip_prefix("Bearer ").map(str::to_string),
+            "content-type" => {
+                let media_type = value.split(';').next().unwrap_or_default();
+                content_type = Some(media_type.trim().to_ascii_lowercase());
+            }
+            "origin" => origin = Some(value.trim().to_string()),
+            "x-approver-token" => approver_token = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    if content_length > MAX_BODY_BYTES {
+        return None;
+    }
+    let mut raw_body = vec![0u8; content_length];
+    reader.read_exact(&mut raw_body).ok()?;
+    let body = if raw_body.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&raw_body).ok()?
+    };
+
+    let (path, query_string) = target.split_once('?').unwrap_or((target, ""));
+    let query = query_string
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+    Some(AdminRequest {
+        method,
+        path: path.trim_end_matches('/').to_string(),
+        query,
+        token,
+        content_type,
+        origin,
+        approver_token,
+        body,
+    })
+}
+
+/// Returns a 401 response unless the request carries the configured admin token.
+fn authorize(req: &AdminRequest) -> Option<AdminResponse> {
+    match (get_admin_token(), &req.token) {
+        (Some(expected), Some(token)) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => None,
+        _ => Some(AdminResponse::error(401, "Missing or invalid admin token")),
+    }
+}
+
+/// Refuses anything a browser could send cross-site: requests with an `Origin`,
+/// and writes that aren't `application/json` (which forces a CORS preflight).
+fn reject_browser_request(req: &AdminRequest) -> Option<AdminResponse> {
+    if req.origin.is_some() {
+        return Some(AdminResponse::error(403, "Cross-origin requests are not accepted"));
+    }
+    if req.method != "GET" && req.content_type.as_deref() != Some("application/json") {
+        return Some(AdminResponse::error(415, "Expected Content-Type: application/json"));
+    }
+    None
+}
+
+/// Compares two byte strings without exiting early on the first difference.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Routes one admin request.
+pub fn route_admin_request(req: &AdminRequest, ctx: &AdminContext) -> AdminResponse {
+    let segments: Vec<&str> = req.path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (req.method.as_str(), segments.as_slice()) {
+        ("GET", ["mirrors"]) => {
+            let state = match req.query.get("state") {
+                Some(raw) => match serde_json::from_value::<MirrorState>(json!(raw)) {
+                    Ok(state) => Some(state),
+                    Err(_) => return AdminResponse::error(400, format!("Unknown mirror state {}", raw)),
+                },
+                None => None,
+            };
+            AdminResponse::ok(json!(list_mirrors(state)))
+        }
+        ("GET", ["mirrors", "sales", "parked"]) => AdminResponse::ok(json!(list_parked_sales())),
+        ("GET", ["mirrors", "nftoken", nftoken_id]) => match get_mirror_by_nftoken(nftoken_id) {
+            Some(record) => AdminResponse::ok(json!(record)),
+            None => AdminResponse::error(404, format!("No mirror for NFToken {}", nftoken_id)),
+        },
+        ("POST", ["mirrors", "reconcile"]) => {
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            let issues = ctx.block_on(reconcile_mirrors(agent, &ctx.config));
+            AdminResponse::ok(json!({ "discrepancies": issues }))
+        }
+        ("POST", ["mirrors", asset_id, "burn"]) => {
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            let Ok(id) = asset_id.parse::<u128>() else {
+                return AdminResponse::error(400, format!("Invalid asset id {}", asset_id));
+            };
+            let request: BurnRequest = serde_json::from_value(req.body.clone()).unwrap_or_default();
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(burn_xrpl_mirrored_token(agent, &ctx.config, &rpc, Nat::from(id), request)) {
+                Ok(status) => AdminResponse::ok(json!(status)),
+                Err(e) => AdminResponse::error(409, format!("{:?}", e)),
+            }
+        }
+        ("GET", ["mirrors", asset_id]) => match get_mirror(asset_id) {
+            Some(record) => AdminResponse::ok(json!(record)),
+            None => AdminResponse::error(404, format!("No mirror for asset {}", asset_id)),
+        },
+        ("GET", ["iou", "trustlines"]) => AdminResponse::ok(json!(list_trust_lines())),
+        ("GET", ["iou", "redemptions", "parked"]) => AdminResponse::ok(json!(list_parked_redemptions())),
+        ("POST", ["iou", "trustlines", holder, decision @ ("approve" | "reject")]) => {
+            match review_trust_line(holder, *decision == "approve") {
+                Ok(line) => AdminResponse::ok(json!(line)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("POST", ["iou", "issue"]) => {
+            let (Some(icp_ref), Some(destination), Some(units)) = (
+                req.body["icp_ref"].as_str(),
+                req.body["destination"].as_str(),
+                req.body["units"].as_str().and_then(|u| u.parse::<u128>().ok()),
+            ) else {
+                return AdminResponse::error(400, "Expected icp_ref, destination and units (string)");
+            };
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(issue_iou(&rpc, icp_ref, destination, units)) {
+                Ok(record) => AdminResponse::ok(json!(record)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["iou", "supply"]) => {
+            let rpc = XRPLRpcClient::from_env();
+            let report = ctx.block_on(async {
+                let icp_locked = match ctx.agent() {
+                    Some(agent) => fetch_bridged_token_supply(agent, &ctx.config)
+                        .await
+                        .ok()
+                        .and_then(|n| u128::try_from(&n.0).ok()),
+                    None => None,
+                };
+                supply_report(&rpc, icp_locked).await
+            });
+            match report {
+                Ok(report) => AdminResponse::ok(json!(report)),
+                Err(e) => AdminResponse::error(503, e.to_string()),
+            }
+        }
+        ("GET", ["wxrp"]) => AdminResponse::ok(wxrp_summary()),
+        ("POST", ["wxrp", "withdraw"]) => {
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            let request: WithdrawalRequest = match serde_json::from_value(req.body.clone()) {
+                Ok(request) => request,
+                Err(e) => return AdminResponse::error(400, e.to_string()),
+            };
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(withdraw_wrapped_xrp(agent, &ctx.config, &rpc, request)) {
+                Ok(record) => AdminResponse::ok(json!(record)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("POST", ["wxrp", "deposits", tx_hash, "remint"]) => {
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            match ctx.block_on(remint_wrap_deposit(agent, &ctx.config, tx_hash)) {
+                Ok(record) => AdminResponse::ok(json!(record)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("POST", ["wxrp", "withdrawals", withdrawal_id, "retry"]) => {
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(retry_withdrawal_payout(&rpc, withdrawal_id)) {
+                Ok(record) => AdminResponse::ok(json!(record)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("POST", ["swaps", "quote"]) => {
+            let (Some(send), Ok(receive)) = (
+                XRPLAmount::from_json(&req.body["send"]),
+                serde_json::from_value::<Issue>(req.body["receive"].clone()),
+            ) else {
+                return AdminResponse::error(400, "Expected send (amount) and receive (issue)");
+            };
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(quote_swap(&rpc, &send, &receive)) {
+                Ok(quote) => AdminResponse::ok(json!(quote)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("POST", ["swaps"]) => {
+            let request: SwapRequest = match serde_json::from_value(req.body.clone()) {
+                Ok(request) => request,
+                Err(e) => return AdminResponse::error(400, e.to_string()),
+            };
+            let rpc = XRPLRpcClient::from_env();
+            let fill = match ctx.block_on(execute_swap(&rpc, &request)) {
+                Ok(fill) => fill,
+                Err(e) => return AdminResponse::error(409, e.to_string()),
+            };
+            // The swap already happened on-ledger; a failed report is retried in the background.
+            let reported = match ctx.agent() {
+                Some(agent) => match ctx.block_on(report_fill(agent, &ctx.config, &fill)) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        bridge_log_event("error", format!("❌ Swap fill report for {} failed: {}", fill.swap_id, e));
+                        false
+                    }
+                },
+                None => false,
+            };
+            AdminResponse::ok(json!({ "fill": fill, "reported": reported }))
+        }
+        ("GET", ["escrows"]) => AdminResponse::ok(json!(list_escrows())),
+        ("POST", ["escrows", "conditions", deal_id]) => {
+            AdminResponse::ok(json!({ "deal_id": deal_id, "condition": create_deal_condition(deal_id) }))
+        }
+        ("GET", ["escrows", key]) => match get_escrow(key) {
+            Some(record) => AdminResponse::ok(json!(record)),
+            None => AdminResponse::error(404, format!("No escrow {}", key)),
+        },
+        ("POST", ["escrows", key, "cancel"]) => {
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(cancel_escrow(&rpc, key)) {
+                Ok(validated) => AdminResponse::ok(validated),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["paychan"]) => AdminResponse::ok(json!(list_channels())),
+        ("POST", ["paychan", "claims"]) => {
+            let claim: ChannelClaim = match serde_json::from_value(req.body.clone()) {
+                Ok(claim) => claim,
+                Err(e) => return AdminResponse::error(400, e.to_string()),
+            };
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(accept_channel_claim(&rpc, claim)) {
+                Ok(receipt) => AdminResponse::ok(json!(receipt)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["paychan", channel_id]) => match get_channel(channel_id) {
+            Some(record) => AdminResponse::ok(json!(record)),
+            None => AdminResponse::error(404, format!("No payment channel {}", channel_id)),
+        },
+        ("POST", ["paychan", channel_id, "redeem"]) => {
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(redeem_channel(&rpc, channel_id)) {
+                Ok(validated) => AdminResponse::ok(json!({ "redeemed": validated.is_some(), "tx": validated })),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["checks"]) => AdminResponse::ok(json!(list_checks())),
+        ("GET", ["checks", check_id]) => match get_check(check_id) {
+            Some(record) => AdminResponse::ok(json!(record)),
+            None => AdminResponse::error(404, format!("No check {}", check_id)),
+        },
+        ("POST", ["checks", check_id, "approve"]) => match approve_check(check_id) {
+            Ok(record) => AdminResponse::ok(json!(record)),
+            Err(e) => AdminResponse::error(409, e.to_string()),
+        },
+        ("POST", ["checks", check_id, "cash"]) => {
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(cash_check(&rpc, check_id)) {
+                Ok(record) => AdminResponse::ok(json!(record)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["refunds"]) => AdminResponse::ok(json!(list_refunds())),
+        ("GET", ["refunds", tx_hash]) => match get_refund(tx_hash) {
+            Some(record) => AdminResponse::ok(json!(record)),
+            None => AdminResponse::error(404, format!("No refund for {}", tx_hash)),
+        },
+        ("POST", ["refunds", tx_hash, "release"]) => match release_refund(tx_hash) {
+            Ok(record) => AdminResponse::ok(json!(record)),
+            Err(e) => AdminResponse::error(409, e.to_string()),
+        },
+        ("GET", ["intents"]) => AdminResponse::ok(json!(list_payment_intents())),
+        ("POST", ["intents"]) => {
+            let request: IntentRequest = match serde_json::from_value(req.body.clone()) {
+                Ok(request) => request,
+                Err(e) => return AdminResponse::error(400, e.to_string()),
+            };
+            match create_payment_intent(request) {
+                Ok(intent) => AdminResponse::ok(json!(intent)),
+                Err(e) => AdminResponse::error(400, e.to_string()),
+            }
+        }
+        ("GET", ["intents", intent_id]) => match get_payment_intent(intent_id) {
+            Some(intent) => AdminResponse::ok(json!(intent)),
+            None => AdminResponse::error(404, format!("No intent {}", intent_id)),
+        },
+        ("POST", ["intents", intent_id, "cancel"]) => match cancel_payment_intent(intent_id) {
+            Ok(intent) => AdminResponse::ok(json!(intent)),
+            Err(e) => AdminResponse::error(409, e.to_string()),
+        },
+        ("GET", ["links"]) => AdminResponse::ok(json!(list_account_links())),
+        ("POST", ["links", "challenges"]) => {
+            let Some(principal) = req.body["principal"].as_str() else {
+                return AdminResponse::error(400, "Missing principal");
+            };
+            match issue_link_challenge(principal) {
+                Ok(challenge) => AdminResponse::ok(json!(challenge)),
+                Err(e) => AdminResponse::error(400, e.to_string()),
+            }
+        }
+        ("POST", ["links"]) => {
+            let proof: LinkProof = match serde_json::from_value(req.body.clone()) {
+                Ok(proof) => proof,
+                Err(e) => return AdminResponse::error(400, e.to_string()),
+            };
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(link_account(agent, &ctx.config, &rpc, proof)) {
+                Ok(link) => AdminResponse::ok(json!(link)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["links", account]) => match get_account_link(account) {
+            Some(link) => AdminResponse::ok(json!(link)),
+            None => AdminResponse::error(404, format!("No link for {}", account)),
+        },
+        ("GET", ["screening"]) => AdminResponse::ok(screening_summary()),
+        ("POST", ["screening", "reload"]) => AdminResponse::ok(reload_screening_lists()),
+        ("GET", ["quarantine"]) => {
+            let status = match req.query.get("status") {
+                Some(raw) => match serde_json::from_value::<QuarantineStatus>(json!(raw)) {
+                    Ok(status) => Some(status),
+                    Err(_) => return AdminResponse::error(400, format!("Unknown quarantine status {}", raw)),
+                },
+                None => None,
+            };
+            AdminResponse::ok(json!(list_quarantined(status)))
+        }
+        ("GET", ["quarantine", tx_hash]) => match get_quarantined(tx_hash) {
+            Some(record) => AdminResponse::ok(json!(record)),
+            None => AdminResponse::error(404, format!("Nothing quarantined for {}", tx_hash)),
+        },
+        ("POST", ["quarantine", tx_hash, decision @ ("release" | "reject")]) => {
+            let Some(reviewer) = req.body["reviewer"].as_str().filter(|r| !r.is_empty()) else {
+                return AdminResponse::error(400, "Missing reviewer");
+            };
+            let note = req.body["note"].as_str().map(str::to_string);
+            let result = if *decision == "release" {
+                release_quarantined(tx_hash, reviewer, note)
+            } else {
+                reject_quarantined(tx_hash, reviewer, note)
+            };
+            match result {
+                Ok(record) => AdminResponse::ok(json!(record)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["limits"]) => AdminResponse::ok(json!(list_limit_rules())),
+        ("POST", ["limits", "reload"]) => match reload_limit_rules() {
+            Ok(rules) => AdminResponse::ok(json!(rules)),
+            Err(e) => AdminResponse::error(400, e),
+        },
+        ("GET", ["limits", "usage", key]) => AdminResponse::ok(json!(limit_usage(key))),
+        ("GET", ["approvals"]) => {
+            let status = match req.query.get("status") {
+                Some(raw) => match serde_json::from_value::<HeldStatus>(json!(raw)) {
+                    Ok(status) => Some(status),
+                    Err(_) => return AdminResponse::error(400, format!("Unknown held status {}", raw)),
+                },
+                None => None,
+            };
+            AdminResponse::ok(json!(list_held_actions(status)))
+        }
+        ("GET", ["approvals", tx_hash]) => match get_held_action(tx_hash) {
+            Some(held) => AdminResponse::ok(json!(held)),
+            None => AdminResponse::error(404, format!("Nothing held for {}", tx_hash)),
+        },
+        ("POST", ["approvals", tx_hash, decision @ ("approve" | "reject")]) => {
+            let Some(approver_token) = req.approver_token.as_deref().filter(|t| !t.is_empty()) else {
+                return AdminResponse::error(401, "Missing X-Approver-Token");
+            };
+            let result = if *decision == "approve" {
+                approve_held_action(tx_hash, approver_token)
+            } else {
+                reject_held_action(tx_hash, approver_token)
+            };
+            match result {
+                Ok(held) => AdminResponse::ok(json!(held)),
+                Err(e) => AdminResponse::error(409, e.to_string()),
+            }
+        }
+        ("GET", ["pause"]) => AdminResponse::ok(json!(pause_status())),
+        ("POST", ["pause"]) => {
+            let Some(flow) = req.body["flow"].as_str().and_then(PauseFlow::parse) else {
+                return AdminResponse::error(400, "flow must be global, inbound, outbound or action:<Type>");
+            };
+            let Some(operator) = req.body["operator"].as_str().filter(|o| !o.is_empty()) else {
+                return AdminResponse::error(400, "Missing operator");
+            };
+            // `paused: null` clears the override and follows the canister again.
+            let paused = match &req.body["paused"] {
+                Value::Bool(paused) => Some(*paused),
+                Value::Null => None,
+                _ => return AdminResponse::error(400, "paused must be true, false or null"),
+            };
+            AdminResponse::ok(json!(set_pause_override(&flow, paused, operator)))
+        }
+        ("GET", ["params"]) => AdminResponse::ok(json!(parameter_status())),
+        ("POST", ["params", "sync"]) => {
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            AdminResponse::ok(json!(ctx.block_on(sync_parameters(agent, &ctx.config))))
+        }
+        ("GET", ["reserves"]) => match latest_attestation() {
+            Some(attestation) => AdminResponse::ok(json!(attestation)),
+            None => AdminResponse::error(404, "No reserve attestation yet"),
+        },
+        ("POST", ["reserves", "attest"]) => {
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(attest_reserves(agent, &ctx.config, &rpc)) {
+                Ok(attestation) => AdminResponse::ok(json!(attestation)),
+                Err(e) => AdminResponse::error(503, e.to_string()),
+            }
+        }
+        ("GET", ["reconciliation"]) => match latest_reconciliation() {
+            Some(report) => reconciliation_response(req, &report),
+            None => AdminResponse::error(404, "No reconciliation report yet"),
+        },
+        ("GET", ["reconciliation", report_id]) => match get_reconciliation(report_id) {
+            Some(report) => reconciliation_response(req, &report),
+            None => AdminResponse::error(404, format!("Unknown reconciliation report {}", report_id)),
+        },
+        ("POST", ["reconciliation"]) => {
+            let Some(agent) = ctx.agent() else {
+                return AdminResponse::error(503, "IC agent not ready");
+            };
+            let range: ReconRange = match serde_json::from_value(req.body.clone()) {
+                Ok(range) => range,
+                Err(e) => return AdminResponse::error(400, format!("Invalid range: {}", e)),
+            };
+            let rpc = XRPLRpcClient::from_env();
+            match ctx.block_on(reconcile(agent, &ctx.config, &rpc, range)) {
+                Ok(report) => reconciliation_response(req, &report),
+                Err(e) => AdminResponse::error(503, e.to_string()),
+            }
+        }
+        _ => AdminResponse::error(404, format!("No route for {} {}", req.method, req.path)),
+    }
+}
+
+/// A reconciliation report as JSON, or its discrepancies as CSV with `?format=csv`.
+fn reconciliation_response(req: &AdminRequest, report: &ReconciliationReport) -> AdminResponse {
+    match req.query.get("format").map(String::as_str) {
+        Some("csv") => AdminResponse::csv(report_to_csv(report)),
+        _ => AdminResponse::ok(json!(report)),
+    }
+}
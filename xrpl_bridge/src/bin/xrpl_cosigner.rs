@@ -0,0 +1,32 @@
+use std::error::Error;
+use std::time::Duration;
+
+use tokio::time;
+use namora_bridge::log::bridge_log_event;
+use namora_bridge::xrpl::multisig::cosign_pending_requests;
+use namora_bridge::xrpl::rpc::XRPLRpcClient;
+
+/// ✍️ Co-signer process for the multisigned bridge wallet.
+/// Watches the shared drop directory (`XRPL_COSIGN_DIR`) and answers each request with a
+/// `sign_for` signature from this signer's key, using its own node (`XRPL_RPC_URL`).
+/// Only requests signed with the bridge's request key (`XRPL_COSIGN_REQUEST_PUBKEY`) that
+/// pass this signer's own policy checks (`COSIGNER_MAX_DROPS`, `COSIGNER_MAX_IOU_VALUE`,
+/// screening deny list) are signed.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let account = std::env::var("COSIGNER_ACCOUNT")?;
+    let secret = std::env::var("COSIGNER_SECRET")?;
+    let rpc = XRPLRpcClient::from_env();
+
+    bridge_log_event("startup", format!("🚀 Co-signer {} watching for requests...", account));
+
+    loop {
+        match cosign_pending_requests(&rpc, &account, &secret).await {
+            Ok(0) => {}
+            Ok(n) => bridge_log_event("multisig", format!("Signed {} request(s)", n)),
+            Err(e) => bridge_log_event("error", format!("❌ Co-signing failed: {}", e)),
+        }
+
+        time::sleep(Duration::from_secs(5)).await;
+    }
+}
@@ -7,7 +7,7 @@ pub fn get_bridge_address() -> Option<String> {
     env::var("XRPL_BRIDGE_ADDRESS").ok()
 }
 
-/// Gets the default minimum tip amount in drops
+/// Gets the default minimum tip amount in drops (until governed parameters are applied)
 pub fn get_minimum_tip_drops() -> u64 {
     env::var("MIN_TIP_DROPS")
         .ok()
@@ -15,6 +15,387 @@ pub fn get_minimum_tip_drops() -> u64 {
         .unwrap_or(1000) // fallback default
 }
 
+/// Whether the bridge runs on XRPL mainnet (`XRPL_NETWORK=mainnet`); anything else is a test network
+pub fn get_xrpl_mainnet() -> bool {
+    env::var("XRPL_NETWORK")
+        .map(|val| val.eq_ignore_ascii_case("mainnet"))
+        .unwrap_or(false)
+}
+
+/// Gets the XRPL JSON-RPC endpoint used for account queries and submission.
+/// Signing is delegated to this node, so signing requests are refused unless it is on loopback.
+pub fn get_xrpl_rpc_url() -> String {
+    env::var("XRPL_RPC_URL").unwrap_or_else(|_| "https://s.altnet.rippletest.net:51234".to_string())
+}
+
+/// Gets the XRPL WebSocket endpoint the bridge subscribes to for validated transactions
+pub fn get_xrpl_ws_url() -> String {
+    env::var("XRPL_WS_URL").unwrap_or_else(|_| "wss://s.altnet.rippletest.net:51233".to_string())
+}
+
+/// Gets the bridge wallet secret used to sign outbound transactions (from env)
+pub fn get_bridge_secret() -> Option<String> {
+    env::var("XRPL_BRIDGE_SECRET").ok()
+}
+
+/// Number of XRPL Tickets the allocator tries to keep in reserve
+pub fn get_ticket_target() -> u32 {
+    env::var("XRPL_TICKET_TARGET")
+        .ok()
+        .and_then(|val| val.parse::<u32>().ok())
+        .unwrap_or(20)
+        .min(250) // XRPL limit on tickets per account
+}
+
+/// Below this many free Tickets the allocator requests a `TicketCreate` top-up
+pub fn get_ticket_low_watermark() -> u32 {
+    env::var("XRPL_TICKET_LOW_WATERMARK")
+        .ok()
+        .and_then(|val| val.parse::<u32>().ok())
+        .unwrap_or(5)
+}
+
+/// Slippage (basis points) allowed on swaps paid in through SWAP memos
+pub fn get_swap_slippage_bps() -> u32 {
+    env::var("SWAP_SLIPPAGE_BPS")
+        .ok()
+        .and_then(|val| val.parse::<u32>().ok())
+        .unwrap_or(100)
+}
+
+/// Upper bound on the `Fee` (in drops) the bridge will ever pay for one transaction
+pub fn get_max_fee_drops() -> u64 {
+    env::var("XRPL_MAX_FEE_DROPS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(2000)
+}
+
+/// One entry of the bridge account's SignerList.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerEntryConfig {
+    pub account: String,
+    pub weight: u16,
+}
+
+/// Parses `XRPL_SIGNER_LIST` ("rSigner1:1,rSigner2:2,...", weight defaults to 1)
+pub fn get_signer_list() -> Vec<SignerEntryConfig> {
+    env::var("XRPL_SIGNER_LIST")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(2, ':');
+            let account = parts.next()?.to_string();
+            let weight = parts.next().map(|w| w.parse().ok()).unwrap_or(Some(1))?;
+            Some(SignerEntryConfig { account, weight })
+        })
+        .collect()
+}
+
+/// Total signer weight required to authorize a multisigned transaction
+pub fn get_signer_quorum() -> Option<u32> {
+    env::var("XRPL_SIGNER_QUORUM").ok().and_then(|val| val.parse::<u32>().ok())
+}
+
+/// Outbound XRP transfers above this many drops must take the multisig path
+pub fn get_multisig_threshold_drops() -> Option<u64> {
+    env::var("XRPL_MULTISIG_THRESHOLD_DROPS").ok().and_then(|val| val.parse::<u64>().ok())
+}
+
+/// Directory shared with co-signer processes for the file-drop signing protocol
+pub fn get_cosign_dir() -> String {
+    env::var("XRPL_COSIGN_DIR").unwrap_or_else(|_| ".persistent/cosign/".to_string())
+}
+
+/// Hex Ed25519 seed the bridge signs co-signing requests with (`XRPL_COSIGN_REQUEST_KEY`)
+pub fn get_cosign_request_key() -> Option<String> {
+    env::var("XRPL_COSIGN_REQUEST_KEY").ok().filter(|k| !k.is_empty())
+}
+
+/// Hex Ed25519 public key co-signers accept requests from (`XRPL_COSIGN_REQUEST_PUBKEY`)
+pub fn get_cosign_request_pubkey() -> Option<String> {
+    env::var("XRPL_COSIGN_REQUEST_PUBKEY").ok().filter(|k| !k.is_empty())
+}
+
+/// Largest XRP amount (drops) a co-signer will approve in one transaction (unset = no cap)
+pub fn get_cosigner_max_drops() -> Option<u64> {
+    env::var("COSIGNER_MAX_DROPS").ok().and_then(|val| val.parse::<u64>().ok())
+}
+
+/// Largest IOU value a co-signer will approve per value field; unset = refuse IOU amounts
+pub fn get_cosigner_max_iou_value() -> Option<f64> {
+    env::var("COSIGNER_MAX_IOU_VALUE").ok().and_then(|val| val.parse::<f64>().ok()).filter(|v| v.is_finite())
+}
+
+/// Whether the bridge wallet acts as issuer of the Axia IOU
+pub fn get_iou_enabled() -> bool {
+    env::var("XRPL_IOU_ENABLED")
+        .map(|val| val == "true")
+        .unwrap_or(false)
+}
+
+/// Currency code of the Axia IOU issued by the bridge wallet
+pub fn get_iou_currency() -> String {
+    env::var("XRPL_IOU_CURRENCY").unwrap_or_else(|_| "AXI".to_string())
+}
+
+/// Decimal places between one IOU and the ICP token's smallest unit
+pub fn get_iou_decimals() -> u32 {
+    env::var("XRPL_IOU_DECIMALS")
+        .ok()
+        .and_then(|val| val.parse::<u32>().ok())
+        .unwrap_or(8)
+        .min(30)
+}
+
+/// Authorize new trust lines to the Axia IOU without operator approval
+pub fn get_iou_auto_authorize() -> bool {
+    env::var("XRPL_IOU_AUTO_AUTHORIZE")
+        .map(|val| val == "true")
+        .unwrap_or(false)
+}
+
+/// Cash Checks to the bridge without operator approval
+pub fn get_check_auto_cash() -> bool {
+    env::var("XRPL_CHECK_AUTO_CASH")
+        .map(|val| val != "false")
+        .unwrap_or(true)
+}
+
+/// Checks above this many drops wait for operator approval even with auto-cash on
+pub fn get_check_auto_cash_max_drops() -> Option<u64> {
+    env::var("XRPL_CHECK_AUTO_CASH_MAX_DROPS").ok().and_then(|val| val.parse::<u64>().ok())
+}
+
+/// Automatically return unroutable payments to their sender
+pub fn get_refunds_enabled() -> bool {
+    env::var("REFUNDS_ENABLED")
+        .map(|val| val != "false")
+        .unwrap_or(true)
+}
+
+/// Drops kept from each refund to cover the network fee and discourage griefing
+pub fn get_refund_fee_drops() -> u64 {
+    env::var("REFUND_FEE_DROPS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(1000)
+}
+
+/// Refunds a single sender may receive per `REFUND_WINDOW_SECS`
+pub fn get_refund_max_per_sender() -> usize {
+    env::var("REFUND_MAX_PER_SENDER")
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(3)
+}
+
+pub fn get_refund_window_secs() -> u64 {
+    env::var("REFUND_WINDOW_SECS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(86_400)
+}
+
+/// Deny list of XRPL accounts (CSV or JSON); matching transactions are quarantined
+pub fn get_screening_deny_list() -> Option<String> {
+    env::var("SCREENING_DENY_LIST").ok().filter(|p| !p.is_empty())
+}
+
+/// Accounts exempt from canister screening (CSV or JSON); the deny list still applies
+pub fn get_screening_allow_list() -> Option<String> {
+    env::var("SCREENING_ALLOW_LIST").ok().filter(|p| !p.is_empty())
+}
+
+/// Also screen counterparties with the compliance canister
+pub fn get_screening_use_canister() -> bool {
+    env::var("SCREENING_USE_CANISTER")
+        .map(|val| val == "true")
+        .unwrap_or(false)
+}
+
+/// JSON file of velocity/AML limit rules; no file means no limits
+pub fn get_limit_rules_file() -> Option<String> {
+    env::var("LIMIT_RULES_FILE").ok().filter(|p| !p.is_empty())
+}
+
+/// Actions crediting more than this many drops wait for approval
+pub fn get_approval_threshold_drops() -> Option<u64> {
+    env::var("APPROVAL_THRESHOLD_DROPS").ok().and_then(|val| val.parse::<u64>().ok())
+}
+
+/// Redemptions (TokenSwap) of more than this many IOU units wait for approval
+pub fn get_approval_threshold_iou_units() -> Option<u64> {
+    env::var("APPROVAL_THRESHOLD_IOU_UNITS").ok().and_then(|val| val.parse::<u64>().ok())
+}
+
+/// An approver allowed to vote through the admin API, identified by their own token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApproverConfig {
+    pub name: String,
+    pub token: String,
+}
+
+/// Parses `APPROVAL_APPROVERS` ("alice:<token>,bob:<token>"); entries without a token are ignored.
+/// With no approvers configured, held actions can only be decided through HIL.
+pub fn get_approval_approvers() -> Vec<ApproverConfig> {
+    env::var("APPROVAL_APPROVERS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let (name, token) = entry.trim().split_once(':')?;
+            let (name, token) = (name.trim(), token.trim());
+            if name.is_empty() || token.is_empty() {
+                return None;
+            }
+            Some(ApproverConfig { name: name.to_string(), token: token.to_string() })
+        })
+        .collect()
+}
+
+/// Distinct local approvals needed to release a held action (capped at the approver count)
+pub fn get_approval_quorum() -> usize {
+    let quorum = env::var("APPROVAL_QUORUM")
+        .ok()
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+    match get_approval_approvers().len() {
+        0 => quorum,
+        approvers => quorum.min(approvers),
+    }
+}
+
+/// Held actions nobody decides on expire (and are refunded) after this long
+pub fn get_approval_ttl_secs() -> u64 {
+    env::var("APPROVAL_TTL_SECS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(86_400)
+}
+
+/// Also submit held actions to the HIL canister and accept its decisions
+pub fn get_hil_approvals_enabled() -> bool {
+    env::var("HIL_APPROVALS_ENABLED")
+        .map(|val| val == "true")
+        .unwrap_or(false)
+}
+
+/// Query method on the pause canister; takes a module name and returns `?EmergencyState` (admin2's `getEmergency`)
+pub fn get_pause_method() -> String {
+    env::var("PAUSE_METHOD").unwrap_or_else(|_| "getEmergency".to_string())
+}
+
+/// Module name prefix the bridge's pause flags are kept under (`<prefix>`, `<prefix>.inbound`, ...)
+pub fn get_pause_module() -> String {
+    env::var("PAUSE_MODULE").unwrap_or_else(|_| "xrpl_bridge".to_string())
+}
+
+/// Seconds between pause flag polls
+pub fn get_pause_poll_secs() -> u64 {
+    env::var("PAUSE_POLL_SECS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(15)
+        .max(1)
+}
+
+/// Query method on the governance canister; takes (module, key) and returns `?Config` (admin2's `getConfig`)
+pub fn get_params_method() -> String {
+    env::var("PARAMS_METHOD").unwrap_or_else(|_| "getConfig".to_string())
+}
+
+/// Module the governed parameter set is published under
+pub fn get_params_module() -> String {
+    env::var("PARAMS_MODULE").unwrap_or_else(|_| "xrpl_bridge".to_string())
+}
+
+/// Key of the governed parameter set within its module
+pub fn get_params_key() -> String {
+    env::var("PARAMS_KEY").unwrap_or_else(|_| "parameters".to_string())
+}
+
+/// Seconds between governed parameter syncs
+pub fn get_params_sync_secs() -> u64 {
+    env::var("PARAMS_SYNC_SECS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(60)
+        .max(1)
+}
+
+/// Seconds between proof-of-reserves attestations
+pub fn get_reserves_interval_secs() -> u64 {
+    env::var("RESERVES_INTERVAL_SECS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(3600)
+        .max(60)
+}
+
+/// Shortfall (in basis points of the liability) tolerated before a reserve alert
+pub fn get_reserves_tolerance_bps() -> u64 {
+    env::var("RESERVES_TOLERANCE_BPS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(10)
+        .min(10_000)
+}
+
+/// PEM of the identity that signs attestations (the bridge's IC identity by default)
+pub fn get_reserves_signing_pem() -> String {
+    env::var("RESERVES_SIGNING_PEM").unwrap_or_else(|_| "identity.pem".to_string())
+}
+
+/// Whether attestations are published to `RESERVES_CANISTER_ID` (`namora_ai` or `treasury`)
+pub fn get_reserves_publish() -> bool {
+    env::var("RESERVES_PUBLISH")
+        .map(|val| val == "true")
+        .unwrap_or(false)
+}
+
+/// Update method the attestation is published with
+pub fn get_reserves_publish_method() -> String {
+    env::var("RESERVES_PUBLISH_METHOD").unwrap_or_else(|_| "recordReserveAttestation".to_string())
+}
+
+/// Seconds between scheduled reconciliation runs
+pub fn get_recon_interval_secs() -> u64 {
+    env::var("RECON_INTERVAL_SECS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(3600)
+        .max(60)
+}
+
+/// Seconds a deposit is given to reach ICP before reconciliation expects its credit
+pub fn get_recon_settle_secs() -> u64 {
+    env::var("RECON_SETTLE_SECS")
+        .ok()
+        .and_then(|val| val.parse::<u64>().ok())
+        .unwrap_or(900)
+}
+
+/// Query method on the payment log canister; takes (from, to) in nanoseconds and returns its records
+pub fn get_recon_payment_log_method() -> String {
+    env::var("RECON_PAYMENT_LOG_METHOD").unwrap_or_else(|_| "getPaymentsInRange".to_string())
+}
+
+/// Port for the local (127.0.0.1) admin API
+pub fn get_admin_port() -> u16 {
+    env::var("ADMIN_PORT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(8081)
+}
+
+/// Bearer token required by the admin API; the API does not start without one
+pub fn get_admin_token() -> Option<String> {
+    env::var("BRIDGE_ADMIN_TOKEN").ok().filter(|t| !t.is_empty())
+}
+
 /// Holds bridge-related canister IDs loaded at runtime (e.g. from env).
 #[derive(Debug, Clone)]
 pub struct BridgeConfig {
@@ -23,6 +404,18 @@ pub struct BridgeConfig {
     pub token_swap_canister_id: String,
     pub tip_handler_canister_id: String,
     pub nft_sale_handler_canister_id: String,
+    pub asset_registry_canister_id: String,
+    pub wxrp_ledger_canister_id: String,
+    pub escrow_canister_id: String,
+    pub identity_canister_id: String,
+    pub compliance_canister_id: String,
+    pub hil_canister_id: String,
+    /// Canister holding the bridge's pause flags (e.g. `admin2` or `governance`).
+    pub pause_canister_id: String,
+    /// Canister publishing the governed parameter set.
+    pub governance_canister_id: String,
+    /// Canister reserve attestations are published to (`namora_ai` or `treasury`).
+    pub reserves_canister_id: String,
     // Add more as needed later
 }
 
@@ -44,12 +437,48 @@ impl BridgeConfig {
         let nft_sale_handler_canister_id = std::env::var("NFT_SALE_HANDLER_CANISTER_ID")
             .unwrap_or_else(|_| "eeeee-ee".to_string());
 
+        let asset_registry_canister_id = std::env::var("ASSET_REGISTRY_CANISTER_ID")
+            .unwrap_or_else(|_| "fffff-ff".to_string());
+
+        let wxrp_ledger_canister_id = std::env::var("WXRP_LEDGER_CANISTER_ID")
+            .unwrap_or_else(|_| "ggggg-gg".to_string());
+
+        let escrow_canister_id = std::env::var("ESCROW_CANISTER_ID")
+            .unwrap_or_else(|_| "hhhhh-hh".to_string());
+
+        let identity_canister_id = std::env::var("IDENTITY_CANISTER_ID")
+            .unwrap_or_else(|_| "iiiii-ii".to_string());
+
+        let compliance_canister_id = std::env::var("COMPLIANCE_CANISTER_ID")
+            .unwrap_or_else(|_| "jjjjj-jj".to_string());
+
+        let hil_canister_id = std::env::var("HIL_CANISTER_ID")
+            .unwrap_or_else(|_| "kkkkk-kk".to_string());
+
+        let pause_canister_id = std::env::var("PAUSE_CANISTER_ID")
+            .unwrap_or_else(|_| "lllll-ll".to_string());
+
+        let governance_canister_id = std::env::var("GOVERNANCE_CANISTER_ID")
+            .unwrap_or_else(|_| "mmmmm-mm".to_string());
+
+        let reserves_canister_id = std::env::var("RESERVES_CANISTER_ID")
+            .unwrap_or_else(|_| "nnnnn-nn".to_string());
+
         BridgeConfig {
             nft_canister_id,
             payment_log_canister_id,
             token_swap_canister_id,
             tip_handler_canister_id,
             nft_sale_handler_canister_id,
+            asset_registry_canister_id,
+            wxrp_ledger_canister_id,
+            escrow_canister_id,
+            identity_canister_id,
+            compliance_canister_id,
+            hil_canister_id,
+            pause_canister_id,
+            governance_canister_id,
+            reserves_canister_id,
         }
     }
 }
@@ -1,3 +1,6 @@
+// C callers own the pointers handed to these entry points.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
 use std::ffi::{CStr,};
 use std::os::raw::c_char;
 
@@ -7,6 +10,7 @@ use crate::monitor::get_bridge_status;
 
 use crate::xrpl::types::{ XRPLSubmitResult};
 use crate::xrpl::client::submit_raw_xrpl_tx;
+use crate::xrpl::payment_request::build_tip_payment_request;
 
 
 #[no_mangle]
@@ -18,14 +22,18 @@ pub extern "C" fn rust_tip_artist_via_xrpl(json_payload: *const c_char) -> *mut
         CStr::from_ptr(json_payload).to_string_lossy().into_owned()
     };
 
-    match parse_tip_request(&json_str) {
-        Ok(_req) => {
-            // Simulate tip logic: (in real case you'd handle the tx and queue)
-            let mock_tx_hash = "ABC123TIP";
-            let response = format!(r#"{{"status":"ok","tx_hash":"{}"}}"#, mock_tx_hash);
-            to_c_char(&response)
+    // The bridge never holds the tipper's keys: return a request for their wallet to sign.
+    let request = match parse_tip_request(&json_str).and_then(|req| build_tip_payment_request(&req)) {
+        Ok(request) => request,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    match serde_json::to_value(&request) {
+        Ok(mut response) => {
+            response["status"] = serde_json::json!("ok");
+            to_c_char(&response.to_string())
         }
-        Err(e) => to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+        Err(_) => to_c_char("{\"error\": \"Failed to serialize payment request\"}"),
     }
 }
 
@@ -88,6 +96,137 @@ pub extern "C" fn rust_get_failed_actions() -> *mut c_char {
     })
 }
 
+#[no_mangle]
+pub extern "C" fn rust_get_fee_records() -> *mut c_char {
+    execute_async(async move {
+        use crate::xrpl::fees::load_fee_records;
+        let records = load_fee_records().unwrap_or_else(|_| vec![]);
+        let json = serde_json::to_string(&records).map_err(|e| e.to_string())?;
+        Ok(json)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn rust_get_mirror_status(asset_id: *const c_char) -> *mut c_char {
+    let asset_id = match parse_c_string(asset_id) {
+        Ok(s) => s,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    match crate::state::mirror_registry::get_mirror(&asset_id) {
+        Some(record) => to_c_char(&serde_json::to_string(&record).unwrap_or_else(|_| "{}".to_string())),
+        None => to_c_char(&format!(r#"{{"error":"No mirror for asset {}"}}"#, asset_id)),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_get_mirror_by_nftoken(nftoken_id: *const c_char) -> *mut c_char {
+    let nftoken_id = match parse_c_string(nftoken_id) {
+        Ok(s) => s,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    match crate::state::mirror_registry::get_mirror_by_nftoken(&nftoken_id) {
+        Some(record) => to_c_char(&serde_json::to_string(&record).unwrap_or_else(|_| "{}".to_string())),
+        None => to_c_char(&format!(r#"{{"error":"No mirror for NFToken {}"}}"#, nftoken_id)),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_list_mirrors() -> *mut c_char {
+    let records = crate::state::mirror_registry::list_mirrors(None);
+    match serde_json::to_string(&records) {
+        Ok(json) => to_c_char(&json),
+        Err(_) => to_c_char("{\"error\": \"Failed to serialize mirrors\"}"),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_submit_channel_claim(claim_json: *const c_char) -> *mut c_char {
+    let raw = match parse_c_string(claim_json) {
+        Ok(s) => s,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    execute_async(async move {
+        use crate::xrpl::paychan::{accept_channel_claim, ChannelClaim};
+        use crate::xrpl::rpc::XRPLRpcClient;
+
+        let claim: ChannelClaim = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+        let receipt = accept_channel_claim(&XRPLRpcClient::from_env(), claim)
+            .await
+            .map_err(|e| e.to_string())?;
+        serde_json::to_string(&receipt).map_err(|e| e.to_string())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn rust_create_payment_intent(request_json: *const c_char) -> *mut c_char {
+    let raw = match parse_c_string(request_json) {
+        Ok(s) => s,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    let request: crate::xrpl::intents::IntentRequest = match serde_json::from_str(&raw) {
+        Ok(request) => request,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    match crate::xrpl::intents::create_payment_intent(request) {
+        Ok(intent) => to_c_char(&serde_json::to_string(&intent).unwrap_or_else(|_| "{}".to_string())),
+        Err(e) => to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_get_payment_intent(intent_id: *const c_char) -> *mut c_char {
+    let intent_id = match parse_c_string(intent_id) {
+        Ok(s) => s,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    match crate::xrpl::intents::get_payment_intent(&intent_id) {
+        Some(intent) => to_c_char(&serde_json::to_string(&intent).unwrap_or_else(|_| "{}".to_string())),
+        None => to_c_char(&format!(r#"{{"error":"No intent {}"}}"#, intent_id)),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_request_account_link(principal: *const c_char) -> *mut c_char {
+    let principal = match parse_c_string(principal) {
+        Ok(s) => s,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    match crate::xrpl::linking::issue_link_challenge(&principal) {
+        Ok(challenge) => to_c_char(&serde_json::to_string(&challenge).unwrap_or_else(|_| "{}".to_string())),
+        Err(e) => to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rust_submit_account_link(proof_json: *const c_char) -> *mut c_char {
+    let raw = match parse_c_string(proof_json) {
+        Ok(s) => s,
+        Err(e) => return to_c_char(&format!(r#"{{"error":"{}"}}"#, e)),
+    };
+
+    execute_async(async move {
+        use crate::config::BridgeConfig;
+        use crate::ic_trigger::create_agent_from_env;
+        use crate::xrpl::linking::{link_account, LinkProof};
+        use crate::xrpl::rpc::XRPLRpcClient;
+
+        let proof: LinkProof = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+        let agent = create_agent_from_env().await.map_err(|e| e.to_string())?;
+        let config = BridgeConfig::load();
+        let link = link_account(&agent, &config, &XRPLRpcClient::from_env(), proof)
+            .await
+            .map_err(|e| e.to_string())?;
+        serde_json::to_string(&link).map_err(|e| e.to_string())
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn rust_reset_bridge_state() {
     use crate::state::memory::{clear_queue, clear_verified, reset_metrics};
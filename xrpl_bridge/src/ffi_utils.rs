@@ -11,6 +11,7 @@ pub fn parse_tip_request(json: &str) -> Result<TipRequest, String> {
     serde_json::from_str(json).map_err(|e| e.to_string())
 }
 
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub fn parse_c_string(ptr: *const c_char) -> Result<String, String> {
     if ptr.is_null() {
         return Err("Null pointer received".into());
@@ -1,5 +1,5 @@
-/// 🌉 Namora Bridge FFI Interface
-/// Enhanced FFI interface for full IC canister integration
+//! 🌉 Namora Bridge FFI Interface
+//! Enhanced FFI interface for full IC canister integration
 
 use std::os::raw::c_char;
 
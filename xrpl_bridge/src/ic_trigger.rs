@@ -1,11 +1,24 @@
 use ic_agent::{Agent, Identity};
-use candid::{Nat, Encode, Decode, Principal};
+use candid::{Int, Nat, Encode, Decode, Principal};
 use anyhow::Result;
 use std::sync::Arc;
-use crate::state::queue::{PendingAction};
-
+use crate::state::queue::{enqueue_action, NFTSaleDetails, PendingAction};
+
+use crate::xrpl::approvals::HILApprovalRequest;
+use crate::xrpl::escrow::{mark_escrow_registered, EscrowDealStatus, XRPLEscrow};
+use crate::xrpl::linking::XRPLAccountLinkProof;
+use crate::xrpl::params::GovernedConfig;
+use crate::xrpl::reconciliation::PaymentLogRecord;
+use crate::xrpl::pause::EmergencyFlag;
+use crate::xrpl::reserves::PublishedAttestation;
+use crate::xrpl::screening::SanctionsStatus;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::swap::{execute_swap, refund_unfilled_swap, report_fill, SwapFill};
 use crate::xrpl::types::ParsedMemo;
 use crate::config::BridgeConfig;
+use crate::log::bridge_log_event;
+use candid::CandidType;
+use serde::Deserialize;
 
 #[derive(Debug)]
 pub enum TriggerError {
@@ -58,6 +71,91 @@ pub async fn mirror_nft_to_xrpl(
     }
 }
 
+/// Releases the ICP original once its XRPL mirror has been burned.
+pub async fn unmirror_nft_on_icp(
+    agent: &Agent,
+    config: &BridgeConfig,
+    nft_id: Nat,
+) -> Result<()> {
+    let canister_id = Principal::from_text(&config.nft_canister_id)?;
+    let args = Encode!(&nft_id)?;
+
+    let response = agent
+        .update(&canister_id, "markAsUnmirrored")
+        .with_arg(args)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<(), String> = Decode!(&response, Result::<(), String>)?;
+
+    match result {
+        Ok(_) => {
+            bridge_log_event("mirror", format!("🔓 NFT unlocked on ICP: {}", nft_id));
+            Ok(())
+        }
+        Err(e) => Err(anyhow::anyhow!("❌ Failed to unmirror NFT: {}", e)),
+    }
+}
+
+/// Subset of the `nft` canister's `NFT` record the bridge reads.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct AxiaNFT {
+    pub id: Nat,
+    pub creator: Principal,
+    pub owner: Principal,
+    #[serde(rename = "isActive")]
+    pub is_active: bool,
+}
+
+/// Subset of the `asset_registry` canister's `Asset` record the bridge reads.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct AxiaAsset {
+    pub id: Nat,
+    #[serde(rename = "nftId")]
+    pub nft_id: Nat,
+    #[serde(rename = "ownerIdentity")]
+    pub owner_identity: Principal,
+    #[serde(rename = "isActive")]
+    pub is_active: bool,
+}
+
+/// Fetches an NFT from the configured NFT canister (`getNFT`).
+pub async fn fetch_axia_nft(
+    agent: &Agent,
+    config: &BridgeConfig,
+    nft_id: Nat,
+) -> Result<AxiaNFT> {
+    let canister_id = Principal::from_text(&config.nft_canister_id)?;
+    let args = Encode!(&nft_id)?;
+
+    let response = agent
+        .query(&canister_id, "getNFT")
+        .with_arg(args)
+        .call()
+        .await?;
+
+    let result: Result<AxiaNFT, String> = Decode!(&response, Result::<AxiaNFT, String>)?;
+    result.map_err(|e| anyhow::anyhow!("getNFT failed: {}", e))
+}
+
+/// Lists the asset registry entries attached to an NFT (`getAssetsByNFT`).
+pub async fn fetch_assets_for_nft(
+    agent: &Agent,
+    config: &BridgeConfig,
+    nft_id: Nat,
+) -> Result<Vec<AxiaAsset>> {
+    let canister_id = Principal::from_text(&config.asset_registry_canister_id)?;
+    let args = Encode!(&nft_id)?;
+
+    let response = agent
+        .query(&canister_id, "getAssetsByNFT")
+        .with_arg(args)
+        .call()
+        .await?;
+
+    Ok(Decode!(&response, Vec<AxiaAsset>)?)
+}
+
 /// Logs a verified XRPL payment on-chain using the configured payment log canister.
 pub async fn log_verified_payment(
     agent: &Agent,
@@ -89,6 +187,25 @@ pub async fn log_verified_payment(
     }
 }
 
+/// Payments the payment log canister recorded between `from_ns` and `to_ns`.
+pub async fn fetch_payment_log_records(
+    agent: &Agent,
+    config: &BridgeConfig,
+    method: &str,
+    from_ns: Int,
+    to_ns: Int,
+) -> Result<Vec<PaymentLogRecord>> {
+    let canister_id = Principal::from_text(&config.payment_log_canister_id)?;
+
+    let response = agent
+        .query(&canister_id, method)
+        .with_arg(Encode!(&from_ns, &to_ns)?)
+        .call()
+        .await?;
+
+    Ok(Decode!(&response, Vec<PaymentLogRecord>)?)
+}
+
 /// Handle a tip action from XRPL → AxiaSystem
 pub async fn handle_tip(
     agent: &Agent,
@@ -110,6 +227,19 @@ pub async fn handle_tip(
     result.map_err(|e| anyhow::anyhow!("Tip handling failed: {}", e))
 }
 
+/// Handle a wrap deposit from XRPL: mint wXRP 1:1 to the depositor's principal
+pub async fn handle_wrap_deposit(
+    agent: &Agent,
+    config: &BridgeConfig,
+    principal: Principal,
+    drops: Nat,
+    tx_hash: String,
+) -> Result<()> {
+    let drops = u64::try_from(&drops.0).map_err(|_| anyhow::anyhow!("Deposit {} exceeds u64 drops", tx_hash))?;
+    crate::wrapped_xrp::mint_wrapped_xrp(agent, config, principal, drops, &tx_hash).await?;
+    Ok(())
+}
+
 /// Handle an NFT sale settlement from XRPL
 pub async fn handle_nft_sale(
     agent: &Agent,
@@ -132,6 +262,28 @@ pub async fn handle_nft_sale(
     result.map_err(|e| anyhow::anyhow!("NFT sale handling failed: {}", e))
 }
 
+/// Settles an XLS-20 marketplace trade of a mirrored NFT (ownership + royalties on ICP).
+pub async fn handle_nft_market_sale(
+    agent: &Agent,
+    config: &BridgeConfig,
+    nft_id: Nat,
+    buyer: Principal,
+    sale: NFTSaleDetails,
+    tx_hash: String,
+) -> Result<()> {
+    let canister_id = Principal::from_text(&config.nft_sale_handler_canister_id)?;
+    let args = Encode!(&nft_id, &buyer, &sale, &tx_hash)?;
+
+    let response = agent
+        .update(&canister_id, "handleNFTMarketSaleFromXRPL")
+        .with_arg(args)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<(), String> = Decode!(&response, Result::<(), String>)?;
+    result.map_err(|e| anyhow::anyhow!("NFT market sale handling failed: {}", e))
+}
+
 /// Handle token swap / liquidity action from XRPL
 pub async fn handle_token_swap(
     agent: &Agent,
@@ -153,6 +305,189 @@ pub async fn handle_token_swap(
     result.map_err(|e| anyhow::anyhow!("Token swap handling failed: {}", e))
 }
 
+/// Reports what an XRPL DEX/AMM swap actually filled back to the swap canister.
+pub async fn report_swap_fill(agent: &Agent, config: &BridgeConfig, fill: &SwapFill) -> Result<()> {
+    let canister_id = Principal::from_text(&config.token_swap_canister_id)?;
+    let args = Encode!(fill)?;
+
+    let response = agent
+        .update(&canister_id, "reportSwapFillFromXRPL")
+        .with_arg(args)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<(), String> = Decode!(&response, Result::<(), String>)?;
+    result.map_err(|e| anyhow::anyhow!("Swap fill report failed: {}", e))
+}
+
+/// Registers an XRPL escrow to the bridge with the escrow canister's deal.
+pub async fn register_xrpl_escrow(agent: &Agent, config: &BridgeConfig, escrow: &XRPLEscrow) -> Result<()> {
+    let canister_id = Principal::from_text(&config.escrow_canister_id)?;
+    let args = Encode!(escrow)?;
+
+    let response = agent
+        .update(&canister_id, "registerXRPLEscrow")
+        .with_arg(args)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<(), String> = Decode!(&response, Result::<(), String>)?;
+    result.map_err(|e| anyhow::anyhow!("Escrow registration failed: {}", e))
+}
+
+/// Whether the ICP side has confirmed delivery (or cancelled) the deal behind an escrow.
+pub async fn fetch_escrow_deal_status(agent: &Agent, config: &BridgeConfig, deal_id: &str) -> Result<EscrowDealStatus> {
+    let canister_id = Principal::from_text(&config.escrow_canister_id)?;
+
+    let response = agent
+        .query(&canister_id, "getXRPLEscrowDealStatus")
+        .with_arg(Encode!(&deal_id)?)
+        .call()
+        .await?;
+
+    let result: Result<EscrowDealStatus, String> = Decode!(&response, Result::<EscrowDealStatus, String>)?;
+    result.map_err(|e| anyhow::anyhow!("Deal status lookup failed: {}", e))
+}
+
+/// Tells the escrow canister how the XRPL escrow closed (`finished` = released to the bridge).
+pub async fn settle_xrpl_escrow(agent: &Agent, config: &BridgeConfig, deal_id: &str, tx_hash: &str, finished: bool) -> Result<()> {
+    let canister_id = Principal::from_text(&config.escrow_canister_id)?;
+    let args = Encode!(&deal_id, &tx_hash, &finished)?;
+
+    let response = agent
+        .update(&canister_id, "settleXRPLEscrow")
+        .with_arg(args)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<(), String> = Decode!(&response, Result::<(), String>)?;
+    result.map_err(|e| anyhow::anyhow!("Escrow settlement failed: {}", e))
+}
+
+/// Registers a signature-proven XRPL account ↔ principal link with the identity canister.
+pub async fn register_xrpl_account_link(agent: &Agent, config: &BridgeConfig, proof: &XRPLAccountLinkProof) -> Result<()> {
+    let canister_id = Principal::from_text(&config.identity_canister_id)?;
+    let args = Encode!(proof)?;
+
+    let response = agent
+        .update(&canister_id, "linkXRPLAccount")
+        .with_arg(args)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<(), String> = Decode!(&response, Result::<(), String>)?;
+    result.map_err(|e| anyhow::anyhow!("Account link registration failed: {}", e))
+}
+
+/// Sanctions status of each XRPL account, per the compliance canister.
+pub async fn screen_xrpl_accounts(
+    agent: &Agent,
+    config: &BridgeConfig,
+    accounts: &[String],
+) -> Result<Vec<(String, SanctionsStatus)>> {
+    let canister_id = Principal::from_text(&config.compliance_canister_id)?;
+
+    let response = agent
+        .query(&canister_id, "screenXRPLAccounts")
+        .with_arg(Encode!(&accounts)?)
+        .call()
+        .await?;
+
+    Ok(Decode!(&response, Vec<(String, SanctionsStatus)>)?)
+}
+
+/// Opens an approval request with the HIL service; returns its acknowledgement.
+pub async fn submit_hil_approval_request(agent: &Agent, config: &BridgeConfig, request: &HILApprovalRequest) -> Result<String> {
+    let canister_id = Principal::from_text(&config.hil_canister_id)?;
+    let args = Encode!(request)?;
+
+    let response = agent
+        .update(&canister_id, "submitApprovalRequest")
+        .with_arg(args)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<String, String> = Decode!(&response, Result::<String, String>)?;
+    result.map_err(|e| anyhow::anyhow!("HIL submission failed: {}", e))
+}
+
+/// Current state of a HIL approval request, if the service knows it.
+pub async fn fetch_hil_approval_request(agent: &Agent, config: &BridgeConfig, correlation_id: &str) -> Result<Option<HILApprovalRequest>> {
+    let canister_id = Principal::from_text(&config.hil_canister_id)?;
+
+    let response = agent
+        .query(&canister_id, "getApprovalRequest")
+        .with_arg(Encode!(&correlation_id)?)
+        .call()
+        .await?;
+
+    Ok(Decode!(&response, Option<HILApprovalRequest>)?)
+}
+
+/// Pause flag kept under `module` on the pause canister, if one is set.
+pub async fn fetch_pause_flag(agent: &Agent, config: &BridgeConfig, method: &str, module: &str) -> Result<Option<EmergencyFlag>> {
+    let canister_id = Principal::from_text(&config.pause_canister_id)?;
+
+    let response = agent
+        .query(&canister_id, method)
+        .with_arg(Encode!(&module)?)
+        .call()
+        .await?;
+
+    Ok(Decode!(&response, Option<EmergencyFlag>)?)
+}
+
+/// Config entry `key` under `module` on the governance canister, if published.
+pub async fn fetch_governed_config(
+    agent: &Agent,
+    config: &BridgeConfig,
+    method: &str,
+    module: &str,
+    key: &str,
+) -> Result<Option<GovernedConfig>> {
+    let canister_id = Principal::from_text(&config.governance_canister_id)?;
+
+    let response = agent
+        .query(&canister_id, method)
+        .with_arg(Encode!(&module, &key)?)
+        .call()
+        .await?;
+
+    Ok(Decode!(&response, Option<GovernedConfig>)?)
+}
+
+/// Amount of the Axia token the ICP side holds locked against issued XRPL IOUs.
+pub async fn fetch_bridged_token_supply(agent: &Agent, config: &BridgeConfig) -> Result<Nat> {
+    let canister_id = Principal::from_text(&config.token_swap_canister_id)?;
+
+    let response = agent
+        .query(&canister_id, "getXRPLBridgedSupply")
+        .with_arg(Encode!()?)
+        .call()
+        .await?;
+
+    Ok(Decode!(&response, Nat)?)
+}
+
+/// Publishes a signed reserve attestation to the `namora_ai` or `treasury` canister.
+pub async fn publish_reserve_attestation(
+    agent: &Agent,
+    config: &BridgeConfig,
+    method: &str,
+    attestation: &PublishedAttestation,
+) -> Result<()> {
+    let canister_id = Principal::from_text(&config.reserves_canister_id)?;
+
+    let response = agent
+        .update(&canister_id, method)
+        .with_arg(Encode!(attestation)?)
+        .call_and_wait()
+        .await?;
+
+    let result: Result<(), String> = Decode!(&response, Result::<(), String>)?;
+    result.map_err(|e| anyhow::anyhow!("Attestation rejected: {}", e))
+}
+
 /// Creates an agent from PEM and environment variable (standardized)
 pub async fn create_agent_from_env() -> Result<Agent> {
     let identity = Arc::new(
@@ -213,27 +548,84 @@ pub async fn route_action_to_canister(
                 .map_err(|e| TriggerError::CallFailed(e.to_string()))
         }
 
+        PendingAction::NFTSale {
+            nft_id,
+            buyer,
+            price: _,
+            tx_hash,
+            uuid: _,
+            sale: Some(sale),
+        } => {
+            handle_nft_market_sale(agent, config, nft_id, buyer, *sale, tx_hash)
+                .await
+                .map_err(|e| TriggerError::CallFailed(e.to_string()))
+        }
+
         PendingAction::NFTSale {
             nft_id,
             buyer,
             price,
             tx_hash: _,
             uuid,
+            sale: None,
         } => {
             handle_nft_sale(agent, config, buyer, nft_id.to_string(), price, uuid)
                 .await
                 .map_err(|e| TriggerError::CallFailed(e.to_string()))
         }
 
+        PendingAction::TokenSwap {
+            artist,
+            amount,
+            tx_hash,
+            uuid,
+            swap: Some(request),
+        } => {
+            let fill = match execute_swap(&XRPLRpcClient::from_env(), &request).await {
+                Ok(fill) => fill,
+                Err(e) if refund_unfilled_swap(&request, &e) => {
+                    return Err(TriggerError::CallFailed(format!("Swap {} did not fill: {:?}", request.swap_id, e)));
+                }
+                Err(e) => {
+                    // The trade may still land; run it again under the same swap id.
+                    let _ = enqueue_action(PendingAction::TokenSwap { artist, amount, tx_hash, uuid, swap: Some(request) });
+                    return Err(TriggerError::CallFailed(format!("{:?}", e)));
+                }
+            };
+            report_fill(agent, config, &fill)
+                .await
+                .map_err(|e| TriggerError::CallFailed(e.to_string()))
+        }
+
         PendingAction::TokenSwap {
             artist,
             amount,
             tx_hash: _,
             uuid,
+            swap: None,
         } => {
             handle_token_swap(agent, config, artist, amount, uuid)
                 .await
                 .map_err(|e| TriggerError::CallFailed(e.to_string()))
         }
+
+        PendingAction::WrapXRP {
+            principal,
+            drops,
+            tx_hash,
+            uuid: _,
+        } => {
+            handle_wrap_deposit(agent, config, principal, drops, tx_hash)
+                .await
+                .map_err(|e| TriggerError::CallFailed(e.to_string()))
+        }
+
+        PendingAction::EscrowRegistration { escrow, tx_hash: _ } => {
+            register_xrpl_escrow(agent, config, &escrow)
+                .await
+                .map_err(|e| TriggerError::CallFailed(e.to_string()))?;
+            mark_escrow_registered(&escrow.key());
+            Ok(())
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use candid::{CandidType, Decode, Encode, Nat, Principal};
+use ic_agent::Agent;
+use serde::{Deserialize, Serialize};
+
+/// ICRC-1 account: owner plus optional 32-byte subaccount.
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub owner: Principal,
+    pub subaccount: Option<Vec<u8>>,
+}
+
+impl Account {
+    pub fn of(owner: Principal) -> Self {
+        Self { owner, subaccount: None }
+    }
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct TransferArg {
+    pub from_subaccount: Option<Vec<u8>>,
+    pub to: Account,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub enum TransferError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct TransferFromArgs {
+    pub spender_subaccount: Option<Vec<u8>>,
+    pub from: Account,
+    pub to: Account,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub enum TransferFromError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    InsufficientAllowance { allowance: Nat },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+/// Block a transfer landed in. `duplicate` means the ledger had already applied it.
+#[derive(Debug, Clone)]
+pub struct TransferOutcome {
+    pub block_index: Nat,
+    pub duplicate: bool,
+}
+
+/// Nanosecond timestamp for `created_at_time`, which turns on ledger-side deduplication.
+pub fn ledger_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+/// Client for one ICRC ledger canister.
+#[derive(Clone)]
+pub struct IcrcLedger {
+    agent: Agent,
+    canister_id: Principal,
+}
+
+impl IcrcLedger {
+    pub fn new(agent: &Agent, canister_id: &str) -> Result<Self> {
+        Ok(Self {
+            agent: agent.clone(),
+            canister_id: Principal::from_text(canister_id)?,
+        })
+    }
+
+    /// `icrc1_transfer` from the caller's account; from the minting account this mints.
+    /// A `Duplicate` rejection is treated as success with the original block index.
+    pub async fn icrc1_transfer(&self, arg: TransferArg) -> Result<TransferOutcome> {
+        self.try_icrc1_transfer(arg)
+            .await?
+            .map_err(|e| anyhow::anyhow!("icrc1_transfer failed: {:?}", e))
+    }
+
+    /// Like `icrc1_transfer`, with the ledger's refusal (inner) kept apart from a failed
+    /// call (outer), as in `try_icrc2_transfer_from`.
+    pub async fn try_icrc1_transfer(&self, arg: TransferArg) -> Result<Result<TransferOutcome, TransferError>> {
+        let response = self
+            .agent
+            .update(&self.canister_id, "icrc1_transfer")
+            .with_arg(Encode!(&arg)?)
+            .call_and_wait()
+            .await?;
+
+        Ok(match Decode!(&response, Result<Nat, TransferError>)? {
+            Ok(block_index) => Ok(TransferOutcome { block_index, duplicate: false }),
+            Err(TransferError::Duplicate { duplicate_of }) => Ok(TransferOutcome { block_index: duplicate_of, duplicate: true }),
+            Err(e) => Err(e),
+        })
+    }
+
+    /// `icrc2_transfer_from` on an approved allowance; sending to the minting account burns.
+    pub async fn icrc2_transfer_from(&self, args: TransferFromArgs) -> Result<TransferOutcome> {
+        self.try_icrc2_transfer_from(args)
+            .await?
+            .map_err(|e| anyhow::anyhow!("icrc2_transfer_from failed: {:?}", e))
+    }
+
+    /// Like `icrc2_transfer_from`, but keeps the two failure kinds apart: the inner error
+    /// is the ledger refusing the transfer (nothing applied), the outer one a failed call
+    /// whose transfer may or may not have landed.
+    pub async fn try_icrc2_transfer_from(&self, args: TransferFromArgs) -> Result<Result<TransferOutcome, TransferFromError>> {
+        let response = self
+            .agent
+            .update(&self.canister_id, "icrc2_transfer_from")
+            .with_arg(Encode!(&args)?)
+            .call_and_wait()
+            .await?;
+
+        Ok(match Decode!(&response, Result<Nat, TransferFromError>)? {
+            Ok(block_index) => Ok(TransferOutcome { block_index, duplicate: false }),
+            Err(TransferFromError::Duplicate { duplicate_of }) => Ok(TransferOutcome { block_index: duplicate_of, duplicate: true }),
+            Err(e) => Err(e),
+        })
+    }
+
+    pub async fn icrc1_total_supply(&self) -> Result<Nat> {
+        let response = self
+            .agent
+            .query(&self.canister_id, "icrc1_total_supply")
+            .with_arg(Encode!()?)
+            .call()
+            .await?;
+
+        Ok(Decode!(&response, Nat)?)
+    }
+
+    pub async fn icrc1_balance_of(&self, account: &Account) -> Result<Nat> {
+        let response = self
+            .agent
+            .query(&self.canister_id, "icrc1_balance_of")
+            .with_arg(Encode!(account)?)
+            .call()
+            .await?;
+
+        Ok(Decode!(&response, Nat)?)
+    }
+
+    pub async fn icrc1_minting_account(&self) -> Result<Option<Account>> {
+        let response = self
+            .agent
+            .query(&self.canister_id, "icrc1_minting_account")
+            .with_arg(Encode!()?)
+            .call()
+            .await?;
+
+        Ok(Decode!(&response, Option<Account>)?)
+    }
+}
@@ -6,10 +6,14 @@ pub mod generate_ffi;
 
 pub mod xrpl;
 pub mod ic_trigger;
+pub mod icrc_ledger;
+pub mod wrapped_xrp;
 pub mod config;
 pub mod log;
 pub mod state;
 pub mod monitor;
+pub mod admin;
+pub mod util;
 
 // Note: IC modules are disabled for now due to compilation issues
 // They will be enabled once the real IC integration is needed
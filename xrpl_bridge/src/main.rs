@@ -2,14 +2,34 @@ use std::error::Error;
 use std::time::Duration;
 
 use tokio::time;
-use namora_bridge::config::{BridgeConfig, ExtendedBridgeConfig};
+use namora_bridge::config::{BridgeConfig, ExtendedBridgeConfig, get_bridge_address, get_bridge_secret, get_admin_port, get_iou_enabled, get_params_sync_secs, get_pause_poll_secs, get_recon_interval_secs, get_reserves_interval_secs};
 use namora_bridge::log::bridge_log_event;
 use namora_bridge::monitor::start_monitor_server;
+use namora_bridge::admin::{start_admin_server, set_admin_agent};
 use namora_bridge::state::memory::init_memory_state;
-use namora_bridge::state::db::{load_pending_actions};
-use namora_bridge::state::queue::{enqueue_action, dequeue_pending_action};
+use namora_bridge::state::db::{append_to_tx_log, load_pending_actions};
+use namora_bridge::state::queue::{enqueue_action, dequeue_pending_action_where};
 use namora_bridge::ic_trigger::{route_action_to_canister, create_agent_from_env};
+use namora_bridge::wrapped_xrp::run_wxrp_maintenance;
 use namora_bridge::xrpl::client::connect_to_xrpl;
+use namora_bridge::xrpl::rpc::XRPLRpcClient;
+use namora_bridge::xrpl::sequence::run_ticket_maintenance;
+use namora_bridge::xrpl::approvals::run_approval_maintenance;
+use namora_bridge::xrpl::params::run_parameter_sync;
+use namora_bridge::xrpl::pause::{dispatch_paused, run_pause_polling};
+use namora_bridge::xrpl::fees::run_fee_refresh;
+use namora_bridge::xrpl::checks::run_check_cashing;
+use namora_bridge::xrpl::escrow::run_escrow_maintenance;
+use namora_bridge::xrpl::intents::run_intent_expiry;
+use namora_bridge::xrpl::issuer::run_issuer_maintenance;
+use namora_bridge::xrpl::linking::run_link_registration;
+use namora_bridge::xrpl::paychan::run_channel_redemption;
+use namora_bridge::xrpl::reconciliation::run_reconciliation;
+use namora_bridge::xrpl::refunds::run_refund_processing;
+use namora_bridge::xrpl::reserves::run_reserve_attestation;
+use namora_bridge::xrpl::screening::set_screening_agent;
+use namora_bridge::xrpl::swap::run_swap_reporting;
+use namora_bridge::xrpl::token_mirroring::run_mirror_maintenance;
 
 /// Setup logging format and targets (stdout, file, etc.)
 fn setup_logging() {
@@ -53,6 +73,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
         });
     }
 
+    // Local operator API
+    start_admin_server(get_admin_port(), config.clone());
+
     // Start XRPL client
     tokio::spawn(async move {
         if let Err(e) = connect_to_xrpl().await {
@@ -60,6 +83,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     });
 
+    // Keep the outbound sequence/ticket allocator synced (only when we can sign)
+    if let (Some(account), Some(_)) = (get_bridge_address(), get_bridge_secret()) {
+        tokio::spawn(run_ticket_maintenance(XRPLRpcClient::from_env(), account, 30));
+        tokio::spawn(run_fee_refresh(XRPLRpcClient::from_env(), 15));
+        tokio::spawn(run_channel_redemption(XRPLRpcClient::from_env(), 300));
+        tokio::spawn(run_check_cashing(XRPLRpcClient::from_env(), 30));
+        tokio::spawn(run_refund_processing(XRPLRpcClient::from_env(), 30));
+        tokio::spawn(run_intent_expiry(60));
+        if get_iou_enabled() {
+            tokio::spawn(run_issuer_maintenance(XRPLRpcClient::from_env(), 30));
+        }
+    }
+
     // Start core loop (trigger ICP from pending queue)
     run_bridge_core(config).await;
 
@@ -77,11 +113,45 @@ async fn run_bridge_core(config: BridgeConfig) {
         }
     };
 
+    set_admin_agent(agent.clone());
+    set_screening_agent(agent.clone(), config.clone());
+
+    // Follow the operators' pause flags on the pause canister
+    tokio::spawn(run_pause_polling(agent.clone(), config.clone(), get_pause_poll_secs()));
+
+    // Keep limits, minimums and fees in line with the governed parameter set
+    tokio::spawn(run_parameter_sync(agent.clone(), config.clone(), get_params_sync_secs()));
+
+    // Keep both sides of each mirror consistent (ICP locks, pending buy-back burns)
+    if get_bridge_address().is_some() {
+        tokio::spawn(run_mirror_maintenance(agent.clone(), config.clone(), XRPLRpcClient::from_env(), 60));
+        tokio::spawn(run_escrow_maintenance(agent.clone(), config.clone(), XRPLRpcClient::from_env(), 60));
+
+        // Attest that bridge-held XRPL reserves cover the ICP-side liabilities
+        tokio::spawn(run_reserve_attestation(agent.clone(), config.clone(), XRPLRpcClient::from_env(), get_reserves_interval_secs()));
+
+        // Check every XRPL deposit against the processed ledger and the payment log
+        tokio::spawn(run_reconciliation(agent.clone(), config.clone(), XRPLRpcClient::from_env(), get_recon_interval_secs()));
+    }
+
+    // Mint parked wrap deposits once their sender links, and retry unfinished mints
+    tokio::spawn(run_wxrp_maintenance(agent.clone(), config.clone(), 60));
+
+    // Re-send swap fills the swap canister hasn't accepted yet
+    tokio::spawn(run_swap_reporting(agent.clone(), config.clone(), 60));
+
+    // Retry account links the identity canister hasn't accepted yet
+    tokio::spawn(run_link_registration(agent.clone(), config.clone(), 120));
+
+    // Apply HIL decisions and expire held actions nobody approved
+    tokio::spawn(run_approval_maintenance(agent.clone(), config.clone(), 30));
+
     // Set the interval in seconds for queue processing (default: 6)
     let interval_secs = 6;
 
     loop {
-        match dequeue_pending_action() {
+        // Paused actions stay queued until their flow resumes.
+        match dequeue_pending_action_where(|action| !dispatch_paused(action)) {
             Some(action) => {
                 let cloned_config = config.clone();
                 let cloned_agent = agent.clone();
@@ -91,6 +161,8 @@ async fn run_bridge_core(config: BridgeConfig) {
                         // Optional: persist_failed_action(...)
                     } else {
                         bridge_log_event("trigger", "✅ Routed action to ICP.".to_string());
+                        // Reconciliation checks deposits against this processed-tx ledger
+                        append_to_tx_log(action.tx_hash(), action.kind(), chrono::Utc::now().timestamp() as u64);
                     }
                 });
             }
@@ -8,6 +8,8 @@ use std::thread;
 
 use crate::state::queue;
 use crate::config::BUILD_VERSION;
+use crate::xrpl::pause::{pause_status, PauseStatus};
+use crate::xrpl::reserves::latest_attestation;
 
 // Global Status State
 static LAST_SEEN_TX: Lazy<RwLock<Option<String>>> = Lazy::new(|| RwLock::new(None));
@@ -25,6 +27,9 @@ pub struct BridgeStatus {
     pub last_error: Option<String>,
     pub uptime_seconds: u64,
     pub build_version: &'static str,
+    pub pause: PauseStatus,
+    /// Health of the latest reserve attestation, if one has run.
+    pub reserves_healthy: Option<bool>,
 }
 
 /// Starts a simple HTTP status server
@@ -32,19 +37,17 @@ pub fn start_monitor_server(port: u16) {
     thread::spawn(move || {
         let listener = TcpListener::bind(("0.0.0.0", port)).expect("Failed to bind monitor port");
 
-        for stream in listener.incoming() {
-            if let Ok(mut stream) = stream {
-                let status = get_bridge_status();
-                let response = serde_json::to_string(&status).unwrap_or_else(|_| "{}".to_string());
+        for mut stream in listener.incoming().flatten() {
+            let status = get_bridge_status();
+            let response = serde_json::to_string(&status).unwrap_or_else(|_| "{}".to_string());
 
-                let http_response = format!(
-                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
-                    response.len(),
-                    response
-                );
+            let http_response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            );
 
-                let _ = stream.write_all(http_response.as_bytes());
-            }
+            let _ = stream.write_all(http_response.as_bytes());
         }
     });
 }
@@ -61,6 +64,8 @@ pub fn get_bridge_status() -> BridgeStatus {
         last_error: LAST_ERROR.read().unwrap().clone(),
         uptime_seconds: uptime,
         build_version: BUILD_VERSION,
+        pause: pause_status(),
+        reserves_healthy: latest_attestation().map(|a| a.report.healthy),
     }
 }
 
@@ -5,6 +5,7 @@ use serde_json::{to_writer, from_reader};
 use crate::state::queue::PendingAction;
 
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 
 #[derive(Serialize, Deserialize)]
 struct FailedActionRecord {
@@ -46,7 +47,7 @@ fn ensure_persist_dir() -> Result<(), DBError> {
 pub fn persist_pending_actions(actions: &[PendingAction]) -> Result<(), DBError> {
     ensure_persist_dir()?;
 
-    let file = File::create(&get_pending_actions_file())
+    let file = File::create(get_pending_actions_file())
         .map_err(|e| DBError::WriteFailure(e.to_string()))?;
 
     to_writer(BufWriter::new(file), &actions)
@@ -67,6 +68,76 @@ pub fn load_pending_actions() -> Result<Vec<PendingAction>, DBError> {
         .map_err(|e| DBError::DeserializeError(e.to_string()))
 }
 
+/// 💾 Atomically writes a named JSON state file under `.persistent/`.
+/// Writes to a temp file first so a crash never leaves a half-written state.
+pub fn persist_json_state<T: Serialize>(name: &str, value: &T) -> Result<(), DBError> {
+    ensure_persist_dir()?;
+
+    let path = format!("{}{}", PERSIST_DIR, name);
+    let tmp_path = format!("{}.tmp", path);
+
+    let file = File::create(&tmp_path)
+        .map_err(|e| DBError::WriteFailure(e.to_string()))?;
+    to_writer(BufWriter::new(file), value)
+        .map_err(|e| DBError::WriteFailure(e.to_string()))?;
+
+    fs::rename(&tmp_path, &path)
+        .map_err(|e| DBError::WriteFailure(format!("Failed to replace {}: {}", path, e)))
+}
+
+/// 🔁 Loads a named JSON state file, or `None` if it has never been written.
+pub fn load_json_state<T: DeserializeOwned>(name: &str) -> Result<Option<T>, DBError> {
+    let path = format!("{}{}", PERSIST_DIR, name);
+    if !Path::new(&path).exists() {
+        return Ok(None);
+    }
+
+    let file = File::open(&path)
+        .map_err(|e| DBError::ReadFailure(e.to_string()))?;
+
+    from_reader(BufReader::new(file))
+        .map(Some)
+        .map_err(|e| DBError::DeserializeError(e.to_string()))
+}
+
+/// 📜 Appends one JSON record to a named `.jsonl` file under `.persistent/`.
+pub fn append_jsonl_record<T: Serialize>(name: &str, record: &T) -> Result<(), DBError> {
+    ensure_persist_dir()?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(format!("{}{}", PERSIST_DIR, name))
+        .map_err(|e| DBError::WriteFailure(e.to_string()))?;
+
+    let json = serde_json::to_string(record)
+        .map_err(|e| DBError::WriteFailure(e.to_string()))?;
+
+    writeln!(file, "{}", json).map_err(|e| DBError::WriteFailure(e.to_string()))
+}
+
+/// 📥 Reads every record from a named `.jsonl` file (empty if missing).
+pub fn load_jsonl_records<T: DeserializeOwned>(name: &str) -> Result<Vec<T>, DBError> {
+    let path = format!("{}{}", PERSIST_DIR, name);
+    if !Path::new(&path).exists() {
+        return Ok(vec![]);
+    }
+
+    let file = File::open(&path)
+        .map_err(|e| DBError::ReadFailure(e.to_string()))?;
+
+    let mut results = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| DBError::ReadFailure(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        results.push(serde_json::from_str(&line).map_err(|e| DBError::DeserializeError(e.to_string()))?);
+    }
+
+    Ok(results)
+}
+
 /// 📜 Appends a transaction to the tx log file.
 pub fn append_to_tx_log(tx_hash: &str, action_type: &str, timestamp: u64) {
     let log_entry = format!(
@@ -77,7 +148,7 @@ pub fn append_to_tx_log(tx_hash: &str, action_type: &str, timestamp: u64) {
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&get_tx_log_file())
+        .open(get_tx_log_file())
         .expect("Failed to open tx log file");
 
     file.write_all(log_entry.as_bytes())
@@ -99,7 +170,7 @@ pub fn persist_failed_action(
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&get_failed_actions_file())
+        .open(get_failed_actions_file())
         .map_err(|e| DBError::WriteFailure(e.to_string()))?;
 
     let json = serde_json::to_string(&record)
@@ -0,0 +1,373 @@
+// state/mirror_registry.rs
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::xrpl::types::{MirrorError, XRPLMirrorStatus};
+use crate::util::now_secs;
+
+const MIRROR_REGISTRY_FILE: &str = "mirror_registry.json";
+
+/// Pre-registry mapping file written by the first NFT mint flow; imported once on load.
+const LEGACY_NFT_MIRROR_FILE: &str = "nft_mirrors.json";
+
+/// Lifecycle of an Axia asset's XRPL mirror.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MirrorState {
+    Requested,
+    Minting,
+    Mirrored,
+    BurnPending,
+    Burned,
+    Failed,
+}
+
+impl MirrorState {
+    /// Allowed lifecycle edges.
+    pub fn can_transition_to(self, next: MirrorState) -> bool {
+        use MirrorState::*;
+        matches!(
+            (self, next),
+            (Requested, Minting)
+                | (Requested, Failed)
+                | (Minting, Mirrored)
+                | (Minting, Failed)
+                | (Mirrored, BurnPending)
+                | (Mirrored, Burned)
+                | (BurnPending, Burned)
+                | (BurnPending, Mirrored) // burn abandoned, token still live on XRPL
+                | (BurnPending, Failed)
+                | (Failed, Requested)
+                | (Burned, Requested)
+        )
+    }
+
+    /// True while the asset has (or may have) a live representation on XRPL.
+    pub fn is_live_on_xrpl(self) -> bool {
+        matches!(self, MirrorState::Minting | MirrorState::Mirrored | MirrorState::BurnPending)
+    }
+}
+
+/// What the asset is mirrored as on XRPL.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum XRPLMirrorRef {
+    NFToken { nftoken_id: String },
+    IOU { currency: String, issuer: String },
+}
+
+impl XRPLMirrorRef {
+    /// Index key for the XRPL side of the mapping.
+    pub fn key(&self) -> String {
+        match self {
+            XRPLMirrorRef::NFToken { nftoken_id } => format!("nft:{}", nftoken_id.to_uppercase()),
+            XRPLMirrorRef::IOU { currency, issuer } => format!("iou:{}:{}", currency.to_uppercase(), issuer),
+        }
+    }
+}
+
+/// One lifecycle step in a mirror's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorEvent {
+    pub state: MirrorState,
+    pub at: u64,
+    pub tx_hash: Option<String>,
+    pub note: Option<String>,
+}
+
+/// Full mirror record for one Axia asset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorRecord {
+    pub asset_id: String,
+    pub artist: String,
+    pub mirror_type: String, // "NFT" or "IOU"
+    pub xrpl_ref: Option<XRPLMirrorRef>,
+    pub state: MirrorState,
+    pub metadata_uri: Option<String>,
+    pub mint_tx_hash: Option<String>,
+    pub burn_tx_hash: Option<String>,
+    pub icp_marked: bool,
+    pub history: Vec<MirrorEvent>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl MirrorRecord {
+    /// Legacy status view used by existing callers.
+    pub fn to_status(&self) -> XRPLMirrorStatus {
+        XRPLMirrorStatus {
+            mirrored: self.state == MirrorState::Mirrored,
+            pending: matches!(self.state, MirrorState::Requested | MirrorState::Minting | MirrorState::BurnPending),
+            tx_hash: self.mint_tx_hash.clone(),
+            mirror_type: Some(self.mirror_type.clone()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct MirrorRegistry {
+    records: HashMap<String, MirrorRecord>,
+    by_xrpl: HashMap<String, String>, // XRPLMirrorRef::key() → asset_id
+}
+
+impl MirrorRegistry {
+    fn index(&mut self, record: &MirrorRecord) {
+        if let Some(xrpl_ref) = &record.xrpl_ref {
+            self.by_xrpl.insert(xrpl_ref.key(), record.asset_id.clone());
+        }
+    }
+}
+
+/// Shape of the pre-registry `nft_mirrors.json` entries.
+#[derive(Deserialize)]
+struct LegacyNFTMirror {
+    asset_id: String,
+    nftoken_id: String,
+    artist: String,
+    mint_tx_hash: String,
+    metadata_uri: String,
+    icp_marked: bool,
+}
+
+static MIRROR_REGISTRY: Lazy<RwLock<MirrorRegistry>> = Lazy::new(|| RwLock::new(load_registry()));
+
+fn load_registry() -> MirrorRegistry {
+    let mut registry = MirrorRegistry::default();
+
+    let records = match load_json_state::<Vec<MirrorRecord>>(MIRROR_REGISTRY_FILE) {
+        Ok(records) => records.unwrap_or_default(),
+        Err(e) => {
+            bridge_log_event("error", format!("Could not load mirror registry: {:?}", e));
+            vec![]
+        }
+    };
+    for record in records {
+        registry.index(&record);
+        registry.records.insert(record.asset_id.clone(), record);
+    }
+
+    if let Ok(Some(legacy)) = load_json_state::<Vec<LegacyNFTMirror>>(LEGACY_NFT_MIRROR_FILE) {
+        let mut imported = 0;
+        for old in legacy {
+            if registry.records.contains_key(&old.asset_id) {
+                continue;
+            }
+            let now = now_secs();
+            let record = MirrorRecord {
+                asset_id: old.asset_id,
+                artist: old.artist,
+                mirror_type: "NFT".to_string(),
+                xrpl_ref: Some(XRPLMirrorRef::NFToken { nftoken_id: old.nftoken_id }),
+                state: MirrorState::Mirrored,
+                metadata_uri: Some(old.metadata_uri),
+                mint_tx_hash: Some(old.mint_tx_hash.clone()),
+                burn_tx_hash: None,
+                icp_marked: old.icp_marked,
+                history: vec![MirrorEvent {
+                    state: MirrorState::Mirrored,
+                    at: now,
+                    tx_hash: Some(old.mint_tx_hash),
+                    note: Some("imported from nft_mirrors.json".into()),
+                }],
+                created_at: now,
+                updated_at: now,
+            };
+            registry.index(&record);
+            registry.records.insert(record.asset_id.clone(), record);
+            imported += 1;
+        }
+        if imported > 0 {
+            bridge_log_event("mirror", format!("Imported {} legacy NFT mirror records", imported));
+            persist(&registry);
+        }
+    }
+
+    registry
+}
+
+fn persist(registry: &MirrorRegistry) {
+    let records: Vec<&MirrorRecord> = registry.records.values().collect();
+    if let Err(e) = persist_json_state(MIRROR_REGISTRY_FILE, &records) {
+        bridge_log_event("error", format!("Failed to persist mirror registry: {:?}", e));
+    }
+}
+
+/// Opens (or re-opens after a burn / failure) a mirror request for an asset.
+/// Refuses if the asset is already live on XRPL.
+pub fn request_mirror(
+    asset_id: &str,
+    artist: &str,
+    mirror_type: &str,
+    metadata_uri: Option<String>,
+) -> Result<MirrorRecord, MirrorError> {
+    let mut registry = MIRROR_REGISTRY.write().map_err(|_| MirrorError::InternalError("Lock error".into()))?;
+    let now = now_secs();
+
+    let event = MirrorEvent { state: MirrorState::Requested, at: now, tx_hash: None, note: None };
+
+    let record = match registry.records.get_mut(asset_id) {
+        Some(existing) => {
+            if !existing.state.can_transition_to(MirrorState::Requested) {
+                return Err(MirrorError::AlreadyExists(format!(
+                    "Asset {} is already {:?}",
+                    asset_id, existing.state
+                )));
+            }
+            if existing.icp_marked {
+                return Err(MirrorError::InvalidStatus(format!(
+                    "Asset {} is still locked on ICP from its previous mirror",
+                    asset_id
+                )));
+            }
+            existing.state = MirrorState::Requested;
+            existing.artist = artist.to_string();
+            existing.mirror_type = mirror_type.to_string();
+            existing.metadata_uri = metadata_uri;
+            existing.xrpl_ref = None;
+            existing.mint_tx_hash = None;
+            existing.burn_tx_hash = None;
+            existing.icp_marked = false;
+            existing.history.push(event);
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let record = MirrorRecord {
+                asset_id: asset_id.to_string(),
+                artist: artist.to_string(),
+                mirror_type: mirror_type.to_string(),
+                xrpl_ref: None,
+                state: MirrorState::Requested,
+                metadata_uri,
+                mint_tx_hash: None,
+                burn_tx_hash: None,
+                icp_marked: false,
+                history: vec![event],
+                created_at: now,
+                updated_at: now,
+            };
+            registry.records.insert(asset_id.to_string(), record.clone());
+            record
+        }
+    };
+
+    persist(&registry);
+    Ok(record)
+}
+
+/// Moves a mirror to a new lifecycle state, recording it in the history.
+pub fn transition_mirror(
+    asset_id: &str,
+    next: MirrorState,
+    tx_hash: Option<String>,
+    note: Option<String>,
+) -> Result<MirrorRecord, MirrorError> {
+    let mut registry = MIRROR_REGISTRY.write().map_err(|_| MirrorError::InternalError("Lock error".into()))?;
+
+    let record = registry
+        .records
+        .get_mut(asset_id)
+        .ok_or_else(|| MirrorError::NotFound(format!("No mirror record for asset {}", asset_id)))?;
+
+    // A new mirror can't start until the previous one has released the ICP original.
+    if next == MirrorState::Requested && record.icp_marked {
+        return Err(MirrorError::InvalidStatus(format!("Asset {} is still locked on ICP", asset_id)));
+    }
+    if !record.state.can_transition_to(next) {
+        return Err(MirrorError::InvalidStatus(format!(
+            "Asset {}: cannot go from {:?} to {:?}",
+            asset_id, record.state, next
+        )));
+    }
+
+    let now = now_secs();
+    match next {
+        MirrorState::Mirrored if record.mint_tx_hash.is_none() => record.mint_tx_hash = tx_hash.clone(),
+        MirrorState::Burned => record.burn_tx_hash = tx_hash.clone(),
+        _ => {}
+    }
+    record.state = next;
+    record.updated_at = now;
+    record.history.push(MirrorEvent { state: next, at: now, tx_hash, note });
+
+    let updated = record.clone();
+    persist(&registry);
+
+    bridge_log_event("mirror", format!("Asset {} → {:?}", asset_id, next));
+    Ok(updated)
+}
+
+/// Attaches the XRPL-side identifier to a mirror and indexes it.
+pub fn set_xrpl_ref(asset_id: &str, xrpl_ref: XRPLMirrorRef) -> Result<(), MirrorError> {
+    let mut registry = MIRROR_REGISTRY.write().map_err(|_| MirrorError::InternalError("Lock error".into()))?;
+
+    if let Some(owner) = registry.by_xrpl.get(&xrpl_ref.key()) {
+        if owner != asset_id {
+            return Err(MirrorError::AlreadyExists(format!("{} already mirrors asset {}", xrpl_ref.key(), owner)));
+        }
+    }
+
+    let record = registry
+        .records
+        .get_mut(asset_id)
+        .ok_or_else(|| MirrorError::NotFound(format!("No mirror record for asset {}", asset_id)))?;
+    record.xrpl_ref = Some(xrpl_ref.clone());
+    record.updated_at = now_secs();
+
+    registry.by_xrpl.insert(xrpl_ref.key(), asset_id.to_string());
+    persist(&registry);
+    Ok(())
+}
+
+/// Records that the ICP side acknowledged (or dropped) the mirror flag.
+pub fn set_icp_marked(asset_id: &str, marked: bool) -> Result<(), MirrorError> {
+    let mut registry = MIRROR_REGISTRY.write().map_err(|_| MirrorError::InternalError("Lock error".into()))?;
+
+    let record = registry
+        .records
+        .get_mut(asset_id)
+        .ok_or_else(|| MirrorError::NotFound(format!("No mirror record for asset {}", asset_id)))?;
+    record.icp_marked = marked;
+    record.updated_at = now_secs();
+
+    persist(&registry);
+    Ok(())
+}
+
+/// Looks up a mirror by Axia asset ID.
+pub fn get_mirror(asset_id: &str) -> Option<MirrorRecord> {
+    MIRROR_REGISTRY.read().unwrap().records.get(asset_id).cloned()
+}
+
+/// Looks up a mirror by its XRPL-side identifier.
+pub fn get_mirror_by_xrpl_ref(xrpl_ref: &XRPLMirrorRef) -> Option<MirrorRecord> {
+    let registry = MIRROR_REGISTRY.read().unwrap();
+    registry
+        .by_xrpl
+        .get(&xrpl_ref.key())
+        .and_then(|asset_id| registry.records.get(asset_id))
+        .cloned()
+}
+
+/// Looks up a mirror by XLS-20 NFTokenID.
+pub fn get_mirror_by_nftoken(nftoken_id: &str) -> Option<MirrorRecord> {
+    get_mirror_by_xrpl_ref(&XRPLMirrorRef::NFToken { nftoken_id: nftoken_id.to_string() })
+}
+
+/// Lists mirrors, optionally filtered by state.
+pub fn list_mirrors(state: Option<MirrorState>) -> Vec<MirrorRecord> {
+    let registry = MIRROR_REGISTRY.read().unwrap();
+    let mut records: Vec<MirrorRecord> = registry
+        .records
+        .values()
+        .filter(|r| state.map(|s| r.state == s).unwrap_or(true))
+        .cloned()
+        .collect();
+    records.sort_by_key(|r| r.created_at);
+    records
+}
@@ -1,3 +1,4 @@
 pub mod queue;
 pub mod memory;
-pub mod db;
\ No newline at end of file
+pub mod db;
+pub mod mirror_registry;
@@ -3,11 +3,29 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
 use lazy_static::lazy_static;
-use candid::{Nat, Principal};
+use candid::{CandidType, Nat, Principal};
 use chrono::{Utc, DateTime, Duration};
 use serde::{Deserialize, Serialize};
 
-use crate::xrpl::types::VerifiedXRPLTx;
+use crate::xrpl::escrow::XRPLEscrow;
+use crate::config::get_swap_slippage_bps;
+use crate::xrpl::linking::linked_principal;
+use crate::xrpl::refunds::RefundSource;
+use crate::xrpl::swap::{Issue, SwapRequest};
+use crate::xrpl::types::{VerifiedXRPLTx, XRPLAmount};
+
+/// What actually happened in an XLS-20 marketplace trade (`NFTokenAcceptOffer`).
+#[derive(Clone, Serialize, Deserialize, Debug, CandidType)]
+pub struct NFTSaleDetails {
+    pub nftoken_id: String,
+    pub seller: String,
+    pub buyer: String,
+    pub price: XRPLAmount,
+    pub broker: Option<String>,
+    pub broker_fee: Option<XRPLAmount>,
+    /// Royalty rate taken by the issuer, in 1/100000 units.
+    pub transfer_fee: u16,
+}
 
 /// Represents a queueable XRPL → ICP action
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -21,15 +39,32 @@ pub enum PendingAction {
     NFTSale {
         nft_id: Nat,
         buyer: Principal,
+        /// Drops, or IOU units (value × 10^`XRPL_IOU_DECIMALS`) when `sale` is IOU-priced.
         price: Nat,
         tx_hash: String,
         uuid: String,
+        /// Present for marketplace trades; memo-based sales leave it empty.
+        #[serde(default)]
+        sale: Option<Box<NFTSaleDetails>>,
     },
     TokenSwap {
         artist: Principal,
         amount: Nat,
         tx_hash: String,
         uuid: String,
+        /// XRP paid in with a SWAP memo, traded on the DEX; IOU redemptions leave it empty.
+        #[serde(default)]
+        swap: Option<Box<SwapRequest>>,
+    },
+    WrapXRP {
+        principal: Principal,
+        drops: Nat,
+        tx_hash: String,
+        uuid: String,
+    },
+    EscrowRegistration {
+        escrow: Box<XRPLEscrow>,
+        tx_hash: String,
     },
     // Future: NFTMint, etc.
 }
@@ -57,25 +92,65 @@ lazy_static! {
     static ref PROCESSED_TXS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
 }
 
-/// Enqueues a verified transaction into the queue.
-pub fn enqueue_verified_tx(tx: VerifiedXRPLTx) -> Result<(), QueueError> {
-    let tx_hash = tx.tx_hash.clone();
+impl PendingAction {
+    /// Every action type name, as returned by `kind`.
+    pub const KINDS: [&'static str; 5] = ["Tip", "NFTSale", "TokenSwap", "WrapXRP", "EscrowRegistration"];
+
+    /// Action type name used by limit rules and pause flags.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PendingAction::Tip { .. } => "Tip",
+            PendingAction::NFTSale { .. } => "NFTSale",
+            PendingAction::TokenSwap { .. } => "TokenSwap",
+            PendingAction::WrapXRP { .. } => "WrapXRP",
+            PendingAction::EscrowRegistration { .. } => "EscrowRegistration",
+        }
+    }
 
-    {
-        // Prevent duplicates
-        let pending = PENDING_QUEUE.read().unwrap();
-        if pending.contains_key(&tx_hash) {
-            return Err(QueueError::AlreadyExists);
+    /// Queue key: the XRPL transaction (or synthetic reference) behind the action.
+    pub fn tx_hash(&self) -> &str {
+        match self {
+            PendingAction::Tip { tx_hash, .. } => tx_hash,
+            PendingAction::NFTSale { tx_hash, .. } => tx_hash,
+            PendingAction::TokenSwap { tx_hash, .. } => tx_hash,
+            PendingAction::WrapXRP { tx_hash, .. } => tx_hash,
+            PendingAction::EscrowRegistration { tx_hash, .. } => tx_hash,
         }
-        let processed = PROCESSED_TXS.read().unwrap();
-        if processed.contains(&tx_hash) {
-            return Err(QueueError::AlreadyExists);
+    }
+
+    /// XRP value the action credits; redemptions and IOU-priced sales are IOU-denominated and escrows move nothing yet.
+    pub fn xrp_drops(&self) -> u64 {
+        let to_u64 = |n: &Nat| u64::try_from(&n.0).unwrap_or(u64::MAX);
+        match self {
+            PendingAction::Tip { amount, .. } => to_u64(amount),
+            PendingAction::NFTSale { price, sale, .. } if !is_iou_sale(sale) => to_u64(price),
+            PendingAction::WrapXRP { drops, .. } => to_u64(drops),
+            PendingAction::TokenSwap { amount, swap: Some(_), .. } => to_u64(amount),
+            PendingAction::NFTSale { .. } | PendingAction::TokenSwap { .. } | PendingAction::EscrowRegistration { .. } => 0,
+        }
+    }
+
+    /// IOU units the action redeems or pays (smallest ICP-token units); zero for XRP-denominated actions.
+    pub fn iou_units(&self) -> u64 {
+        match self {
+            PendingAction::TokenSwap { amount, swap: None, .. } => u64::try_from(&amount.0).unwrap_or(u64::MAX),
+            PendingAction::NFTSale { price, sale, .. } if is_iou_sale(sale) => u64::try_from(&price.0).unwrap_or(u64::MAX),
+            _ => 0,
         }
     }
+}
+
+fn is_iou_sale(sale: &Option<Box<NFTSaleDetails>>) -> bool {
+    sale.as_ref().is_some_and(|s| matches!(s.price, XRPLAmount::IOU { .. }))
+}
+
+/// Builds the queued action for a verified transaction.
+pub fn action_from_verified_tx(tx: VerifiedXRPLTx) -> Result<PendingAction, QueueError> {
+    let tx_hash = tx.tx_hash.clone();
 
     let action = match tx.action {
         crate::xrpl::types::XRPLActionType::Tip => {
-            let artist = tx.memo.artist.clone().ok_or(QueueError::ParseError)?;
+            let artist = tx.memo.artist.ok_or(QueueError::ParseError)?;
             PendingAction::Tip {
                 artist,
                 amount: tx.amount,
@@ -84,28 +159,69 @@ pub fn enqueue_verified_tx(tx: VerifiedXRPLTx) -> Result<(), QueueError> {
             }
         }
         crate::xrpl::types::XRPLActionType::NFTSale => {
-            let artist = tx.memo.artist.clone().ok_or(QueueError::ParseError)?;
+            // The buyer is whoever paid: a linked sender wins over whatever the memo claims.
+            let buyer = linked_principal(&tx.sender)
+                .or(tx.memo.artist)
+                .ok_or(QueueError::ParseError)?;
             let nft_id = tx.memo.nft_id.clone().ok_or(QueueError::ParseError)?;
             PendingAction::NFTSale {
-                buyer: artist,
+                buyer,
                 nft_id,
                 price: tx.amount,
                 tx_hash: tx_hash.clone(),
                 uuid: tx.memo.uuid.unwrap_or_default(),
+                sale: None,
+            }
+        }
+        crate::xrpl::types::XRPLActionType::WrapXRP => {
+            // A memo may wrap to someone else; without one, mint to the sender's linked principal.
+            let principal = tx.memo.artist
+                .or_else(|| linked_principal(&tx.sender))
+                .ok_or(QueueError::ParseError)?;
+            PendingAction::WrapXRP {
+                principal,
+                drops: tx.amount,
+                tx_hash: tx_hash.clone(),
+                uuid: tx.memo.uuid.unwrap_or_default(),
+            }
+        }
+        crate::xrpl::types::XRPLActionType::TokenSwap => {
+            // XRP paid in for a swap: the bridge trades it for RECEIVE and reports the fill.
+            let artist = tx.memo.artist.ok_or(QueueError::ParseError)?;
+            let receive = tx.memo.receive.as_deref().and_then(Issue::from_code).ok_or(QueueError::ParseError)?;
+            let drops = u64::try_from(&tx.amount.0).map_err(|_| QueueError::ParseError)?;
+            PendingAction::TokenSwap {
+                artist,
+                amount: tx.amount,
+                tx_hash: tx_hash.clone(),
+                uuid: tx.memo.uuid.unwrap_or_default(),
+                swap: Some(Box::new(SwapRequest {
+                    swap_id: tx_hash.clone(),
+                    send: XRPLAmount::XRP { drops },
+                    receive,
+                    destination: None,
+                    destination_tag: None,
+                    max_slippage_bps: get_swap_slippage_bps(),
+                    credit_to: Some(artist),
+                    refund: Some(RefundSource {
+                        original_tx: tx_hash.clone(),
+                        sender: tx.sender.clone(),
+                        source_tag: None,
+                        delivered: XRPLAmount::XRP { drops },
+                        is_refund_echo: false,
+                    }),
+                })),
             }
         }
-        _ => return Err(QueueError::ParseError), // Extendable
     };
 
-    let wrapper = ActionWrapper {
-        action,
-        retries: 0,
-        last_attempt: Utc::now(),
-        failed: false,
-    };
+    Ok(action)
+}
 
-    let mut write_guard = PENDING_QUEUE.write().map_err(|_| QueueError::WriteFailure)?;
-    write_guard.insert(tx_hash.clone(), wrapper);
+/// Enqueues a verified transaction into the queue.
+pub fn enqueue_verified_tx(tx: VerifiedXRPLTx) -> Result<(), QueueError> {
+    let tx_hash = tx.tx_hash.clone();
+    enqueue_action(action_from_verified_tx(tx)?)?;
 
     println!("📥 Enqueued verified tx: {}", tx_hash);
     Ok(())
@@ -113,11 +229,7 @@ pub fn enqueue_verified_tx(tx: VerifiedXRPLTx) -> Result<(), QueueError> {
 
 /// Enqueues a pending action directly into the queue.
 pub fn enqueue_action(action: PendingAction) -> Result<(), QueueError> {
-    let tx_hash = match &action {
-        PendingAction::Tip { tx_hash, .. } => tx_hash.clone(),
-        PendingAction::NFTSale { tx_hash, .. } => tx_hash.clone(),
-        PendingAction::TokenSwap { tx_hash, .. } => tx_hash.clone(),
-    };
+    let tx_hash = action.tx_hash().to_string();
 
     {
         // Prevent duplicates
@@ -232,10 +344,15 @@ pub fn queue_size() -> usize {
 
 /// Dequeues the next pending action from the queue.
 pub fn dequeue_pending_action() -> Option<PendingAction> {
+    dequeue_pending_action_where(|_| true)
+}
+
+/// Dequeues the next pending action `allowed` accepts, leaving the rest queued.
+pub fn dequeue_pending_action_where(allowed: impl Fn(&PendingAction) -> bool) -> Option<PendingAction> {
     let mut pending = PENDING_QUEUE.write().unwrap();
     
-    // Get the first item from the queue
-    if let Some((tx_hash, wrapper)) = pending.iter().next() {
+    // Get the first eligible item from the queue
+    if let Some((tx_hash, wrapper)) = pending.iter().find(|(_, w)| allowed(&w.action)) {
         let tx_hash = tx_hash.clone();
         let action = wrapper.action.clone();
         
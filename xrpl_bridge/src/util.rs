@@ -0,0 +1,51 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds between the Unix epoch and the Ripple epoch (2000-01-01).
+pub const RIPPLE_EPOCH_OFFSET: u64 = 946_684_800;
+
+/// Current Unix time in seconds.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Upper-case hex, as XRPL fields carry it.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Decodes hex of either case; `None` for odd lengths or any non-hex character.
+/// Works on bytes, so untrusted (even non-ASCII) input can't split a char.
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let digits = hex.as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| Some(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?))
+        .collect()
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trips_and_rejects_bad_input() {
+        assert_eq!(from_hex("00ff7A"), Some(vec![0x00, 0xFF, 0x7A]));
+        assert_eq!(to_hex(&[0x00, 0xFF, 0x7A]), "00FF7A");
+        assert_eq!(from_hex(""), Some(vec![]));
+        assert_eq!(from_hex("0"), None);
+        assert_eq!(from_hex("0g"), None);
+        // Multi-byte characters must be rejected, not sliced through.
+        assert_eq!(from_hex("0é0"), None);
+        assert_eq!(from_hex("éé"), None);
+    }
+}
@@ -0,0 +1,741 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use anyhow::Result;
+use candid::{Nat, Principal};
+use ic_agent::Agent;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::config::{get_bridge_address, BridgeConfig};
+use crate::icrc_ledger::{ledger_now, Account, IcrcLedger, TransferArg, TransferError, TransferFromArgs};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::{enqueue_action, PendingAction, QueueError};
+use crate::xrpl::approvals::admit_action;
+use crate::xrpl::linking::linked_principal;
+use crate::xrpl::memo::{memo_text_from_tx, parse_memo_string};
+use crate::xrpl::multisig::submit_transfer;
+use crate::xrpl::pause::{dispatch_paused, ensure_outbound_allowed};
+use crate::xrpl::refunds::RefundSource;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::{XRPLActionType, XRPLAmount};
+use crate::util::now_secs;
+
+const WXRP_BOOK_FILE: &str = "wxrp_book.json";
+
+/// Destination tag that marks an XRP payment to the bridge as a wrap deposit.
+pub const WRAP_DEPOSIT_TAG: u32 = 4001;
+
+/// One XRPL deposit and the wXRP mint that backs it (1 drop = 1 wXRP unit).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapDepositRecord {
+    pub tx_hash: String,
+    pub principal: String,
+    pub drops: u64,
+    /// Fixed on the first attempt so retries hit the ledger's dedup window.
+    pub created_at_time: u64,
+    pub block_index: Option<u64>,
+    pub supply_after: Option<u64>,
+    /// A mint call may have reached the ledger without an answer (a failed call, or a
+    /// restart mid-call), so it may have landed.
+    #[serde(default)]
+    pub outcome_unknown: bool,
+    /// Past the ledger's dedup window with an unknown outcome: an operator must check the
+    /// ledger and re-mint (`remint_wrap_deposit`) if nothing landed.
+    #[serde(default)]
+    pub needs_review: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A wrap deposit that names no principal, from a sender with no linked principal yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedDeposit {
+    pub tx_hash: String,
+    pub sender: String,
+    pub drops: u64,
+    pub uuid: String,
+    /// Returned to the sender if screening or limits refuse the deposit on release.
+    pub refund: Option<RefundSource>,
+    pub parked_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WithdrawalStatus {
+    /// Burn sent but not confirmed; a retry resumes it with the same request.
+    Burning,
+    /// wXRP burned; XRP not yet sent.
+    Burned,
+    Paid,
+    /// Burned on ICP but the XRP payout failed; retry with `retry_withdrawal_payout`.
+    PayoutFailed,
+    /// The ledger refused the burn, so nothing was burned.
+    Failed,
+}
+
+/// One wXRP burn and the XRP payout it releases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalRecord {
+    pub withdrawal_id: String,
+    pub principal: String,
+    pub destination: String,
+    pub destination_tag: Option<u32>,
+    pub drops: u64,
+    /// Fixed on the first attempt so retries hit the ledger's dedup window.
+    pub created_at_time: u64,
+    pub burn_block: Option<u64>,
+    pub xrpl_tx_hash: Option<String>,
+    pub status: WithdrawalStatus,
+    pub error: Option<String>,
+    pub created_at: u64,
+    /// Validated ledger before the first payout attempt; the search for an earlier payout starts here.
+    #[serde(default)]
+    pub payout_from_ledger: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WrappedXRPBook {
+    deposits: BTreeMap<String, WrapDepositRecord>,
+    withdrawals: BTreeMap<String, WithdrawalRecord>,
+    #[serde(default)]
+    parked_deposits: BTreeMap<String, ParkedDeposit>,
+    minted_drops: u64,
+    burned_drops: u64,
+}
+
+static WXRP_BOOK: Lazy<RwLock<WrappedXRPBook>> = Lazy::new(|| {
+    let book = load_json_state::<WrappedXRPBook>(WXRP_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load wXRP book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+/// Serializes mints and burns so each supply assertion sees only its own change.
+static WXRP_OP_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+fn persist(book: &WrappedXRPBook) {
+    if let Err(e) = persist_json_state(WXRP_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist wXRP book: {:?}", e));
+    }
+}
+
+fn nat_to_u64(n: &Nat) -> Result<u64> {
+    u64::try_from(&n.0).map_err(|_| anyhow::anyhow!("{} does not fit in u64", n))
+}
+
+/// Checks one operation moved ledger supply by exactly `expected_delta`
+/// and that the ledger agrees with the bridge's running mint/burn totals.
+fn assert_supply(op: &str, before: u64, after: u64, expected_delta: i128) -> Result<()> {
+    let actual = after as i128 - before as i128;
+    if actual != expected_delta {
+        bridge_log_event(
+            "error",
+            format!("🚨 wXRP supply moved by {} during {} (expected {})", actual, op, expected_delta),
+        );
+        return Err(anyhow::anyhow!("wXRP supply assertion failed for {}", op));
+    }
+
+    let book = WXRP_BOOK.read().unwrap();
+    let booked = book.minted_drops.saturating_sub(book.burned_drops);
+    if booked != after {
+        bridge_log_event(
+            "error",
+            format!("🚨 wXRP ledger supply {} differs from bridge books {} after {}", after, booked, op),
+        );
+    }
+    Ok(())
+}
+
+/// Reads a wrap deposit (XRP to the bridge tagged `WRAP_DEPOSIT_TAG`, or with a `WRAP` memo)
+/// off a validated Payment. A deposit with no principal to mint to is parked until its
+/// sender links one.
+pub fn ingest_wrap_deposit(tx_hash: &str, tx: &Value, meta: &Value) -> Option<PendingAction> {
+    let memo = memo_text_from_tx(tx).and_then(|text| parse_memo_string(&text).ok());
+    let wrap_memo = memo.as_ref().is_some_and(|m| m.action == XRPLActionType::WrapXRP);
+    if tx["DestinationTag"].as_u64() != Some(WRAP_DEPOSIT_TAG as u64) && !wrap_memo {
+        return None;
+    }
+    let delivered = if meta["delivered_amount"].is_null() { &tx["Amount"] } else { &meta["delivered_amount"] };
+    let drops = XRPLAmount::from_json(delivered)?.drops()?;
+
+    let sender = tx["Account"].as_str().unwrap_or_default();
+    let uuid = memo
+        .as_ref()
+        .and_then(|m| m.fields.get("UUID").cloned())
+        .unwrap_or_else(|| tx_hash.to_string());
+    let principal = memo
+        .as_ref()
+        .and_then(|m| m.fields.get("PRINCIPAL"))
+        .and_then(|p| Principal::from_text(p).ok())
+        .or_else(|| linked_principal(sender));
+
+    match principal {
+        Some(principal) => Some(wrap_action(tx_hash, principal, drops, uuid)),
+        None => {
+            bridge_log_event("warn", format!("⏸️ Wrap deposit {} names no principal and {} is unlinked; it waits for the link", tx_hash, sender));
+            let mut book = WXRP_BOOK.write().unwrap();
+            book.parked_deposits.insert(
+                tx_hash.to_string(),
+                ParkedDeposit {
+                    tx_hash: tx_hash.to_string(),
+                    sender: sender.to_string(),
+                    drops,
+                    uuid,
+                    refund: RefundSource::from_tx(tx_hash, tx, meta),
+                    parked_at: now_secs(),
+                },
+            );
+            persist(&book);
+            None
+        }
+    }
+}
+
+fn wrap_action(tx_hash: &str, principal: Principal, drops: u64, uuid: String) -> PendingAction {
+    PendingAction::WrapXRP {
+        principal,
+        drops: Nat::from(drops),
+        tx_hash: tx_hash.to_string(),
+        uuid,
+    }
+}
+
+/// Queues parked deposits whose sender has linked a principal since.
+pub fn release_linked_deposits() {
+    let ready: Vec<ParkedDeposit> = {
+        let mut book = WXRP_BOOK.write().unwrap();
+        let ready: Vec<String> = book
+            .parked_deposits
+            .values()
+            .filter(|parked| linked_principal(&parked.sender).is_some())
+            .map(|parked| parked.tx_hash.clone())
+            .collect();
+        if ready.is_empty() {
+            return;
+        }
+        let ready = ready.iter().filter_map(|hash| book.parked_deposits.remove(hash)).collect();
+        persist(&book);
+        ready
+    };
+
+    for parked in ready {
+        let Some(principal) = linked_principal(&parked.sender) else { continue };
+        let action = wrap_action(&parked.tx_hash, principal, parked.drops, parked.uuid);
+        let Some(action) = admit_action(&parked.sender, action, parked.refund) else { continue };
+        match enqueue_action(action) {
+            Ok(()) => bridge_log_event("wxrp", format!("▶️ Sender linked; queued wrap deposit {}", parked.tx_hash)),
+            Err(QueueError::AlreadyExists) => {}
+            Err(e) => bridge_log_event("error", format!("Failed to queue {}: {:?}", parked.tx_hash, e)),
+        }
+    }
+}
+
+/// ICRC-1 memos are capped at 32 bytes: a tx hash goes in as its raw 32 bytes.
+fn ledger_memo(reference: &str) -> Vec<u8> {
+    let is_hash = reference.len() == 64 && reference.bytes().all(|b| b.is_ascii_hexdigit());
+    if is_hash {
+        (0..64)
+            .step_by(2)
+            .filter_map(|i| u8::from_str_radix(&reference[i..i + 2], 16).ok())
+            .collect()
+    } else {
+        reference.bytes().take(32).collect()
+    }
+}
+
+/// A request to turn wXRP back into XRP.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalRequest {
+    pub withdrawal_id: String,
+    pub principal: String,
+    pub destination: String,
+    pub destination_tag: Option<u32>,
+    pub drops: u64,
+}
+
+/// Mints wXRP 1:1 for an XRPL deposit. Idempotent per deposit hash: retries mint the
+/// first attempt's principal and amount with its `created_at_time`.
+pub async fn mint_wrapped_xrp(
+    agent: &Agent,
+    config: &BridgeConfig,
+    principal: Principal,
+    drops: u64,
+    tx_hash: &str,
+) -> Result<WrapDepositRecord> {
+    if principal == Principal::anonymous() {
+        return Err(anyhow::anyhow!("Deposit {} has no principal to mint to", tx_hash));
+    }
+
+    let _guard = WXRP_OP_LOCK.lock().await;
+
+    let record = {
+        let mut book = WXRP_BOOK.write().unwrap();
+        let record = book.deposits.entry(tx_hash.to_string()).or_insert_with(|| WrapDepositRecord {
+            tx_hash: tx_hash.to_string(),
+            principal: principal.to_text(),
+            drops,
+            created_at_time: ledger_now(),
+            block_index: None,
+            supply_after: None,
+            outcome_unknown: false,
+            needs_review: false,
+            error: None,
+        });
+        if record.block_index.is_some() {
+            return Ok(record.clone());
+        }
+        if record.needs_review {
+            return Err(anyhow::anyhow!("Mint for deposit {} is held for review", tx_hash));
+        }
+        let record = record.clone();
+        persist(&book);
+        record
+    };
+    let principal = Principal::from_text(&record.principal)?;
+    let drops = record.drops;
+
+    let ledger = IcrcLedger::new(agent, &config.wxrp_ledger_canister_id)?;
+    let before = nat_to_u64(&ledger.icrc1_total_supply().await?)?;
+    let mint = TransferArg {
+        from_subaccount: None,
+        to: Account::of(principal),
+        amount: Nat::from(drops),
+        fee: None,
+        memo: Some(ledger_memo(tx_hash)),
+        created_at_time: Some(record.created_at_time),
+    };
+    // Set before sending so a restart mid-call still counts as an unknown outcome.
+    update_deposit(tx_hash, |d| d.outcome_unknown = true);
+
+    // As with burns, a failed call is settled by asking again with the identical request.
+    let mut asked_again = false;
+    let mut attempt = ledger.try_icrc1_transfer(mint.clone()).await;
+    if let Err(e) = &attempt {
+        bridge_log_event("warn", format!("⚠️ Mint for deposit {} has an unknown outcome, asking the ledger: {}", tx_hash, e));
+        asked_again = true;
+        attempt = ledger.try_icrc1_transfer(mint).await;
+    }
+
+    let outcome = match attempt {
+        Ok(Ok(outcome)) => outcome,
+        // Outside the dedup window the ledger can no longer tell us whether an earlier
+        // attempt landed. If none could have, a fresh window is safe; otherwise hold it.
+        Ok(Err(TransferError::TooOld)) if !record.outcome_unknown => {
+            update_deposit(tx_hash, |d| {
+                d.created_at_time = ledger_now();
+                d.outcome_unknown = false;
+                d.error = Some("created_at_time expired; re-stamped for the next attempt".to_string());
+            });
+            return Err(anyhow::anyhow!("Mint for deposit {} expired unapplied; re-stamped for retry", tx_hash));
+        }
+        Ok(Err(TransferError::TooOld)) => {
+            update_deposit(tx_hash, |d| {
+                d.needs_review = true;
+                d.error = Some("an earlier mint may have landed and the ledger no longer dedups it".to_string());
+            });
+            bridge_log_event("error", format!("🚨 Mint for deposit {} outlived the ledger's dedup window with an unknown outcome; held for review", tx_hash));
+            return Err(anyhow::anyhow!("Mint for deposit {} needs operator review", tx_hash));
+        }
+        Ok(Err(rejected)) => {
+            // Refused, so this attempt added nothing unknown.
+            update_deposit(tx_hash, |d| {
+                d.outcome_unknown = record.outcome_unknown;
+                d.error = Some(format!("{:?}", rejected));
+            });
+            return Err(anyhow::anyhow!("icrc1_transfer failed: {:?}", rejected));
+        }
+        Err(e) => {
+            update_deposit(tx_hash, |d| d.error = Some(format!("mint outcome unknown: {}", e)));
+            return Err(anyhow::anyhow!("Mint for deposit {} has an unknown outcome; it will be retried: {}", tx_hash, e));
+        }
+    };
+
+    let after = nat_to_u64(&ledger.icrc1_total_supply().await?)?;
+    let block_index = nat_to_u64(&outcome.block_index)?;
+
+    let updated = {
+        // Counted with the block index, so each deposit's mint is booked exactly once.
+        let mut book = WXRP_BOOK.write().unwrap();
+        book.minted_drops += drops;
+        let entry = book
+            .deposits
+            .get_mut(tx_hash)
+            .ok_or_else(|| anyhow::anyhow!("Deposit {} vanished", tx_hash))?;
+        entry.block_index = Some(block_index);
+        entry.supply_after = Some(after);
+        entry.outcome_unknown = false;
+        entry.error = None;
+        let updated = entry.clone();
+        persist(&book);
+        updated
+    };
+
+    let expected = match (outcome.duplicate, asked_again) {
+        (false, _) => drops as i128,
+        (true, false) => 0,
+        (true, true) if record.outcome_unknown && after == before => 0,
+        (true, true) => drops as i128,
+    };
+    assert_supply(&format!("mint {}", tx_hash), before, after, expected)?;
+
+    bridge_log_event("wxrp", format!("🪙 Minted {} wXRP to {} at block {}", drops, principal, block_index));
+    Ok(updated)
+}
+
+/// Burns a user's wXRP (via their ICRC-2 approval) and pays out the same amount of XRP
+/// to an XRPL account linked to them.
+pub async fn withdraw_wrapped_xrp(
+    agent: &Agent,
+    config: &BridgeConfig,
+    rpc: &XRPLRpcClient,
+    request: WithdrawalRequest,
+) -> Result<WithdrawalRecord> {
+    let WithdrawalRequest { withdrawal_id, principal, destination, destination_tag, drops } = request;
+    let withdrawal_id = withdrawal_id.as_str();
+    let principal = Principal::from_text(&principal)?;
+    if drops == 0 {
+        return Err(anyhow::anyhow!("Withdrawal amount must be non-zero"));
+    }
+    // The XRP may only go to an account the principal has proven it controls.
+    if linked_principal(&destination) != Some(principal) {
+        return Err(anyhow::anyhow!("{} is not an XRPL account linked to {}", destination, principal));
+    }
+    // Don't burn wXRP for a payout that can't be sent.
+    ensure_outbound_allowed(&format!("wxrp-withdraw-{}", withdrawal_id))?;
+
+    let _guard = WXRP_OP_LOCK.lock().await;
+
+    let (record, resumed) = {
+        let mut book = WXRP_BOOK.write().unwrap();
+        let (record, resumed) = match book.withdrawals.get_mut(withdrawal_id) {
+            // Retries keep the first `created_at_time` (and terms): if an earlier burn did
+            // land, the ledger answers `Duplicate` instead of burning a second time.
+            Some(existing) if matches!(existing.status, WithdrawalStatus::Burning | WithdrawalStatus::Failed) => {
+                if existing.principal != principal.to_text()
+                    || existing.destination != destination
+                    || existing.destination_tag != destination_tag
+                    || existing.drops != drops
+                {
+                    return Err(anyhow::anyhow!("Withdrawal {} was first requested with different terms", withdrawal_id));
+                }
+                existing.status = WithdrawalStatus::Burning;
+                existing.error = None;
+                (existing.clone(), true)
+            }
+            Some(existing) => return Ok(existing.clone()),
+            None => {
+                let record = WithdrawalRecord {
+                    withdrawal_id: withdrawal_id.to_string(),
+                    principal: principal.to_text(),
+                    destination,
+                    destination_tag,
+                    drops,
+                    created_at_time: ledger_now(),
+                    burn_block: None,
+                    xrpl_tx_hash: None,
+                    status: WithdrawalStatus::Burning,
+                    error: None,
+                    created_at: now_secs(),
+                    payout_from_ledger: None,
+                };
+                book.withdrawals.insert(withdrawal_id.to_string(), record.clone());
+                (record, false)
+            }
+        };
+        persist(&book);
+        (record, resumed)
+    };
+
+    let ledger = IcrcLedger::new(agent, &config.wxrp_ledger_canister_id)?;
+    let minting_account = match ledger.icrc1_minting_account().await? {
+        Some(account) => account,
+        None => Account::of(agent.get_principal().map_err(|e| anyhow::anyhow!(e))?),
+    };
+
+    let before = nat_to_u64(&ledger.icrc1_total_supply().await?)?;
+    let burn = TransferFromArgs {
+        spender_subaccount: None,
+        from: Account::of(principal),
+        to: minting_account,
+        amount: Nat::from(drops),
+        fee: None,
+        memo: Some(ledger_memo(withdrawal_id)),
+        created_at_time: Some(record.created_at_time),
+    };
+
+    // A failed call may still have burned. Asking again with the identical request settles
+    // it: the ledger either dedups it to the earlier block or applies it now, exactly once.
+    let mut asked_again = false;
+    let mut attempt = ledger.try_icrc2_transfer_from(burn.clone()).await;
+    if let Err(e) = &attempt {
+        bridge_log_event("warn", format!("⚠️ Burn for withdrawal {} has an unknown outcome, asking the ledger: {}", withdrawal_id, e));
+        asked_again = true;
+        attempt = ledger.try_icrc2_transfer_from(burn).await;
+    }
+
+    let outcome = match attempt {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(rejected)) => {
+            update_withdrawal(withdrawal_id, |w| {
+                w.status = WithdrawalStatus::Failed;
+                w.error = Some(format!("{:?}", rejected));
+            });
+            return Err(anyhow::anyhow!("icrc2_transfer_from failed: {:?}", rejected));
+        }
+        Err(e) => {
+            // Still unknown: stay `Burning` so the next retry resumes with the same request.
+            update_withdrawal(withdrawal_id, |w| w.error = Some(format!("burn outcome unknown: {}", e)));
+            return Err(anyhow::anyhow!("Burn for withdrawal {} has an unknown outcome; retry to settle it: {}", withdrawal_id, e));
+        }
+    };
+    let after = nat_to_u64(&ledger.icrc1_total_supply().await?)?;
+
+    {
+        // Counted with the status change, so each withdrawal's burn is booked exactly once.
+        let mut book = WXRP_BOOK.write().unwrap();
+        if let Some(w) = book.withdrawals.get_mut(withdrawal_id) {
+            w.status = WithdrawalStatus::Burned;
+            w.burn_block = nat_to_u64(&outcome.block_index).ok();
+            w.error = None;
+            book.burned_drops += drops;
+        }
+        persist(&book);
+    }
+
+    let expected = match (outcome.duplicate, asked_again) {
+        (false, _) => -(drops as i128),
+        // Burned on an earlier run, before `before` was read.
+        (true, false) => 0,
+        // Landed on this run's failed call, or (for a resumed withdrawal) possibly on an earlier run's.
+        (true, true) if resumed && after == before => 0,
+        (true, true) => -(drops as i128),
+    };
+    assert_supply(&format!("burn {}", withdrawal_id), before, after, expected)?;
+
+    pay_out(rpc, withdrawal_id).await
+}
+
+/// Retries the XRP leg of a withdrawal whose wXRP was already burned.
+pub async fn retry_withdrawal_payout(rpc: &XRPLRpcClient, withdrawal_id: &str) -> Result<WithdrawalRecord> {
+    let _guard = WXRP_OP_LOCK.lock().await;
+    pay_out(rpc, withdrawal_id).await
+}
+
+async fn pay_out(rpc: &XRPLRpcClient, withdrawal_id: &str) -> Result<WithdrawalRecord> {
+    let record = get_withdrawal(withdrawal_id).ok_or_else(|| anyhow::anyhow!("Unknown withdrawal {}", withdrawal_id))?;
+    if !matches!(record.status, WithdrawalStatus::Burned | WithdrawalStatus::PayoutFailed) {
+        return Err(anyhow::anyhow!("Withdrawal {} is {:?}", withdrawal_id, record.status));
+    }
+    let bridge = get_bridge_address().ok_or_else(|| anyhow::anyhow!("XRPL_BRIDGE_ADDRESS not set"))?;
+    let invoice_id = payout_invoice_id(withdrawal_id);
+
+    // An earlier attempt may have paid even though it reported a failure; never pay twice.
+    match record.payout_from_ledger {
+        Some(from_ledger) => {
+            if let Some(hash) = find_payout(rpc, &bridge, from_ledger, &invoice_id).await? {
+                bridge_log_event("wxrp", format!("🔎 Withdrawal {} was already paid in {}", withdrawal_id, hash));
+                return mark_paid(withdrawal_id, &record, Some(hash));
+            }
+        }
+        None => {
+            let from_ledger = rpc.validated_ledger_index().await?;
+            update_withdrawal(withdrawal_id, |w| w.payout_from_ledger = Some(from_ledger));
+        }
+    }
+
+    let mut payment = json!({
+        "TransactionType": "Payment",
+        "Destination": record.destination,
+        "Amount": record.drops.to_string(),
+        "InvoiceID": invoice_id,
+    });
+    if let Some(tag) = record.destination_tag {
+        payment["DestinationTag"] = json!(tag);
+    }
+
+    match submit_transfer(rpc, &format!("wxrp-withdraw-{}", withdrawal_id), payment).await {
+        Ok(tx) => mark_paid(withdrawal_id, &record, tx["hash"].as_str().map(str::to_string)),
+        Err(e) => {
+            update_withdrawal(withdrawal_id, |w| {
+                w.status = WithdrawalStatus::PayoutFailed;
+                w.error = Some(e.to_string());
+            });
+            Err(anyhow::anyhow!("wXRP burned but XRP payout failed: {}", e))
+        }
+    }
+}
+
+fn mark_paid(withdrawal_id: &str, record: &WithdrawalRecord, tx_hash: Option<String>) -> Result<WithdrawalRecord> {
+    let updated = update_withdrawal(withdrawal_id, |w| {
+        w.status = WithdrawalStatus::Paid;
+        w.xrpl_tx_hash = tx_hash;
+        w.error = None;
+    });
+    bridge_log_event("wxrp", format!("💸 Withdrawal {} paid {} drops to {}", withdrawal_id, record.drops, record.destination));
+    updated.ok_or_else(|| anyhow::anyhow!("Withdrawal {} vanished", withdrawal_id))
+}
+
+/// `InvoiceID` stamped on a withdrawal's payout, so the payment can be found on-ledger.
+fn payout_invoice_id(withdrawal_id: &str) -> String {
+    Sha256::digest(format!("wxrp-withdraw-{}", withdrawal_id).as_bytes())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+/// Hash of a successful bridge payout carrying `invoice_id` in an `account_tx` entry.
+fn payout_hash(entry: &Value, bridge: &str, invoice_id: &str) -> Option<String> {
+    // API v2 returns `tx_json` + `hash`; v1 returns `tx` with the hash inside.
+    let tx = if entry["tx_json"].is_object() { &entry["tx_json"] } else { &entry["tx"] };
+    let paid = entry["validated"] == true
+        && tx["TransactionType"] == "Payment"
+        && tx["Account"].as_str() == Some(bridge)
+        && tx["InvoiceID"].as_str().is_some_and(|id| id.eq_ignore_ascii_case(invoice_id))
+        && entry["meta"]["TransactionResult"] == "tesSUCCESS";
+    if !paid {
+        return None;
+    }
+    entry["hash"].as_str().or_else(|| tx["hash"].as_str()).map(str::to_string)
+}
+
+/// Searches the bridge's validated transactions since `from_ledger` for the payout.
+async fn find_payout(rpc: &XRPLRpcClient, bridge: &str, from_ledger: u32, invoice_id: &str) -> Result<Option<String>> {
+    let mut marker = None;
+    loop {
+        let (page, next) = rpc.account_tx_page(bridge, from_ledger as i64, -1, marker).await?;
+        if let Some(hash) = page.iter().find_map(|entry| payout_hash(entry, bridge, invoice_id)) {
+            return Ok(Some(hash));
+        }
+        match next {
+            Some(next) => marker = Some(next),
+            None => return Ok(None),
+        }
+    }
+}
+
+fn update_withdrawal(withdrawal_id: &str, change: impl FnOnce(&mut WithdrawalRecord)) -> Option<WithdrawalRecord> {
+    let mut book = WXRP_BOOK.write().unwrap();
+    let record = book.withdrawals.get_mut(withdrawal_id)?;
+    change(record);
+    let updated = record.clone();
+    persist(&book);
+    Some(updated)
+}
+
+pub fn get_withdrawal(withdrawal_id: &str) -> Option<WithdrawalRecord> {
+    WXRP_BOOK.read().unwrap().withdrawals.get(withdrawal_id).cloned()
+}
+
+/// Whether a wrap deposit is parked until its sender links a principal.
+pub fn is_parked_deposit(tx_hash: &str) -> bool {
+    WXRP_BOOK.read().unwrap().parked_deposits.keys().any(|hash| hash.eq_ignore_ascii_case(tx_hash))
+}
+
+pub fn get_wrap_deposit(tx_hash: &str) -> Option<WrapDepositRecord> {
+    WXRP_BOOK.read().unwrap().deposits.get(tx_hash).cloned()
+}
+
+fn update_deposit(tx_hash: &str, change: impl FnOnce(&mut WrapDepositRecord)) -> Option<WrapDepositRecord> {
+    let mut book = WXRP_BOOK.write().unwrap();
+    let record = book.deposits.get_mut(tx_hash)?;
+    change(record);
+    let updated = record.clone();
+    persist(&book);
+    Some(updated)
+}
+
+/// Mints a held deposit again under a fresh `created_at_time`, once an operator has
+/// checked the ledger and found no earlier mint for it.
+pub async fn remint_wrap_deposit(agent: &Agent, config: &BridgeConfig, tx_hash: &str) -> Result<WrapDepositRecord> {
+    let record = {
+        let _guard = WXRP_OP_LOCK.lock().await;
+        let record = get_wrap_deposit(tx_hash).ok_or_else(|| anyhow::anyhow!("Unknown deposit {}", tx_hash))?;
+        if !record.needs_review {
+            return Err(anyhow::anyhow!("Deposit {} is not held for review", tx_hash));
+        }
+        update_deposit(tx_hash, |d| {
+            d.created_at_time = ledger_now();
+            d.outcome_unknown = false;
+            d.needs_review = false;
+            d.error = None;
+        });
+        record
+    };
+    bridge_log_event("wxrp", format!("🔁 Operator cleared deposit {} for a fresh mint", tx_hash));
+    mint_wrapped_xrp(agent, config, Principal::from_text(&record.principal)?, record.drops, tx_hash).await
+}
+
+/// Retries mints that failed or never got an answer, unless inbound WrapXRP is paused.
+async fn retry_pending_mints(agent: &Agent, config: &BridgeConfig) {
+    let pending: Vec<WrapDepositRecord> = WXRP_BOOK
+        .read()
+        .unwrap()
+        .deposits
+        .values()
+        .filter(|d| d.block_index.is_none() && !d.needs_review)
+        .cloned()
+        .collect();
+
+    for deposit in pending {
+        let Ok(principal) = Principal::from_text(&deposit.principal) else { continue };
+        if dispatch_paused(&wrap_action(&deposit.tx_hash, principal, deposit.drops, deposit.tx_hash.clone())) {
+            return;
+        }
+        if let Err(e) = mint_wrapped_xrp(agent, config, principal, deposit.drops, &deposit.tx_hash).await {
+            bridge_log_event("warn", format!("⚠️ Retried mint for deposit {} failed: {}", deposit.tx_hash, e));
+        }
+    }
+}
+
+/// Queues parked deposits whose sender has linked and retries unfinished mints, so a
+/// deposit's XRP always ends up backing wXRP.
+pub async fn run_wxrp_maintenance(agent: Agent, config: BridgeConfig, interval_secs: u64) {
+    loop {
+        release_linked_deposits();
+        retry_pending_mints(&agent, &config).await;
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
+
+/// Running totals and both record sets, for the admin API.
+pub fn wxrp_summary() -> Value {
+    let book = WXRP_BOOK.read().unwrap();
+    json!({
+        "minted_drops": book.minted_drops,
+        "burned_drops": book.burned_drops,
+        "outstanding_drops": book.minted_drops.saturating_sub(book.burned_drops),
+        "deposits": book.deposits.values().collect::<Vec<_>>(),
+        "parked_deposits": book.parked_deposits.values().collect::<Vec<_>>(),
+        "withdrawals": book.withdrawals.values().collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payouts_match_on_invoice_id_and_success() {
+        let invoice = payout_invoice_id("w-1");
+        let entry = |invoice_id: &str, result: &str| {
+            json!({
+                "validated": true,
+                "hash": "ABC",
+                "tx_json": { "TransactionType": "Payment", "Account": "rBridge", "InvoiceID": invoice_id },
+                "meta": { "TransactionResult": result },
+            })
+        };
+
+        assert_eq!(payout_hash(&entry(&invoice.to_lowercase(), "tesSUCCESS"), "rBridge", &invoice), Some("ABC".into()));
+        assert_eq!(payout_hash(&entry(&invoice, "tecPATH_DRY"), "rBridge", &invoice), None);
+        assert_eq!(payout_hash(&entry(&payout_invoice_id("w-2"), "tesSUCCESS"), "rBridge", &invoice), None);
+        assert_eq!(payout_hash(&entry(&invoice, "tesSUCCESS"), "rOther", &invoice), None);
+        assert_eq!(invoice.len(), 64);
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::config::get_xrpl_mainnet;
+
+/// XRPL's base58 alphabet (not Bitcoin's).
+const ALPHABET: &[u8; 58] = b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz";
+
+const CLASSIC_VERSION: u8 = 0x00;
+const X_PREFIX_MAIN: [u8; 2] = [0x05, 0x44];
+const X_PREFIX_TEST: [u8; 2] = [0x04, 0x93];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    InvalidBase58,
+    BadChecksum,
+    BadPrefix,
+    /// X-address network flag doesn't match the bridge's network.
+    WrongNetwork { test: bool },
+    /// X-address tag and a separate destination tag disagree.
+    ConflictingTag,
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::InvalidBase58 => write!(f, "Invalid base58 address"),
+            AddressError::BadChecksum => write!(f, "Address checksum mismatch"),
+            AddressError::BadPrefix => write!(f, "Unknown address prefix"),
+            AddressError::WrongNetwork { test: true } => write!(f, "Test-network X-address on a mainnet bridge"),
+            AddressError::WrongNetwork { test: false } => write!(f, "Mainnet X-address on a test-network bridge"),
+            AddressError::ConflictingTag => write!(f, "X-address tag conflicts with destination tag"),
+        }
+    }
+}
+
+/// Classic address plus the tag and network flag an X-address carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedXAddress {
+    pub classic: String,
+    pub tag: Option<u32>,
+    pub test: bool,
+}
+
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    let digest = Sha256::digest(Sha256::digest(payload));
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    let mut digits: Vec<u8> = Vec::new();
+
+    for &byte in &bytes[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    std::iter::repeat_n(ALPHABET[0], zeros)
+        .chain(digits.iter().rev().map(|d| ALPHABET[*d as usize]))
+        .map(char::from)
+        .collect()
+}
+
+fn base58_decode(s: &str) -> Option<Vec<u8>> {
+    let zeros = s.bytes().take_while(|c| *c == ALPHABET[0]).count();
+    let mut bytes: Vec<u8> = Vec::new();
+
+    for c in s.bytes().skip(zeros) {
+        let mut carry = ALPHABET.iter().position(|a| *a == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xFF) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xFF) as u8);
+            carry >>= 8;
+        }
+    }
+
+    Some(std::iter::repeat_n(0, zeros).chain(bytes.into_iter().rev()).collect())
+}
+
+/// Decodes base58check, returning the payload without its checksum.
+fn decode_checked(s: &str) -> Result<Vec<u8>, AddressError> {
+    let raw = base58_decode(s).ok_or(AddressError::InvalidBase58)?;
+    if raw.len() < 5 {
+        return Err(AddressError::InvalidBase58);
+    }
+    let (payload, check) = raw.split_at(raw.len() - 4);
+    if checksum(payload) != check {
+        return Err(AddressError::BadChecksum);
+    }
+    Ok(payload.to_vec())
+}
+
+fn encode_checked(payload: &[u8]) -> String {
+    let mut raw = payload.to_vec();
+    raw.extend_from_slice(&checksum(payload));
+    base58_encode(&raw)
+}
+
+/// 20-byte account ID of a classic `r...` address.
+pub fn decode_classic_address(address: &str) -> Result<[u8; 20], AddressError> {
+    let payload = decode_checked(address)?;
+    if payload.len() != 21 || payload[0] != CLASSIC_VERSION {
+        return Err(AddressError::BadPrefix);
+    }
+    let mut account_id = [0u8; 20];
+    account_id.copy_from_slice(&payload[1..]);
+    Ok(account_id)
+}
+
+pub fn encode_classic_address(account_id: &[u8; 20]) -> String {
+    let mut payload = vec![CLASSIC_VERSION];
+    payload.extend_from_slice(account_id);
+    encode_checked(&payload)
+}
+
+/// Looks like an X-address (`X...` mainnet, `T...` test); not a validity check.
+pub fn is_x_address(address: &str) -> bool {
+    address.len() == 47 && (address.starts_with('X') || address.starts_with('T'))
+}
+
+pub fn encode_x_address(classic: &str, tag: Option<u32>, test: bool) -> Result<String, AddressError> {
+    let account_id = decode_classic_address(classic)?;
+    let mut payload = Vec::with_capacity(31);
+    payload.extend_from_slice(if test { &X_PREFIX_TEST } else { &X_PREFIX_MAIN });
+    payload.extend_from_slice(&account_id);
+    payload.push(tag.is_some() as u8);
+    payload.extend_from_slice(&tag.unwrap_or(0).to_le_bytes());
+    // Reserved for 64-bit tags; must be zero.
+    payload.extend_from_slice(&[0u8; 4]);
+    Ok(encode_checked(&payload))
+}
+
+pub fn decode_x_address(address: &str) -> Result<DecodedXAddress, AddressError> {
+    let payload = decode_checked(address)?;
+    if payload.len() != 31 {
+        return Err(AddressError::BadPrefix);
+    }
+    let test = match [payload[0], payload[1]] {
+        X_PREFIX_MAIN => false,
+        X_PREFIX_TEST => true,
+        _ => return Err(AddressError::BadPrefix),
+    };
+
+    let mut account_id = [0u8; 20];
+    account_id.copy_from_slice(&payload[2..22]);
+    let tag = match payload[22] {
+        0 => None,
+        1 => Some(u32::from_le_bytes([payload[23], payload[24], payload[25], payload[26]])),
+        _ => return Err(AddressError::BadPrefix),
+    };
+    if payload[27..31] != [0u8; 4] || (tag.is_none() && payload[23..27] != [0u8; 4]) {
+        return Err(AddressError::BadPrefix);
+    }
+
+    Ok(DecodedXAddress {
+        classic: encode_classic_address(&account_id),
+        tag,
+        test,
+    })
+}
+
+/// Resolves a destination (classic or X-address) to classic address + tag for this
+/// bridge's network. An X-address's tag must agree with any separate tag.
+pub fn normalize_destination(address: &str, tag: Option<u32>) -> Result<(String, Option<u32>), AddressError> {
+    if !is_x_address(address) {
+        return Ok((address.to_string(), tag));
+    }
+
+    let decoded = decode_x_address(address)?;
+    if decoded.test == get_xrpl_mainnet() {
+        return Err(AddressError::WrongNetwork { test: decoded.test });
+    }
+    match (decoded.tag, tag) {
+        (Some(a), Some(b)) if a != b => Err(AddressError::ConflictingTag),
+        (a, b) => Ok((decoded.classic, a.or(b))),
+    }
+}
+
+/// X-address for `classic` + `tag` on this bridge's network.
+pub fn bridge_x_address(classic: &str, tag: Option<u32>) -> Result<String, AddressError> {
+    encode_x_address(classic, tag, !get_xrpl_mainnet())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLASSIC: &str = "r9cZA1mLK5R5Am25ArfXFmqgNwjZgnfk59";
+
+    // Vectors from the XLS-5d (X-address) specification.
+    const VECTORS: [(Option<u32>, bool, &str); 5] = [
+        (None, false, "X7AcgcsBL6XDcUb289X4mJ8djcdyKaB5hJDWMArnXr61cqZ"),
+        (Some(1), false, "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGZMhc9YTE92ehJ2Fu"),
+        (Some(14), false, "X7AcgcsBL6XDcUb289X4mJ8djcdyKaGo2K5VpXpmCqbV2gS"),
+        (Some(11747), false, "X7AcgcsBL6XDcUb289X4mJ8djcdyKaLFuhLRuNXPrDeJd9A"),
+        (None, true, "T719a5UwUCnEs54UsxG9CJYYDhwmFCqkr7wxCcNcfZ6p5GZ"),
+    ];
+
+    #[test]
+    fn x_addresses_match_known_vectors() {
+        for (tag, test, x_address) in VECTORS {
+            assert_eq!(encode_x_address(CLASSIC, tag, test).unwrap(), x_address);
+            let decoded = decode_x_address(x_address).unwrap();
+            assert_eq!(decoded, DecodedXAddress { classic: CLASSIC.to_string(), tag, test });
+        }
+        assert_eq!(
+            encode_x_address("rGWrZyQqhTp9Xu7G5Pkayo7bXjH4k4QYpf", None, false).unwrap(),
+            "XVLhHMPHU98es4dbozjVtdWzVrDjtV5fdx1mHp98tDMoQXb"
+        );
+    }
+
+    #[test]
+    fn corrupted_x_addresses_are_rejected() {
+        let mut corrupted = VECTORS[1].2.to_string();
+        corrupted.replace_range(10..11, "a");
+        assert_eq!(decode_x_address(&corrupted), Err(AddressError::BadChecksum));
+        assert_eq!(decode_x_address(CLASSIC), Err(AddressError::BadPrefix));
+        assert_eq!(decode_x_address("X0AcgcsBL6XDcUb289X4mJ8djcdyKaB5hJDWMArnXr61cqZ"), Err(AddressError::InvalidBase58));
+        let tagged = bridge_x_address(CLASSIC, Some(1)).unwrap();
+        assert_eq!(normalize_destination(&tagged, Some(2)), Err(AddressError::ConflictingTag));
+        assert_eq!(normalize_destination(&tagged, None).unwrap(), (CLASSIC.to_string(), Some(1)));
+    }
+
+    #[test]
+    fn classic_addresses_round_trip() {
+        let account_id = decode_classic_address(CLASSIC).unwrap();
+        assert_eq!(encode_classic_address(&account_id), CLASSIC);
+        assert_eq!(normalize_destination(CLASSIC, Some(7)).unwrap(), (CLASSIC.to_string(), Some(7)));
+    }
+}
@@ -0,0 +1,718 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use candid::{CandidType, Int, Nat};
+use ic_agent::Agent;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    get_approval_approvers, get_approval_quorum, get_approval_threshold_iou_units, get_approval_ttl_secs,
+    get_hil_approvals_enabled, get_iou_decimals, ApproverConfig, BridgeConfig,
+};
+use crate::ic_trigger::{fetch_hil_approval_request, submit_hil_approval_request};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::{enqueue_action, PendingAction, QueueError};
+use crate::xrpl::limits::{count_action, limit_breaches, try_count_action, BreachAction, RuleUsage};
+use crate::xrpl::params::approval_threshold_drops;
+use crate::xrpl::refunds::{record_refund, RefundDecision, RefundSource, RefundStatus};
+use crate::xrpl::types::XRPLError;
+use crate::util::now_secs;
+
+const APPROVAL_BOOK_FILE: &str = "approvals.json";
+
+/// Correlation ids the bridge opens with the HIL service are `<prefix><tx_hash>`.
+const HIL_CORRELATION_PREFIX: &str = "xrpl-bridge:";
+
+/// Why an action was held instead of queued.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HoldReason {
+    /// Credits more than the (governed) approval threshold.
+    AboveThreshold { drops: u64, threshold_drops: u64 },
+    /// Redeems more IOU units than the IOU approval threshold.
+    AboveIouThreshold { units: u64, threshold_units: u64 },
+    /// A counterparty was flagged (but not blocked) by screening.
+    Flagged(String),
+    LimitBreach(Vec<RuleUsage>),
+}
+
+impl HoldReason {
+    fn describe(&self) -> String {
+        match self {
+            HoldReason::AboveThreshold { drops, threshold_drops } => {
+                format!("{} drops above approval threshold {}", drops, threshold_drops)
+            }
+            HoldReason::AboveIouThreshold { units, threshold_units } => {
+                format!("{} IOU units above approval threshold {}", units, threshold_units)
+            }
+            HoldReason::Flagged(reason) => format!("screening flag: {}", reason),
+            HoldReason::LimitBreach(breaches) => format!(
+                "limit breach: {}",
+                breaches.iter().map(|b| b.rule_id.as_str()).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HeldStatus {
+    /// Waiting for approvers.
+    Held,
+    /// Reached quorum (or was approved through HIL) and queued.
+    Approved,
+    /// Refused; refunded when the payment can be returned.
+    Rejected,
+    /// Nobody decided in time; refunded when the payment can be returned.
+    Expired,
+    /// Returned straight away under a limit rule's refund policy.
+    Refunded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApprovalSource {
+    Admin,
+    Hil,
+}
+
+/// One approver's vote for a held action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub approver: String,
+    pub source: ApprovalSource,
+    pub at: u64,
+}
+
+/// An action kept out of the queue until enough approvers release it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldAction {
+    pub tx_hash: String,
+    pub sender: String,
+    pub action: PendingAction,
+    pub reasons: Vec<HoldReason>,
+    /// Present when the funds arrived on-ledger and could be returned.
+    pub refund: Option<RefundSource>,
+    pub refund_status: Option<RefundStatus>,
+    pub status: HeldStatus,
+    /// Distinct local approvals needed, fixed when the action was held.
+    pub quorum: usize,
+    pub approvals: Vec<Approval>,
+    /// Who rejected it, or `hil:<approver>` for HIL decisions.
+    pub decided_by: Option<String>,
+    pub decided_at: Option<u64>,
+    /// Set once the request is open with the HIL service.
+    pub hil_correlation_id: Option<String>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub updated_at: u64,
+}
+
+impl HeldAction {
+    /// Adds one approver's vote, approving once `quorum` distinct approvers agree
+    /// (or straight away when `quorum_met`). Votes after `expires_at` are refused.
+    fn add_approval(&mut self, approver: &str, source: ApprovalSource, quorum_met: bool, now: u64) -> Result<(), XRPLError> {
+        if self.status != HeldStatus::Held {
+            return Err(XRPLError::InvalidTransaction(format!("{} is already {:?}", self.tx_hash, self.status)));
+        }
+        if now >= self.expires_at {
+            return Err(XRPLError::InvalidTransaction(format!("Approval window for {} has closed", self.tx_hash)));
+        }
+        if self.approvals.iter().any(|a| a.approver == approver) {
+            return Err(XRPLError::InvalidTransaction(format!("{} already approved {}", approver, self.tx_hash)));
+        }
+
+        self.approvals.push(Approval { approver: approver.to_string(), source, at: now });
+        self.updated_at = now;
+        if quorum_met || self.approvals.len() >= self.quorum {
+            self.status = HeldStatus::Approved;
+            self.decided_at = Some(now);
+        }
+        Ok(())
+    }
+
+    /// Moves a still-held action to `status` (rejected or expired).
+    fn close(&mut self, status: HeldStatus, decided_by: Option<&str>, now: u64) -> Result<(), XRPLError> {
+        if self.status != HeldStatus::Held {
+            return Err(XRPLError::InvalidTransaction(format!("{} is already {:?}", self.tx_hash, self.status)));
+        }
+        self.status = status;
+        self.decided_by = decided_by.map(str::to_string);
+        self.decided_at = Some(now);
+        self.updated_at = now;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ApprovalBook {
+    held: BTreeMap<String, HeldAction>,
+    /// Screening flags waiting for the flagged transaction to be admitted.
+    flags: BTreeMap<String, String>,
+}
+
+static APPROVAL_BOOK: Lazy<RwLock<ApprovalBook>> = Lazy::new(|| {
+    let book = load_json_state::<ApprovalBook>(APPROVAL_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load approval book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+fn persist(book: &ApprovalBook) {
+    if let Err(e) = persist_json_state(APPROVAL_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist approval book: {:?}", e));
+    }
+}
+
+/// Marks a transaction that screening flagged; its action will be held when admitted.
+pub fn flag_for_approval(tx_hash: &str, reason: &str) {
+    let mut book = APPROVAL_BOOK.write().unwrap();
+    book.flags.insert(tx_hash.to_string(), reason.to_string());
+    persist(&book);
+}
+
+/// Checks a verified action against the approval threshold, screening flags and
+/// limit rules. Actions that pass are counted toward the limits and handed back
+/// for queueing; the rest are held for approval, or refunded when a breached
+/// limit asks for it. Escrow registrations move no value and are always admitted.
+pub fn admit_action(sender: &str, action: PendingAction, refund: Option<RefundSource>) -> Option<PendingAction> {
+    if matches!(action, PendingAction::EscrowRegistration { .. }) {
+        return Some(action);
+    }
+
+    let tx_hash = action.tx_hash().to_string();
+    let flag = {
+        let book = APPROVAL_BOOK.read().unwrap();
+        // Re-delivered: already held or decided.
+        if book.held.contains_key(&tx_hash) {
+            return None;
+        }
+        book.flags.get(&tx_hash).cloned()
+    };
+
+    let mut reasons = Vec::new();
+    let drops = action.xrp_drops();
+    if let Some(threshold_drops) = approval_threshold_drops().filter(|t| drops > *t) {
+        reasons.push(HoldReason::AboveThreshold { drops, threshold_drops });
+    }
+    let units = action.iou_units();
+    if let Some(threshold_units) = get_approval_threshold_iou_units().filter(|t| units > *t) {
+        reasons.push(HoldReason::AboveIouThreshold { units, threshold_units });
+    }
+    if let Some(flag) = flag {
+        reasons.push(HoldReason::Flagged(flag));
+    }
+
+    // Held actions are only counted toward the limits once approved.
+    let breached = if reasons.is_empty() {
+        match try_count_action(sender, &action) {
+            Ok(()) => return Some(action),
+            Err(breached) => breached,
+        }
+    } else {
+        limit_breaches(sender, &action)
+    };
+
+    // Only XRP that reached the bridge can be sent back.
+    let refund = refund.filter(|source| source.delivered.drops().is_some());
+    let wants_refund = breached.iter().any(|b| b.on_breach == BreachAction::Refund);
+    if !breached.is_empty() {
+        reasons.push(HoldReason::LimitBreach(breached));
+    }
+    let refund_status = match (&refund, wants_refund) {
+        (Some(source), true) => Some(record_refund(source.clone(), RefundDecision::Refund("velocity limit exceeded")).status),
+        _ => None,
+    };
+    let refunded = refund_status == Some(RefundStatus::Pending);
+
+    let now = now_secs();
+    let held = HeldAction {
+        tx_hash: tx_hash.clone(),
+        sender: sender.to_string(),
+        action,
+        reasons,
+        refund,
+        refund_status,
+        status: if refunded { HeldStatus::Refunded } else { HeldStatus::Held },
+        quorum: get_approval_quorum(),
+        approvals: Vec::new(),
+        decided_by: None,
+        decided_at: None,
+        hil_correlation_id: None,
+        created_at: now,
+        expires_at: now + get_approval_ttl_secs(),
+        updated_at: now,
+    };
+    bridge_log_event(
+        "approvals",
+        format!(
+            "🛑 {} from {} — {} — {:?}",
+            tx_hash,
+            sender,
+            held.reasons.iter().map(HoldReason::describe).collect::<Vec<_>>().join("; "),
+            held.status
+        ),
+    );
+
+    let mut book = APPROVAL_BOOK.write().unwrap();
+    book.flags.remove(&tx_hash);
+    book.held.insert(tx_hash, held);
+    persist(&book);
+    None
+}
+
+/// Queues an approved action and counts it toward the limits.
+fn release(held: &HeldAction) -> Result<(), XRPLError> {
+    count_action(&held.sender, &held.action);
+    match enqueue_action(held.action.clone()) {
+        Ok(()) | Err(QueueError::AlreadyExists) => {}
+        Err(e) => return Err(XRPLError::Other(format!("Approved {} but could not queue it: {:?}", held.tx_hash, e))),
+    }
+    bridge_log_event(
+        "approvals",
+        format!(
+            "✅ Released {} ({})",
+            held.tx_hash,
+            held.approvals.iter().map(|a| a.approver.as_str()).collect::<Vec<_>>().join(", ")
+        ),
+    );
+    Ok(())
+}
+
+/// Records one approver's vote; the action is queued once `quorum` distinct approvers agree.
+fn record_approval(tx_hash: &str, approver: &str, source: ApprovalSource, quorum_met: bool) -> Result<HeldAction, XRPLError> {
+    let held = {
+        let mut book = APPROVAL_BOOK.write().unwrap();
+        let held = book
+            .held
+            .get_mut(tx_hash)
+            .ok_or_else(|| XRPLError::Other(format!("Nothing held for {}", tx_hash)))?;
+        held.add_approval(approver, source, quorum_met, now_secs())?;
+        let held = held.clone();
+        persist(&book);
+        held
+    };
+
+    if held.status == HeldStatus::Approved {
+        release(&held)?;
+    } else {
+        bridge_log_event(
+            "approvals",
+            format!("👍 {} approved {} ({}/{})", approver, tx_hash, held.approvals.len(), held.quorum),
+        );
+    }
+    Ok(held)
+}
+
+/// Resolves which approver presented `token`. Only configured approvers can vote locally.
+fn approver_for_token(approvers: &[ApproverConfig], token: &str) -> Result<String, XRPLError> {
+    approvers
+        .iter()
+        .find(|a| a.token == token)
+        .map(|a| a.name.clone())
+        .ok_or_else(|| XRPLError::InvalidTransaction("Unknown approver token".into()))
+}
+
+/// Approver vote through the admin API, identified by the approver's own token.
+pub fn approve_held_action(tx_hash: &str, approver_token: &str) -> Result<HeldAction, XRPLError> {
+    let approver = approver_for_token(&get_approval_approvers(), approver_token)?;
+    record_approval(tx_hash, &approver, ApprovalSource::Admin, false)
+}
+
+/// Closes a held action without queueing it, returning the payment when possible.
+/// The status check and transition happen under one lock, before the refund is recorded.
+fn close_held(tx_hash: &str, status: HeldStatus, decided_by: Option<&str>, reason: &'static str) -> Result<HeldAction, XRPLError> {
+    let source = {
+        let mut book = APPROVAL_BOOK.write().unwrap();
+        let held = book
+            .held
+            .get_mut(tx_hash)
+            .ok_or_else(|| XRPLError::Other(format!("Nothing held for {}", tx_hash)))?;
+        held.close(status, decided_by, now_secs())?;
+        let source = held.refund.clone();
+        persist(&book);
+        source
+    };
+    let refund_status = source.map(|source| record_refund(source, RefundDecision::Refund(reason)).status);
+
+    let mut book = APPROVAL_BOOK.write().unwrap();
+    let held = book
+        .held
+        .get_mut(tx_hash)
+        .ok_or_else(|| XRPLError::Other(format!("Nothing held for {}", tx_hash)))?;
+    held.refund_status = refund_status.or(held.refund_status);
+    let held = held.clone();
+    persist(&book);
+
+    bridge_log_event(
+        "approvals",
+        format!("↩️ {} {:?} by {} (refund {:?})", tx_hash, status, decided_by.unwrap_or("timeout"), held.refund_status),
+    );
+    Ok(held)
+}
+
+/// Approver refusal through the admin API; one rejection is enough.
+pub fn reject_held_action(tx_hash: &str, approver_token: &str) -> Result<HeldAction, XRPLError> {
+    let approver = approver_for_token(&get_approval_approvers(), approver_token)?;
+    close_held(tx_hash, HeldStatus::Rejected, Some(&approver), "rejected by approver")
+}
+
+/// Expires held actions nobody decided on, refunding them where possible.
+pub fn expire_held_actions() {
+    let now = now_secs();
+    for held in list_held_actions(Some(HeldStatus::Held)).into_iter().filter(|h| h.expires_at <= now) {
+        if let Err(e) = close_held(&held.tx_hash, HeldStatus::Expired, None, "approval expired") {
+            bridge_log_event("error", format!("❌ Could not expire held {}: {}", held.tx_hash, e));
+        }
+    }
+}
+
+pub fn get_held_action(tx_hash: &str) -> Option<HeldAction> {
+    APPROVAL_BOOK.read().unwrap().held.get(tx_hash).cloned()
+}
+
+pub fn list_held_actions(status: Option<HeldStatus>) -> Vec<HeldAction> {
+    APPROVAL_BOOK
+        .read()
+        .unwrap()
+        .held
+        .values()
+        .filter(|h| status.is_none_or(|s| h.status == s))
+        .cloned()
+        .collect()
+}
+
+/// Mirror of the `hil_service` `HILRequestType` variant.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub enum HILRequestType {
+    EscrowRelease {
+        #[serde(rename = "transactionId")]
+        transaction_id: String,
+        amount: f64,
+    },
+    ComplianceOverride {
+        #[serde(rename = "violationType")]
+        violation_type: String,
+        #[serde(rename = "riskLevel")]
+        risk_level: String,
+    },
+    HighValueTransaction { amount: f64, threshold: f64 },
+    SanctionsFlag {
+        #[serde(rename = "entityId")]
+        entity_id: String,
+        #[serde(rename = "flagType")]
+        flag_type: String,
+    },
+    EmergencyEscalation { reason: String, urgency: String },
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub enum HILPriority {
+    Critical {
+        #[serde(rename = "slaMinutes")]
+        sla_minutes: Nat,
+    },
+    High {
+        #[serde(rename = "slaMinutes")]
+        sla_minutes: Nat,
+    },
+    Medium {
+        #[serde(rename = "slaMinutes")]
+        sla_minutes: Nat,
+    },
+    Low {
+        #[serde(rename = "slaMinutes")]
+        sla_minutes: Nat,
+    },
+}
+
+/// Mirror of the `hil_service` `ApprovalStatus` variant; times are IC nanoseconds.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub enum HILApprovalStatus {
+    Pending,
+    Acknowledged { by: String, at: Int },
+    UnderReview {
+        by: String,
+        #[serde(rename = "startedAt")]
+        started_at: Int,
+    },
+    Approved { by: String, at: Int, reasoning: String },
+    Denied { by: String, at: Int, reasoning: String },
+    Escalated { to: String, at: Int, reason: String },
+    Expired { at: Int },
+    AutoClosed { at: Int, reason: String },
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct HILRiskAssessment {
+    #[serde(rename = "riskScore")]
+    pub risk_score: f64,
+    #[serde(rename = "riskFactors")]
+    pub risk_factors: Vec<String>,
+    #[serde(rename = "mitigationSuggestions")]
+    pub mitigation_suggestions: Vec<String>,
+    #[serde(rename = "escalationTriggers")]
+    pub escalation_triggers: Vec<String>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct HILAuditBundle {
+    #[serde(rename = "featuresHash")]
+    pub features_hash: String,
+    #[serde(rename = "aiFactors")]
+    pub ai_factors: Vec<String>,
+    pub confidence: f64,
+    pub recommendation: String,
+    #[serde(rename = "fallbackReason")]
+    pub fallback_reason: Option<String>,
+    #[serde(rename = "originalRequest")]
+    pub original_request: String,
+    #[serde(rename = "decisionPath")]
+    pub decision_path: Vec<String>,
+    #[serde(rename = "riskAssessment")]
+    pub risk_assessment: HILRiskAssessment,
+    #[serde(rename = "complianceFlags")]
+    pub compliance_flags: Vec<String>,
+}
+
+/// Mirror of the `hil_service` `ApprovalRequest` record.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct HILApprovalRequest {
+    #[serde(rename = "correlationId")]
+    pub correlation_id: String,
+    #[serde(rename = "requestType")]
+    pub request_type: HILRequestType,
+    pub priority: HILPriority,
+    #[serde(rename = "submittedAt")]
+    pub submitted_at: Int,
+    #[serde(rename = "slaExpiresAt")]
+    pub sla_expires_at: Int,
+    #[serde(rename = "auditBundle")]
+    pub audit_bundle: HILAuditBundle,
+    pub status: HILApprovalStatus,
+    #[serde(rename = "assignedTo")]
+    pub assigned_to: Option<String>,
+    #[serde(rename = "escalationLevel")]
+    pub escalation_level: Nat,
+    pub metadata: Vec<(String, String)>,
+}
+
+fn hil_request(held: &HeldAction) -> HILApprovalRequest {
+    let nanos = |secs: u64| Int::from(secs as i128 * 1_000_000_000);
+    let xrp = |drops: u64| drops as f64 / 1_000_000.0;
+    let iou = |units: u64| units as f64 / 10f64.powi(get_iou_decimals() as i32);
+
+    let request_type = held
+        .reasons
+        .iter()
+        .find_map(|reason| match reason {
+            HoldReason::Flagged(flag) => Some(HILRequestType::SanctionsFlag {
+                entity_id: held.sender.clone(),
+                flag_type: flag.clone(),
+            }),
+            _ => None,
+        })
+        .or_else(|| {
+            held.reasons.iter().find_map(|reason| match reason {
+                HoldReason::AboveThreshold { drops, threshold_drops } => Some(HILRequestType::HighValueTransaction {
+                    amount: xrp(*drops),
+                    threshold: xrp(*threshold_drops),
+                }),
+                HoldReason::AboveIouThreshold { units, threshold_units } => Some(HILRequestType::HighValueTransaction {
+                    amount: iou(*units),
+                    threshold: iou(*threshold_units),
+                }),
+                _ => None,
+            })
+        })
+        .unwrap_or_else(|| HILRequestType::ComplianceOverride {
+            violation_type: "velocity_limit".to_string(),
+            risk_level: "medium".to_string(),
+        });
+    let reasons: Vec<String> = held.reasons.iter().map(HoldReason::describe).collect();
+
+    HILApprovalRequest {
+        correlation_id: format!("{}{}", HIL_CORRELATION_PREFIX, held.tx_hash),
+        request_type,
+        priority: HILPriority::High { sla_minutes: Nat::from(held.expires_at.saturating_sub(held.created_at) / 60) },
+        submitted_at: nanos(held.created_at),
+        sla_expires_at: nanos(held.expires_at),
+        audit_bundle: HILAuditBundle {
+            features_hash: held.tx_hash.clone(),
+            ai_factors: Vec::new(),
+            confidence: 1.0,
+            recommendation: "hold".to_string(),
+            fallback_reason: None,
+            original_request: serde_json::to_string(&held.action).unwrap_or_default(),
+            decision_path: reasons.clone(),
+            risk_assessment: HILRiskAssessment {
+                risk_score: 0.5,
+                risk_factors: reasons.clone(),
+                mitigation_suggestions: Vec::new(),
+                escalation_triggers: Vec::new(),
+            },
+            compliance_flags: reasons,
+        },
+        status: HILApprovalStatus::Pending,
+        assigned_to: None,
+        escalation_level: Nat::from(0u8),
+        metadata: vec![
+            ("tx_hash".to_string(), held.tx_hash.clone()),
+            ("sender".to_string(), held.sender.clone()),
+            ("drops".to_string(), held.action.xrp_drops().to_string()),
+            ("iou_units".to_string(), held.action.iou_units().to_string()),
+        ],
+    }
+}
+
+fn set_hil_correlation(tx_hash: &str, correlation_id: String) {
+    let mut book = APPROVAL_BOOK.write().unwrap();
+    if let Some(held) = book.held.get_mut(tx_hash) {
+        held.hil_correlation_id = Some(correlation_id);
+        held.updated_at = now_secs();
+    }
+    persist(&book);
+}
+
+/// Opens held actions with the HIL service and applies its decisions.
+/// An approval there releases the action on its own; a denial rejects it.
+async fn sync_hil_decisions(agent: &Agent, config: &BridgeConfig) {
+    for held in list_held_actions(Some(HeldStatus::Held)) {
+        let Some(correlation_id) = held.hil_correlation_id.clone() else {
+            let request = hil_request(&held);
+            match submit_hil_approval_request(agent, config, &request).await {
+                Ok(_) => {
+                    bridge_log_event("approvals", format!("📨 Opened HIL request {}", request.correlation_id));
+                    set_hil_correlation(&held.tx_hash, request.correlation_id);
+                }
+                // Submitted before a restart, but not recorded.
+                Err(e) if e.to_string().contains("Duplicate correlation ID") => {
+                    set_hil_correlation(&held.tx_hash, request.correlation_id);
+                }
+                Err(e) => bridge_log_event("error", format!("❌ HIL submission for {} failed: {}", held.tx_hash, e)),
+            }
+            continue;
+        };
+
+        let request = match fetch_hil_approval_request(agent, config, &correlation_id).await {
+            Ok(Some(request)) => request,
+            Ok(None) => continue,
+            Err(e) => {
+                bridge_log_event("error", format!("❌ HIL lookup for {} failed: {}", held.tx_hash, e));
+                continue;
+            }
+        };
+        let result = match request.status {
+            HILApprovalStatus::Approved { by, .. } => {
+                record_approval(&held.tx_hash, &format!("hil:{}", by), ApprovalSource::Hil, true)
+            }
+            HILApprovalStatus::Denied { by, .. } => {
+                close_held(&held.tx_hash, HeldStatus::Rejected, Some(&format!("hil:{}", by)), "rejected by approver")
+            }
+            // Still open; local expiry applies as usual.
+            _ => continue,
+        };
+        if let Err(e) = result {
+            bridge_log_event("error", format!("❌ Could not apply HIL decision for {}: {}", held.tx_hash, e));
+        }
+    }
+}
+
+pub async fn run_approval_maintenance(agent: Agent, config: BridgeConfig, interval_secs: u64) {
+    loop {
+        if get_hil_approvals_enabled() {
+            sync_hil_decisions(&agent, &config).await;
+        }
+        expire_held_actions();
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candid::Principal;
+
+    fn held(quorum: usize) -> HeldAction {
+        HeldAction {
+            tx_hash: "ABC".into(),
+            sender: "rSender".into(),
+            action: PendingAction::Tip {
+                artist: Principal::anonymous(),
+                amount: Nat::from(5_000_000u64),
+                tx_hash: "ABC".into(),
+                uuid: "u-1".into(),
+            },
+            reasons: vec![HoldReason::AboveThreshold { drops: 5_000_000, threshold_drops: 1_000_000 }],
+            refund: None,
+            refund_status: None,
+            status: HeldStatus::Held,
+            quorum,
+            approvals: Vec::new(),
+            decided_by: None,
+            decided_at: None,
+            hil_correlation_id: None,
+            created_at: 100,
+            expires_at: 200,
+            updated_at: 100,
+        }
+    }
+
+    #[test]
+    fn quorum_of_distinct_approvers_approves() {
+        let mut action = held(2);
+        action.add_approval("alice", ApprovalSource::Admin, false, 110).unwrap();
+        assert_eq!(action.status, HeldStatus::Held);
+        assert!(action.add_approval("alice", ApprovalSource::Admin, false, 111).is_err());
+        action.add_approval("bob", ApprovalSource::Admin, false, 112).unwrap();
+        assert_eq!(action.status, HeldStatus::Approved);
+        assert_eq!(action.decided_at, Some(112));
+    }
+
+    #[test]
+    fn hil_approval_meets_quorum_alone() {
+        let mut action = held(3);
+        action.add_approval("hil:carol", ApprovalSource::Hil, true, 110).unwrap();
+        assert_eq!(action.status, HeldStatus::Approved);
+    }
+
+    #[test]
+    fn expired_actions_cannot_be_approved() {
+        let mut action = held(1);
+        assert!(action.add_approval("alice", ApprovalSource::Admin, false, 200).is_err());
+        action.close(HeldStatus::Expired, None, 200).unwrap();
+        assert!(action.add_approval("alice", ApprovalSource::Admin, false, 150).is_err());
+        assert_eq!(action.status, HeldStatus::Expired);
+    }
+
+    #[test]
+    fn approved_actions_cannot_expire_or_be_rejected() {
+        let mut action = held(1);
+        action.add_approval("alice", ApprovalSource::Admin, false, 150).unwrap();
+        assert!(action.close(HeldStatus::Expired, None, 210).is_err());
+        assert!(action.close(HeldStatus::Rejected, Some("bob"), 160).is_err());
+        assert_eq!(action.status, HeldStatus::Approved);
+    }
+
+    #[test]
+    fn rejection_is_final() {
+        let mut action = held(2);
+        action.close(HeldStatus::Rejected, Some("bob"), 150).unwrap();
+        assert_eq!(action.decided_by.as_deref(), Some("bob"));
+        assert!(action.add_approval("alice", ApprovalSource::Admin, false, 151).is_err());
+        assert!(action.close(HeldStatus::Expired, None, 210).is_err());
+    }
+
+    #[test]
+    fn only_configured_approver_tokens_vote() {
+        let approvers = vec![
+            ApproverConfig { name: "alice".into(), token: "t-alice".into() },
+            ApproverConfig { name: "bob".into(), token: "t-bob".into() },
+        ];
+        assert_eq!(approver_for_token(&approvers, "t-bob").unwrap(), "bob");
+        assert!(approver_for_token(&approvers, "alice").is_err());
+        assert!(approver_for_token(&[], "anything").is_err());
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use candid::Nat;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::{get_bridge_address, get_check_auto_cash, get_check_auto_cash_max_drops};
+use crate::log::bridge_log_event;
+use crate::state::db::{append_jsonl_record, load_json_state, persist_json_state};
+use crate::state::queue::{action_from_verified_tx, enqueue_action, QueueError};
+use crate::xrpl::approvals::admit_action;
+use crate::xrpl::memo::memo_text_from_tx;
+use crate::xrpl::outbound::submit_and_wait;
+use crate::xrpl::refunds::RefundSource;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::{CandidateXRPLTx, VerifiedXRPLTx, XRPLAmount, XRPLError};
+use crate::xrpl::verifier::{parse_memo, parse_tag, verify_candidate_tx};
+use crate::util::{RIPPLE_EPOCH_OFFSET, now_secs};
+
+const CHECK_BOOK_FILE: &str = "check_book.json";
+const CHECK_REPORT_FILE: &str = "check_reports.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckStatus {
+    /// Routing validated; waiting to be cashed.
+    Pending,
+    /// Over the auto-cash policy; waiting for an operator.
+    AwaitingApproval,
+    /// `CheckCash` written and submitted; not yet validated.
+    Cashing,
+    Cashed,
+    /// Memo or destination tag failed validation; never cashed.
+    Rejected,
+    Expired,
+    /// The sender cancelled it.
+    Cancelled,
+    /// Cashing failed on-ledger (e.g. the sender lacks funds).
+    Uncashable,
+}
+
+impl CheckStatus {
+    fn is_final(self) -> bool {
+        matches!(
+            self,
+            CheckStatus::Cashed | CheckStatus::Rejected | CheckStatus::Expired | CheckStatus::Cancelled | CheckStatus::Uncashable
+        )
+    }
+}
+
+/// A Check addressed to the bridge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckRecord {
+    pub check_id: String,
+    pub create_tx: String,
+    pub sender: String,
+    pub send_max: XRPLAmount,
+    pub destination_tag: Option<u32>,
+    pub memo: String,
+    /// Ripple-epoch seconds.
+    pub expiration: Option<u64>,
+    pub status: CheckStatus,
+    pub cash_tx: Option<String>,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckReport {
+    check_id: String,
+    sender: String,
+    status: CheckStatus,
+    reason: Option<String>,
+    reported_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CheckBook {
+    checks: BTreeMap<String, CheckRecord>,
+}
+
+static CHECK_BOOK: Lazy<RwLock<CheckBook>> = Lazy::new(|| {
+    let book = load_json_state::<CheckBook>(CHECK_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load check book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+fn ripple_now() -> u64 {
+    now_secs().saturating_sub(RIPPLE_EPOCH_OFFSET)
+}
+
+fn persist(book: &CheckBook) {
+    if let Err(e) = persist_json_state(CHECK_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist check book: {:?}", e));
+    }
+}
+
+/// Moves a check to `status`. Final states other than `Cashed` are reported.
+fn set_status(check_id: &str, status: CheckStatus, error: Option<String>) -> Option<CheckRecord> {
+    let record = {
+        let mut book = CHECK_BOOK.write().unwrap();
+        let record = book.checks.get_mut(check_id)?;
+        record.status = status;
+        record.error = error.clone();
+        record.updated_at = now_secs();
+        let record = record.clone();
+        persist(&book);
+        record
+    };
+
+    if status.is_final() && status != CheckStatus::Cashed {
+        bridge_log_event(
+            "warn",
+            format!("⚠️ Check {} from {} is {:?}: {}", check_id, record.sender, status, error.as_deref().unwrap_or("-")),
+        );
+        let report = CheckReport {
+            check_id: check_id.to_string(),
+            sender: record.sender.clone(),
+            status,
+            reason: error,
+            reported_at: now_secs(),
+        };
+        if let Err(e) = append_jsonl_record(CHECK_REPORT_FILE, &report) {
+            bridge_log_event("error", format!("Failed to write check report: {:?}", e));
+        }
+    }
+    Some(record)
+}
+
+fn candidate_for(record: &CheckRecord, tx_hash: &str, drops: u64) -> CandidateXRPLTx {
+    CandidateXRPLTx {
+        tx_hash: tx_hash.to_string(),
+        sender: record.sender.clone(),
+        destination: get_bridge_address().unwrap_or_default(),
+        destination_tag: record.destination_tag,
+        amount: Nat::from(drops),
+        memo: record.memo.clone(),
+    }
+}
+
+/// Records a `CheckCreate` to the bridge and validates its routing the way a Payment's is.
+pub fn observe_check_create(tx_hash: &str, tx: &Value, meta: &Value) -> bool {
+    let Some(bridge) = get_bridge_address() else {
+        return false;
+    };
+    if tx["Destination"].as_str() != Some(bridge.as_str()) {
+        return false;
+    }
+
+    let check_id = meta["AffectedNodes"].as_array().and_then(|nodes| {
+        nodes.iter().find_map(|node| {
+            let created = &node["CreatedNode"];
+            (created["LedgerEntryType"] == "Check").then(|| created["LedgerIndex"].as_str())?
+        })
+    });
+    let (Some(check_id), Some(sender), Some(send_max)) =
+        (check_id, tx["Account"].as_str(), XRPLAmount::from_json(&tx["SendMax"]))
+    else {
+        bridge_log_event("warn", format!("Could not read CheckCreate {}", tx_hash));
+        return false;
+    };
+
+    let mut record = CheckRecord {
+        check_id: check_id.to_string(),
+        create_tx: tx_hash.to_string(),
+        sender: sender.to_string(),
+        send_max,
+        destination_tag: tx["DestinationTag"].as_u64().map(|t| t as u32),
+        memo: memo_text_from_tx(tx).unwrap_or_default(),
+        expiration: tx["Expiration"].as_u64(),
+        status: CheckStatus::Pending,
+        cash_tx: None,
+        error: None,
+        created_at: now_secs(),
+        updated_at: now_secs(),
+    };
+
+    let rejection = match record.send_max.drops() {
+        None => Some("only XRP Checks are cashed".to_string()),
+        Some(drops) => verify_candidate_tx(candidate_for(&record, tx_hash, drops))
+            .err()
+            .map(|e| format!("{:?}", e)),
+    };
+    let auto_cash = get_check_auto_cash()
+        && get_check_auto_cash_max_drops()
+            .zip(record.send_max.drops())
+            .is_none_or(|(max, drops)| drops <= max);
+    if rejection.is_none() && !auto_cash {
+        record.status = CheckStatus::AwaitingApproval;
+    }
+
+    {
+        let mut book = CHECK_BOOK.write().unwrap();
+        if book.checks.contains_key(check_id) {
+            return true;
+        }
+        book.checks.insert(check_id.to_string(), record.clone());
+        persist(&book);
+    }
+
+    bridge_log_event("checks", format!("🧾 Check {} from {} ({:?})", check_id, sender, record.status));
+    if let Some(reason) = rejection {
+        set_status(check_id, CheckStatus::Rejected, Some(reason));
+    }
+    true
+}
+
+/// Tracks the sender cancelling one of our checks.
+pub fn observe_check_cancel(tx_hash: &str, tx: &Value) -> bool {
+    let Some(check_id) = tx["CheckID"].as_str() else {
+        return false;
+    };
+    let Some(record) = get_check(check_id) else {
+        return false;
+    };
+    if record.status.is_final() {
+        return true;
+    }
+    set_status(check_id, CheckStatus::Cancelled, Some(format!("cancelled by {}", tx_hash)));
+    true
+}
+
+/// Cashes one check for its full `SendMax` and queues its action once the cash validates.
+pub async fn cash_check(rpc: &XRPLRpcClient, check_id: &str) -> Result<CheckRecord, XRPLError> {
+    let record = get_check(check_id).ok_or_else(|| XRPLError::Other(format!("Unknown check {}", check_id)))?;
+    if record.status.is_final() {
+        return Ok(record);
+    }
+    if record.expiration.is_some_and(|t| ripple_now() >= t) {
+        set_status(check_id, CheckStatus::Expired, Some("expired before cashing".into()));
+        return Err(XRPLError::InvalidTransaction(format!("Check {} has expired", check_id)));
+    }
+    let drops = record
+        .send_max
+        .drops()
+        .ok_or_else(|| XRPLError::InvalidTransaction(format!("Check {} is not XRP", check_id)))?;
+
+    set_status(check_id, CheckStatus::Cashing, None);
+
+    let tx = json!({
+        "TransactionType": "CheckCash",
+        "CheckID": check_id,
+        "Amount": drops.to_string(),
+    });
+
+    let validated = match submit_and_wait(rpc, &format!("check-{}", check_id), tx).await {
+        Ok(validated) => validated,
+        Err(XRPLError::TransactionRejected(reason)) => {
+            set_status(check_id, CheckStatus::Uncashable, Some(reason.clone()));
+            return Err(XRPLError::TransactionRejected(reason));
+        }
+        Err(e) => {
+            // Not known to have failed on-ledger; stays in Cashing and is retried.
+            let mut book = CHECK_BOOK.write().unwrap();
+            if let Some(r) = book.checks.get_mut(check_id) {
+                r.error = Some(e.to_string());
+            }
+            persist(&book);
+            return Err(e);
+        }
+    };
+
+    let cash_hash = validated["hash"].as_str().unwrap_or_default().to_string();
+    {
+        let mut book = CHECK_BOOK.write().unwrap();
+        if let Some(r) = book.checks.get_mut(check_id) {
+            r.cash_tx = Some(cash_hash.clone());
+        }
+        persist(&book);
+    }
+    let record = set_status(check_id, CheckStatus::Cashed, None).unwrap_or(record);
+
+    // Routing was validated at detection; rebuild it against the cash tx, which is the queue key.
+    let (Some(action), Ok(memo)) = (parse_tag(&candidate_for(&record, &cash_hash, drops)), parse_memo(&record.memo)) else {
+        return Ok(record);
+    };
+    let verified = VerifiedXRPLTx {
+        tx_hash: cash_hash.clone(),
+        action,
+        sender: record.sender.clone(),
+        amount: Nat::from(drops),
+        memo,
+        timestamp: now_secs(),
+    };
+    // Cashed XRP sits with the bridge, so a limit breach can be refunded like a payment.
+    let refund = RefundSource {
+        original_tx: cash_hash.clone(),
+        sender: record.sender.clone(),
+        source_tag: None,
+        delivered: XRPLAmount::XRP { drops },
+        is_refund_echo: false,
+    };
+    let queued = action_from_verified_tx(verified)
+        .map(|action| admit_action(&record.sender, action, Some(refund)))
+        .and_then(|admitted| admitted.map_or(Ok(()), enqueue_action));
+    match queued {
+        Ok(()) | Err(QueueError::AlreadyExists) => {}
+        Err(e) => bridge_log_event("error", format!("Cashed check {} but could not queue it: {:?}", check_id, e)),
+    }
+
+    bridge_log_event("checks", format!("✅ Cashed check {} for {} drops ({})", check_id, drops, cash_hash));
+    Ok(record)
+}
+
+/// Operator approval for a check held by the auto-cash policy.
+pub fn approve_check(check_id: &str) -> Result<CheckRecord, XRPLError> {
+    match get_check(check_id) {
+        Some(record) if record.status == CheckStatus::AwaitingApproval => {
+            set_status(check_id, CheckStatus::Pending, None)
+                .ok_or_else(|| XRPLError::Other(format!("Unknown check {}", check_id)))
+        }
+        Some(record) => Err(XRPLError::InvalidTransaction(format!(
+            "Check {} is {:?}, not awaiting approval",
+            check_id, record.status
+        ))),
+        None => Err(XRPLError::Other(format!("Unknown check {}", check_id))),
+    }
+}
+
+pub fn get_check(check_id: &str) -> Option<CheckRecord> {
+    CHECK_BOOK.read().unwrap().checks.get(check_id).cloned()
+}
+
+pub fn list_checks() -> Vec<CheckRecord> {
+    CHECK_BOOK.read().unwrap().checks.values().cloned().collect()
+}
+
+/// Cashes pending checks and reports ones that expired while waiting.
+pub async fn run_check_cashing(rpc: XRPLRpcClient, interval_secs: u64) {
+    loop {
+        for record in list_checks() {
+            match record.status {
+                CheckStatus::Pending | CheckStatus::Cashing => {
+                    if let Err(e) = cash_check(&rpc, &record.check_id).await {
+                        bridge_log_event("warn", format!("Check {} not cashed: {}", record.check_id, e));
+                    }
+                }
+                CheckStatus::AwaitingApproval if record.expiration.is_some_and(|t| ripple_now() >= t) => {
+                    set_status(&record.check_id, CheckStatus::Expired, Some("expired awaiting approval".into()));
+                }
+                _ => {}
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
@@ -1,9 +1,17 @@
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+
 use tokio::time::{sleep, Duration};
 use tokio_tungstenite::connect_async;
 use tokio_tungstenite::tungstenite::Message;
 use url::Url;
 use futures_util::{SinkExt, StreamExt};
 
+use crate::config::{get_bridge_address, get_xrpl_ws_url};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::xrpl::fees;
+use crate::xrpl::ingest::ingest_stream_transaction;
 use crate::xrpl::types::{CandidateXRPLTx, XRPLCommand, XRPLError, XRPLRawTx, XRPLSubmitResult};
 use reqwest::Client;
 use dashmap::DashSet;
@@ -11,18 +19,66 @@ use once_cell::sync::Lazy;
 use candid::Nat;
 
 //Global in-memory cache of subscribed accounts/tags
-pub static SUBSCRIBED_ACCOUNTS: Lazy<DashSet<String>> = Lazy::new(|| DashSet::new());
+pub static SUBSCRIBED_ACCOUNTS: Lazy<DashSet<String>> = Lazy::new(DashSet::new);
+
+const TRACKED_ACCOUNTS_FILE: &str = "tracked_accounts.json";
+
+/// Accounts besides the bridge whose transactions the bridge must see,
+/// e.g. holders of mirrored NFTokens trading them between themselves.
+static TRACKED_ACCOUNTS: Lazy<RwLock<BTreeSet<String>>> = Lazy::new(|| {
+    let accounts = load_json_state::<BTreeSet<String>>(TRACKED_ACCOUNTS_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load tracked accounts: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(accounts)
+});
+
+/// Tracked accounts added since the live connection last subscribed.
+static NEWLY_TRACKED: Lazy<DashSet<String>> = Lazy::new(DashSet::new);
+
+/// Adds `account` to the `accounts` subscription, now and on every reconnect.
+pub fn track_account(account: &str) {
+    if get_bridge_address().as_deref() == Some(account) {
+        return;
+    }
+    let mut accounts = TRACKED_ACCOUNTS.write().unwrap();
+    if !accounts.insert(account.to_string()) {
+        return;
+    }
+    if let Err(e) = persist_json_state(TRACKED_ACCOUNTS_FILE, &*accounts) {
+        bridge_log_event("error", format!("Failed to persist tracked accounts: {:?}", e));
+    }
+    NEWLY_TRACKED.insert(account.to_string());
+    bridge_log_event("ingest", format!("👀 Tracking {}", account));
+}
+
+/// The bridge and every tracked account.
+fn watched_accounts() -> Vec<String> {
+    let tracked = TRACKED_ACCOUNTS.read().unwrap();
+    get_bridge_address().into_iter().chain(tracked.iter().cloned()).collect()
+}
+
+/// Takes the accounts tracked since the last subscribe.
+fn drain_newly_tracked() -> Vec<String> {
+    let accounts: Vec<String> = NEWLY_TRACKED.iter().map(|a| a.key().clone()).collect();
+    for account in &accounts {
+        NEWLY_TRACKED.remove(account);
+    }
+    accounts
+}
 
 
 /// Bootstraps the XRPL WebSocket client and starts the main event loop.
 /// Will automatically reconnect with exponential backoff if disconnected.
 pub async fn connect_to_xrpl() -> Result<(), XRPLError> {
-    let endpoint = "wss://s.altnet.rippletest.net:51233"; // Testnet endpoint
+    let endpoint = get_xrpl_ws_url();
     let mut retry_count = 0;
     let max_retries = 5;
 
     loop {
-        match Url::parse(endpoint) {
+        match Url::parse(&endpoint) {
             Ok(url) => {
                 match connect_async(url).await {
                     Ok((ws_stream, _)) => {
@@ -34,12 +90,34 @@ pub async fn connect_to_xrpl() -> Result<(), XRPLError> {
                         let ping = serde_json::to_string(&XRPLCommand::Ping)?;
                         write.send(Message::Text(ping)).await?;
 
+                        // Ledger/server streams feed the fee oracle; the accounts subscription
+                        // carries validated transactions touching the bridge or a tracked account
+                        drain_newly_tracked();
+                        let streams = serde_json::to_string(&XRPLCommand::Subscribe {
+                            streams: Some(vec!["ledger".to_string(), "server".to_string()]),
+                            accounts: Some(watched_accounts()),
+                        })?;
+                        write.send(Message::Text(streams)).await?;
+
                         // Event loop
                         while let Some(msg) = read.next().await {
                             match msg {
                                 Ok(Message::Text(txt)) => {
-                                    println!("📥 XRPL Msg: {}", txt);
-                                    // Here you would call `handle_xrpl_event(&txt)` eventually
+                                    if let Err(e) = handle_xrpl_event(&txt) {
+                                        eprintln!("⚠️ Failed to handle XRPL msg: {}", e);
+                                    }
+                                    let added = drain_newly_tracked();
+                                    if !added.is_empty() {
+                                        let subscribe = serde_json::to_string(&XRPLCommand::Subscribe {
+                                            streams: None,
+                                            accounts: Some(added),
+                                        })?;
+                                        // A failed send drops the connection; the reconnect subscribes everything.
+                                        if let Err(e) = write.send(Message::Text(subscribe)).await {
+                                            eprintln!("⚠️ WebSocket error: {}", e);
+                                            break;
+                                        }
+                                    }
                                 },
                                 Ok(_) => continue,
                                 Err(e) => {
@@ -76,8 +154,7 @@ pub async fn connect_to_xrpl() -> Result<(), XRPLError> {
 /// Subscribes to a given XRP address (and optional destination tag) over WebSocket.
 /// Subscribes to a given XRP address (and optional destination tag) over WebSocket.
 pub async fn subscribe_to_address(address: &str, tag: Option<u32>) -> Result<(), XRPLError> {
-    let endpoint = "wss://s.altnet.rippletest.net:51233";
-    let url = Url::parse(endpoint).map_err(|e| XRPLError::InvalidEndpoint(e.to_string()))?;
+    let url = Url::parse(&get_xrpl_ws_url()).map_err(|e| XRPLError::InvalidEndpoint(e.to_string()))?;
 
     let cache_key = format!("{}:{:?}", address, tag);
     if SUBSCRIBED_ACCOUNTS.contains(&cache_key) {
@@ -179,15 +256,14 @@ pub fn process_incoming_tx(tx: &XRPLRawTx) -> Option<CandidateXRPLTx> {
         None => return None,
     };
 
-let candidate = Some(CandidateXRPLTx {
+Some(CandidateXRPLTx {
     tx_hash: tx.hash.clone(),
     sender: tx.account.clone(),
     destination: tx.destination.clone().unwrap_or_default(),
     destination_tag: tx.destination_tag, // assuming it's already Option<u32>
     amount: Nat::from(amount_drops), // use u64 directly
     memo: memo.clone(),
-});
-candidate
+})
 }
 
 /// Returns true if the transaction is a relevant Payment type.
@@ -228,8 +304,29 @@ pub fn handle_xrpl_event(raw: &str) -> Result<(), XRPLError> {
     let json: serde_json::Value = serde_json::from_str(raw)
         .map_err(|e| XRPLError::Other(format!("Invalid JSON: {}", e)))?;
 
+    match json["type"].as_str() {
+        Some("ledgerClosed") => {
+            fees::observe_ledger_closed(&json);
+            return Ok(());
+        }
+        Some("serverStatus") => {
+            fees::observe_server_status(&json);
+            return Ok(());
+        }
+        // The subscribe response carries the initial ledger and server load
+        Some("response") => {
+            fees::observe_ledger_closed(&json["result"]);
+            fees::observe_server_status(&json["result"]);
+            return Ok(());
+        }
+        _ => {}
+    }
+
     // Check if it's a transaction message
     if json["type"] == "transaction" {
+        if ingest_stream_transaction(&json) {
+            return Ok(());
+        }
         if let Some(tx_obj) = json.get("transaction") {
             let parsed: XRPLRawTx = serde_json::from_value(tx_obj.clone())
                 .map_err(|e| XRPLError::Other(format!("Failed to decode XRPLRawTx: {}", e)))?;
@@ -0,0 +1,403 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use candid::CandidType;
+use ic_agent::Agent;
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::config::{get_bridge_address, BridgeConfig};
+use crate::ic_trigger::{fetch_escrow_deal_status, settle_xrpl_escrow};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::PendingAction;
+use crate::xrpl::fees::get_fee_snapshot;
+use crate::xrpl::outbound::submit_and_wait;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::{XRPLAmount, XRPLError};
+use crate::util::{RIPPLE_EPOCH_OFFSET, from_hex, now_secs, to_hex};
+
+const ESCROW_BOOK_FILE: &str = "escrow_book.json";
+
+/// An XRPL escrow to the bridge, as registered with the escrow canister.
+#[derive(Debug, Clone, Serialize, Deserialize, CandidType)]
+pub struct XRPLEscrow {
+    pub deal_id: String,
+    pub owner: String,
+    pub offer_sequence: u32,
+    pub amount: XRPLAmount,
+    /// PREIMAGE-SHA-256 condition, hex.
+    pub condition: String,
+    /// Ripple-epoch seconds.
+    pub finish_after: Option<u32>,
+    pub cancel_after: Option<u32>,
+    pub create_tx: String,
+}
+
+impl XRPLEscrow {
+    /// Escrows are addressed on-ledger by owner + the sequence of their `EscrowCreate`.
+    pub fn key(&self) -> String {
+        escrow_key(&self.owner, self.offer_sequence)
+    }
+}
+
+fn escrow_key(owner: &str, offer_sequence: u32) -> String {
+    format!("{}:{}", owner, offer_sequence)
+}
+
+/// Where the deal stands on the ICP side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, CandidType)]
+pub enum EscrowDealStatus {
+    Pending,
+    Delivered,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscrowStatus {
+    /// Seen on XRPL; not yet registered with the escrow canister.
+    Observed,
+    Registered,
+    Finished,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowRecord {
+    pub escrow: XRPLEscrow,
+    pub status: EscrowStatus,
+    pub close_tx: Option<String>,
+    /// The escrow canister has been told how the escrow closed.
+    #[serde(default)]
+    pub settled_on_icp: bool,
+    pub error: Option<String>,
+    pub updated_at: u64,
+}
+
+/// Condition the bridge handed out for a deal. The preimage is the secret that releases it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DealCondition {
+    deal_id: String,
+    condition: String,
+    preimage: String,
+    created_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct EscrowBook {
+    conditions: BTreeMap<String, DealCondition>,
+    escrows: BTreeMap<String, EscrowRecord>,
+}
+
+static ESCROW_BOOK: Lazy<RwLock<EscrowBook>> = Lazy::new(|| {
+    let book = load_json_state::<EscrowBook>(ESCROW_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load escrow book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+fn ripple_now() -> u64 {
+    now_secs().saturating_sub(RIPPLE_EPOCH_OFFSET)
+}
+
+fn persist(book: &EscrowBook) {
+    if let Err(e) = persist_json_state(ESCROW_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist escrow book: {:?}", e));
+    }
+}
+
+/// DER-encoded PREIMAGE-SHA-256 condition: fingerprint plus cost (the preimage length).
+pub fn preimage_condition(preimage: &[u8]) -> String {
+    let fingerprint = Sha256::digest(preimage);
+    let mut der = vec![0xA0, 0x25, 0x80, 0x20];
+    der.extend_from_slice(&fingerprint);
+    der.extend_from_slice(&[0x81, 0x01, preimage.len() as u8]);
+    to_hex(&der)
+}
+
+/// DER-encoded PREIMAGE-SHA-256 fulfillment for a preimage of at most 127 bytes.
+pub fn preimage_fulfillment(preimage: &[u8]) -> String {
+    let mut der = vec![0xA0, preimage.len() as u8 + 2, 0x80, preimage.len() as u8];
+    der.extend_from_slice(preimage);
+    to_hex(&der)
+}
+
+/// Condition the payer puts in `EscrowCreate` for `deal_id`. Stable per deal.
+pub fn create_deal_condition(deal_id: &str) -> String {
+    let mut book = ESCROW_BOOK.write().unwrap();
+    if let Some(existing) = book.conditions.get(deal_id) {
+        return existing.condition.clone();
+    }
+
+    let mut preimage = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut preimage);
+    let condition = preimage_condition(&preimage);
+
+    book.conditions.insert(
+        deal_id.to_string(),
+        DealCondition {
+            deal_id: deal_id.to_string(),
+            condition: condition.clone(),
+            preimage: to_hex(&preimage),
+            created_at: now_secs(),
+        },
+    );
+    persist(&book);
+
+    bridge_log_event("escrow", format!("🔐 Issued escrow condition for deal {}", deal_id));
+    condition
+}
+
+/// Reads an `EscrowCreate` to the bridge whose condition the bridge issued.
+pub fn ingest_escrow_create(tx_hash: &str, tx: &Value) -> Option<PendingAction> {
+    let bridge = get_bridge_address()?;
+    if tx["Destination"].as_str() != Some(bridge.as_str()) {
+        return None;
+    }
+
+    let owner = tx["Account"].as_str()?;
+    let condition = tx["Condition"].as_str().map(str::to_uppercase);
+    let deal_id = {
+        let book = ESCROW_BOOK.read().unwrap();
+        condition
+            .as_ref()
+            .and_then(|c| book.conditions.values().find(|d| d.condition == *c))
+            .map(|d| d.deal_id.clone())
+    };
+    let (Some(deal_id), Some(condition)) = (deal_id, condition) else {
+        bridge_log_event("warn", format!("Escrow {} to the bridge has no condition we issued", tx_hash));
+        return None;
+    };
+
+    // Ticketed transactions carry Sequence 0; the escrow is then keyed by the ticket.
+    let offer_sequence = match tx["Sequence"].as_u64() {
+        Some(0) | None => tx["TicketSequence"].as_u64()?,
+        Some(seq) => seq,
+    } as u32;
+
+    let escrow = XRPLEscrow {
+        deal_id,
+        owner: owner.to_string(),
+        offer_sequence,
+        amount: XRPLAmount::from_json(&tx["Amount"])?,
+        condition,
+        finish_after: tx["FinishAfter"].as_u64().map(|t| t as u32),
+        cancel_after: tx["CancelAfter"].as_u64().map(|t| t as u32),
+        create_tx: tx_hash.to_string(),
+    };
+
+    {
+        let mut book = ESCROW_BOOK.write().unwrap();
+        book.escrows.entry(escrow.key()).or_insert_with(|| EscrowRecord {
+            escrow: escrow.clone(),
+            status: EscrowStatus::Observed,
+            close_tx: None,
+            settled_on_icp: false,
+            error: None,
+            updated_at: now_secs(),
+        });
+        persist(&book);
+    }
+
+    bridge_log_event(
+        "escrow",
+        format!("📦 Escrow {} from {} for deal {}", escrow.key(), owner, escrow.deal_id),
+    );
+
+    Some(PendingAction::EscrowRegistration {
+        escrow: Box::new(escrow),
+        tx_hash: tx_hash.to_string(),
+    })
+}
+
+/// Tracks `EscrowFinish`/`EscrowCancel` on our escrows, whoever submitted them.
+pub fn observe_escrow_close(tx_hash: &str, tx: &Value) -> bool {
+    let (Some(owner), Some(seq)) = (tx["Owner"].as_str(), tx["OfferSequence"].as_u64()) else {
+        return false;
+    };
+    let status = match tx["TransactionType"].as_str() {
+        Some("EscrowFinish") => EscrowStatus::Finished,
+        Some("EscrowCancel") => EscrowStatus::Cancelled,
+        _ => return false,
+    };
+
+    let mut book = ESCROW_BOOK.write().unwrap();
+    let Some(record) = book.escrows.get_mut(&escrow_key(owner, seq as u32)) else {
+        return false;
+    };
+    record.status = status;
+    record.close_tx = Some(tx_hash.to_string());
+    record.updated_at = now_secs();
+    persist(&book);
+
+    bridge_log_event("escrow", format!("📦 Escrow {}:{} closed as {:?} ({})", owner, seq, status, tx_hash));
+    true
+}
+
+/// Called once the escrow canister has accepted the registration.
+pub fn mark_escrow_registered(key: &str) {
+    let mut book = ESCROW_BOOK.write().unwrap();
+    if let Some(record) = book.escrows.get_mut(key) {
+        if record.status == EscrowStatus::Observed {
+            record.status = EscrowStatus::Registered;
+            record.updated_at = now_secs();
+            persist(&book);
+        }
+    }
+}
+
+/// Whether the bridge tracks the escrow `owner` created with `offer_sequence`.
+pub fn is_tracked_escrow(owner: &str, offer_sequence: u32) -> bool {
+    ESCROW_BOOK.read().unwrap().escrows.contains_key(&escrow_key(owner, offer_sequence))
+}
+
+pub fn get_escrow(key: &str) -> Option<EscrowRecord> {
+    ESCROW_BOOK.read().unwrap().escrows.get(key).cloned()
+}
+
+pub fn list_escrows() -> Vec<EscrowRecord> {
+    ESCROW_BOOK.read().unwrap().escrows.values().cloned().collect()
+}
+
+fn record_close(key: &str, result: &Result<Value, XRPLError>, status: EscrowStatus) {
+    let mut book = ESCROW_BOOK.write().unwrap();
+    let Some(record) = book.escrows.get_mut(key) else {
+        return;
+    };
+    match result {
+        Ok(validated) => {
+            record.status = status;
+            record.close_tx = validated["hash"].as_str().map(str::to_string);
+            record.error = None;
+        }
+        Err(e) => record.error = Some(e.to_string()),
+    }
+    record.updated_at = now_secs();
+    persist(&book);
+}
+
+/// Releases the escrow to the bridge with the deal's fulfillment.
+pub async fn finish_escrow(rpc: &XRPLRpcClient, key: &str) -> Result<Value, XRPLError> {
+    let record = get_escrow(key).ok_or_else(|| XRPLError::Other(format!("Unknown escrow {}", key)))?;
+    let escrow = &record.escrow;
+    if let Some(finish_after) = escrow.finish_after {
+        if ripple_now() <= finish_after as u64 {
+            return Err(XRPLError::InvalidTransaction(format!("Escrow {} is locked until {}", key, finish_after)));
+        }
+    }
+
+    let preimage = {
+        let book = ESCROW_BOOK.read().unwrap();
+        book.conditions
+            .get(&escrow.deal_id)
+            .and_then(|d| from_hex(&d.preimage))
+            .ok_or_else(|| XRPLError::Other(format!("No preimage held for deal {}", escrow.deal_id)))?
+    };
+    let fulfillment = preimage_fulfillment(&preimage);
+
+    // Conditional finishes cost 330 base units plus 10 per 16 bytes of fulfillment.
+    let fulfillment_len = fulfillment.len() as u64 / 2;
+    let fee_floor = get_fee_snapshot().base_fee * (33 + fulfillment_len.div_ceil(16));
+
+    let tx = json!({
+        "TransactionType": "EscrowFinish",
+        "Owner": escrow.owner,
+        "OfferSequence": escrow.offer_sequence,
+        "Condition": escrow.condition,
+        "Fulfillment": fulfillment,
+        "Fee": fee_floor.to_string(),
+    });
+
+    let result = submit_and_wait(rpc, &format!("escrow-finish-{}", key), tx).await;
+    record_close(key, &result, EscrowStatus::Finished);
+    result
+}
+
+/// Returns the escrow to its owner. XRPL only allows this after `CancelAfter`.
+pub async fn cancel_escrow(rpc: &XRPLRpcClient, key: &str) -> Result<Value, XRPLError> {
+    let record = get_escrow(key).ok_or_else(|| XRPLError::Other(format!("Unknown escrow {}", key)))?;
+    let escrow = &record.escrow;
+    match escrow.cancel_after {
+        Some(cancel_after) if ripple_now() > cancel_after as u64 => {}
+        _ => {
+            return Err(XRPLError::InvalidTransaction(format!("Escrow {} has not expired", key)));
+        }
+    }
+
+    let tx = json!({
+        "TransactionType": "EscrowCancel",
+        "Owner": escrow.owner,
+        "OfferSequence": escrow.offer_sequence,
+    });
+
+    let result = submit_and_wait(rpc, &format!("escrow-cancel-{}", key), tx).await;
+    record_close(key, &result, EscrowStatus::Cancelled);
+    result
+}
+
+/// One pass: finish delivered deals, cancel cancelled or expired ones, and
+/// tell the escrow canister how each escrow closed.
+pub async fn settle_escrows(agent: &Agent, config: &BridgeConfig, rpc: &XRPLRpcClient) {
+    for record in list_escrows() {
+        let key = record.escrow.key();
+        let deal_id = &record.escrow.deal_id;
+
+        if record.status == EscrowStatus::Registered {
+            let status = match fetch_escrow_deal_status(agent, config, deal_id).await {
+                Ok(status) => status,
+                Err(e) => {
+                    bridge_log_event("warn", format!("Could not read deal {}: {}", deal_id, e));
+                    continue;
+                }
+            };
+            let expired = record.escrow.cancel_after.is_some_and(|t| ripple_now() > t as u64);
+            let unlocked = record.escrow.finish_after.is_none_or(|t| ripple_now() > t as u64);
+
+            let result = match status {
+                // Past CancelAfter a finish is rejected on-ledger, so only cancel remains.
+                EscrowDealStatus::Delivered if unlocked && !expired => Some(finish_escrow(rpc, &key).await),
+                EscrowDealStatus::Cancelled | EscrowDealStatus::Pending if expired => Some(cancel_escrow(rpc, &key).await),
+                _ => None,
+            };
+            if let Some(Err(e)) = result {
+                bridge_log_event("error", format!("❌ Escrow {} for deal {}: {}", key, deal_id, e));
+            }
+        }
+
+        let Some(record) = get_escrow(&key) else { continue };
+        let finished = match record.status {
+            EscrowStatus::Finished => true,
+            EscrowStatus::Cancelled => false,
+            _ => continue,
+        };
+        if record.settled_on_icp {
+            continue;
+        }
+        let tx_hash = record.close_tx.clone().unwrap_or_default();
+        match settle_xrpl_escrow(agent, config, deal_id, &tx_hash, finished).await {
+            Ok(()) => {
+                let mut book = ESCROW_BOOK.write().unwrap();
+                if let Some(r) = book.escrows.get_mut(&key) {
+                    r.settled_on_icp = true;
+                    r.updated_at = now_secs();
+                }
+                persist(&book);
+                bridge_log_event("escrow", format!("✅ Deal {} settled ({})", deal_id, tx_hash));
+            }
+            Err(e) => bridge_log_event("warn", format!("Settling deal {} on ICP failed: {}", deal_id, e)),
+        }
+    }
+}
+
+pub async fn run_escrow_maintenance(agent: Agent, config: BridgeConfig, rpc: XRPLRpcClient, interval_secs: u64) {
+    loop {
+        settle_escrows(&agent, &config, &rpc).await;
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::xrpl::params::max_fee_drops;
+use crate::log::bridge_log_event;
+use crate::state::db::{append_jsonl_record, load_jsonl_records, DBError};
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::XRPLError;
+use crate::util::now_secs;
+
+const FEE_LOG_FILE: &str = "fees.jsonl";
+
+/// XRPL reference transaction cost in drops.
+const REFERENCE_FEE_DROPS: u64 = 10;
+
+/// Extra headroom over the open-ledger fee so we aren't priced out by the next tx.
+const FEE_MARGIN_PERCENT: u64 = 20;
+
+/// Fee bump per escalation. rippled only replaces a queued tx for at least 25% more;
+/// we bump by 50% so one step also clears a rising open-ledger fee.
+const ESCALATION_PERCENT: u64 = 50;
+
+/// Latest view of network fees, merged from the `fee` command and the ledger/server streams.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeSnapshot {
+    pub base_fee: u64,
+    pub open_ledger_fee: Option<u64>,
+    pub median_fee: Option<u64>,
+    pub minimum_fee: Option<u64>,
+    pub load_factor: u64,
+    pub load_base: u64,
+    pub queue_size: Option<u64>,
+    pub ledger_index: Option<u64>,
+    pub updated_at: u64,
+}
+
+impl Default for FeeSnapshot {
+    fn default() -> Self {
+        Self {
+            base_fee: REFERENCE_FEE_DROPS,
+            open_ledger_fee: None,
+            median_fee: None,
+            minimum_fee: None,
+            load_factor: 256,
+            load_base: 256,
+            queue_size: None,
+            ledger_index: None,
+            updated_at: 0,
+        }
+    }
+}
+
+/// One fee payment, appended to `fees.jsonl` for finance reconciliation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeRecord {
+    pub job_id: String,
+    pub tx_hash: String,
+    pub fee_drops: u64,
+    pub attempt: u32,
+    pub result: String,
+    pub ledger_index: Option<u64>,
+    pub recorded_at: u64,
+}
+
+static FEE_SNAPSHOT: Lazy<RwLock<FeeSnapshot>> = Lazy::new(|| RwLock::new(FeeSnapshot::default()));
+
+/// rippled sends drops as strings in `fee`, numbers in the streams.
+fn as_drops(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Returns the current fee snapshot.
+pub fn get_fee_snapshot() -> FeeSnapshot {
+    FEE_SNAPSHOT.read().unwrap().clone()
+}
+
+/// Ingests the `result` of a `fee` command.
+pub fn observe_fee_result(result: &Value) {
+    let mut snapshot = FEE_SNAPSHOT.write().unwrap();
+    let drops = &result["drops"];
+
+    if let Some(base) = as_drops(&drops["base_fee"]) {
+        snapshot.base_fee = base;
+    }
+    snapshot.open_ledger_fee = as_drops(&drops["open_ledger_fee"]).or(snapshot.open_ledger_fee);
+    snapshot.median_fee = as_drops(&drops["median_fee"]).or(snapshot.median_fee);
+    snapshot.minimum_fee = as_drops(&drops["minimum_fee"]).or(snapshot.minimum_fee);
+    snapshot.queue_size = as_drops(&result["current_queue_size"]).or(snapshot.queue_size);
+    snapshot.ledger_index = as_drops(&result["ledger_current_index"]).or(snapshot.ledger_index);
+    snapshot.updated_at = now_secs();
+}
+
+/// Ingests a `ledgerClosed` stream message (or the `ledger` part of a subscribe response).
+pub fn observe_ledger_closed(msg: &Value) {
+    let mut snapshot = FEE_SNAPSHOT.write().unwrap();
+
+    if let Some(base) = as_drops(&msg["fee_base"]) {
+        snapshot.base_fee = base;
+    }
+    snapshot.ledger_index = as_drops(&msg["ledger_index"]).or(snapshot.ledger_index);
+    snapshot.updated_at = now_secs();
+}
+
+/// Ingests a `serverStatus` stream message (load-based fee scaling).
+pub fn observe_server_status(msg: &Value) {
+    let mut snapshot = FEE_SNAPSHOT.write().unwrap();
+
+    if let Some(load_base) = as_drops(&msg["load_base"]) {
+        snapshot.load_base = load_base.max(1);
+    }
+    // Prefer the escalation factor: it's what the open ledger actually charges.
+    let load_factor = as_drops(&msg["load_factor_fee_escalation"]).or_else(|| as_drops(&msg["load_factor"]));
+    if let Some(load_factor) = load_factor {
+        snapshot.load_factor = load_factor;
+    }
+    if let Some(base) = as_drops(&msg["base_fee"]) {
+        snapshot.base_fee = base;
+    }
+    snapshot.updated_at = now_secs();
+}
+
+/// Fee needed to get into the open ledger now, before any escalation.
+pub fn open_ledger_fee(snapshot: &FeeSnapshot) -> u64 {
+    let load_scaled = snapshot.base_fee.saturating_mul(snapshot.load_factor) / snapshot.load_base.max(1);
+    let floor = snapshot.minimum_fee.unwrap_or(snapshot.base_fee);
+
+    snapshot
+        .open_ledger_fee
+        .unwrap_or(load_scaled)
+        .max(load_scaled)
+        .max(floor)
+}
+
+/// Recommended `Fee` for a single-signed tx at the given escalation attempt (0 = first try),
+/// capped at `XRPL_MAX_FEE_DROPS`.
+pub fn recommended_fee(attempt: u32) -> u64 {
+    let snapshot = get_fee_snapshot();
+    let mut fee = open_ledger_fee(&snapshot) * (100 + FEE_MARGIN_PERCENT) / 100;
+
+    for _ in 0..attempt {
+        fee = fee * (100 + ESCALATION_PERCENT) / 100 + 1;
+    }
+
+    fee.max(snapshot.base_fee).min(max_fee_drops())
+}
+
+/// Refreshes the snapshot with a `fee` RPC call.
+pub async fn refresh_fees(rpc: &XRPLRpcClient) -> Result<FeeSnapshot, XRPLError> {
+    let result = rpc.request("fee", json!({})).await?;
+    observe_fee_result(&result);
+    Ok(get_fee_snapshot())
+}
+
+/// Appends what a transaction actually paid to the fee log.
+pub fn record_fee_paid(job_id: &str, tx: &Value, attempt: u32) {
+    let record = FeeRecord {
+        job_id: job_id.to_string(),
+        tx_hash: tx["hash"].as_str().unwrap_or_default().to_string(),
+        fee_drops: as_drops(&tx["Fee"]).unwrap_or(0),
+        attempt,
+        result: tx["meta"]["TransactionResult"].as_str().unwrap_or("unknown").to_string(),
+        ledger_index: tx["ledger_index"].as_u64(),
+        recorded_at: now_secs(),
+    };
+
+    if let Err(e) = append_jsonl_record(FEE_LOG_FILE, &record) {
+        bridge_log_event("error", format!("Failed to record fee for {}: {:?}", record.tx_hash, e));
+    }
+}
+
+/// 📥 Reads all recorded fee payments.
+pub fn load_fee_records() -> Result<Vec<FeeRecord>, DBError> {
+    load_jsonl_records(FEE_LOG_FILE)
+}
+
+/// Background loop: poll the `fee` command so escalation data stays fresh between stream events.
+pub async fn run_fee_refresh(rpc: XRPLRpcClient, interval_secs: u64) {
+    loop {
+        if let Err(e) = refresh_fees(&rpc).await {
+            bridge_log_event("warn", format!("Fee refresh failed: {}", e));
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
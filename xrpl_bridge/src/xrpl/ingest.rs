@@ -0,0 +1,175 @@
+use candid::Nat;
+use serde_json::Value;
+
+use crate::config::get_bridge_address;
+use crate::log::bridge_log_event;
+use crate::state::queue::{action_from_verified_tx, enqueue_action, QueueError};
+use crate::state::mirror_registry::get_mirror_by_nftoken;
+use crate::xrpl::checks::{get_check, observe_check_cancel, observe_check_create};
+use crate::xrpl::escrow::{ingest_escrow_create, is_tracked_escrow, observe_escrow_close};
+use crate::xrpl::intents::match_intent_payment;
+use crate::xrpl::issuer::{ingest_redemption, is_parked_redemption, observe_trust_set};
+use crate::xrpl::memo::memo_text_from_tx;
+use crate::xrpl::approvals::admit_action;
+use crate::xrpl::paychan::get_channel;
+use crate::xrpl::refunds::{queue_refund, RefundSource};
+use crate::xrpl::screening::{screen_transaction, ScreeningOutcome};
+use crate::xrpl::token_mirroring::{ingest_nft_accept_offer, parse_nft_accept_offer};
+use crate::xrpl::types::{CandidateXRPLTx, VerifierError, XRPLAmount};
+use crate::xrpl::verifier::verify_candidate_tx;
+use crate::wrapped_xrp::{ingest_wrap_deposit, is_parked_deposit};
+
+/// Verifies a memo/destination-tag Payment to the bridge and queues it,
+/// or hands it to the refund policy when it can't be routed.
+fn route_bridge_payment(hash: &str, tx: &Value, meta: &Value) {
+    let delivered = if meta["delivered_amount"].is_null() { &tx["Amount"] } else { &meta["delivered_amount"] };
+    let drops = XRPLAmount::from_json(delivered).and_then(|a| a.drops()).unwrap_or(0);
+
+    let candidate = CandidateXRPLTx {
+        tx_hash: hash.to_string(),
+        sender: tx["Account"].as_str().unwrap_or_default().to_string(),
+        destination: tx["Destination"].as_str().unwrap_or_default().to_string(),
+        destination_tag: tx["DestinationTag"].as_u64().map(|t| t as u32),
+        amount: Nat::from(drops),
+        memo: memo_text_from_tx(tx).unwrap_or_default(),
+    };
+
+    match verify_candidate_tx(candidate) {
+        Ok(verified) => {
+            let sender = verified.sender.clone();
+            let action = match action_from_verified_tx(verified) {
+                Ok(action) => action,
+                Err(e) => return bridge_log_event("error", format!("Failed to queue {}: {:?}", hash, e)),
+            };
+            let Some(action) = admit_action(&sender, action, RefundSource::from_tx(hash, tx, meta)) else {
+                return;
+            };
+            match enqueue_action(action) {
+                Ok(()) | Err(QueueError::AlreadyExists) => {}
+                Err(e) => bridge_log_event("error", format!("Failed to queue {}: {:?}", hash, e)),
+            }
+        }
+        // Re-delivered stream message for a payment we already routed.
+        Err(VerifierError::ReplayDetected(_)) => {}
+        Err(e) => {
+            queue_refund(hash, tx, meta, &e);
+        }
+    }
+}
+
+/// The transaction, hash and metadata of a stream message.
+fn stream_parts(msg: &Value) -> (&Value, &str, &Value) {
+    // API v2 streams send `tx_json` + `hash`; v1 sends `transaction` with the hash inside.
+    let tx = if msg["tx_json"].is_object() { &msg["tx_json"] } else { &msg["transaction"] };
+    let hash = msg["hash"].as_str().or_else(|| tx["hash"].as_str()).unwrap_or_default();
+    (tx, hash, &msg["meta"])
+}
+
+/// Whether the bridge acts on a transaction: sent to the bridge (or its IOU), trading a
+/// mirrored NFToken, or closing an escrow, check or channel the bridge tracks.
+fn concerns_bridge(tx: &Value, meta: &Value) -> bool {
+    let Some(bridge) = get_bridge_address() else {
+        return false;
+    };
+    if tx["Destination"].as_str() == Some(bridge.as_str()) {
+        return true;
+    }
+    match tx["TransactionType"].as_str() {
+        Some("NFTokenAcceptOffer") => {
+            parse_nft_accept_offer(tx, meta).is_some_and(|sale| get_mirror_by_nftoken(&sale.nftoken_id).is_some())
+        }
+        Some("EscrowFinish" | "EscrowCancel") => match (tx["Owner"].as_str(), tx["OfferSequence"].as_u64()) {
+            (Some(owner), Some(seq)) => is_tracked_escrow(owner, seq as u32),
+            _ => false,
+        },
+        Some("CheckCash" | "CheckCancel") => tx["CheckID"].as_str().is_some_and(|id| get_check(id).is_some()),
+        Some("PaymentChannelClaim" | "PaymentChannelFund") => {
+            tx["Channel"].as_str().is_some_and(|id| get_channel(id).is_some())
+        }
+        Some("TrustSet") => tx["LimitAmount"]["issuer"].as_str() == Some(bridge.as_str()),
+        _ => false,
+    }
+}
+
+/// Screens one `transaction` stream message that concerns the bridge and, once its
+/// counterparties are clear, routes it to the ingester for its type.
+pub fn ingest_stream_transaction(msg: &Value) -> bool {
+    let (tx, hash, meta) = stream_parts(msg);
+    if msg["validated"] != true || meta["TransactionResult"] != "tesSUCCESS" {
+        return true;
+    }
+    // Third-party traffic is never screened, quarantined or sent to the compliance canister.
+    if !concerns_bridge(tx, meta) {
+        return true;
+    }
+
+    match screen_transaction(hash, tx, meta, msg) {
+        ScreeningOutcome::Clear => route_stream_transaction(msg),
+        // Held for review, or routed later by the canister check.
+        ScreeningOutcome::Quarantined | ScreeningOutcome::Deferred => {}
+    }
+    true
+}
+
+/// Routes a validated, screened stream message to the ingester for its type.
+pub fn route_stream_transaction(msg: &Value) {
+    let (tx, hash, meta) = stream_parts(msg);
+
+    // Set for plain payments to the bridge, whose XRP can be returned if held and refused.
+    let mut refundable = false;
+    let action = match tx["TransactionType"].as_str() {
+        Some("NFTokenAcceptOffer") => ingest_nft_accept_offer(hash, tx, meta),
+        Some("EscrowCreate") => ingest_escrow_create(hash, tx),
+        Some("EscrowFinish" | "EscrowCancel") => {
+            observe_escrow_close(hash, tx);
+            None
+        }
+        Some("CheckCreate") => {
+            observe_check_create(hash, tx, meta);
+            None
+        }
+        Some("CheckCancel") => {
+            observe_check_cancel(hash, tx);
+            None
+        }
+        Some("TrustSet") => {
+            observe_trust_set(hash, tx);
+            None
+        }
+        Some("Payment") => {
+            let to_bridge = get_bridge_address()
+                .map(|bridge| tx["Destination"].as_str() == Some(bridge.as_str()))
+                .unwrap_or(false);
+            if !to_bridge {
+                return;
+            }
+            match match_intent_payment(hash, tx, meta) {
+                // The intent has already tallied this payment; a hold is never refunded.
+                Some(action) => action,
+                None => {
+                    refundable = true;
+                    ingest_redemption(hash, tx, meta)
+                        .or_else(|| ingest_wrap_deposit(hash, tx, meta))
+                        .or_else(|| {
+                            // A parked payment waits for its sender to link; it isn't refunded.
+                            if !is_parked_deposit(hash) && !is_parked_redemption(hash) {
+                                route_bridge_payment(hash, tx, meta);
+                            }
+                            None
+                        })
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let sender = tx["Account"].as_str().unwrap_or_default();
+    let refund = if refundable { RefundSource::from_tx(hash, tx, meta) } else { None };
+    if let Some(action) = action.and_then(|action| admit_action(sender, action, refund)) {
+        match enqueue_action(action) {
+            Ok(()) => bridge_log_event("ingest", format!("📥 Queued {}", hash)),
+            Err(QueueError::AlreadyExists) => {}
+            Err(e) => bridge_log_event("error", format!("Failed to queue {}: {:?}", hash, e)),
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use candid::{Nat, Principal};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{get_bridge_address, get_iou_currency, get_iou_decimals};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::PendingAction;
+use crate::xrpl::issuer::{iou_value_to_units, units_to_iou_value};
+use crate::xrpl::memo::{generate_uuid, memo_text_from_tx};
+use crate::xrpl::params::currency_allowed;
+use crate::xrpl::refunds::{record_refund, RefundDecision, RefundSource};
+use crate::xrpl::swap::Issue;
+use crate::xrpl::types::{XRPLAmount, XRPLError};
+use crate::util::now_secs;
+
+const INTENT_BOOK_FILE: &str = "payment_intents.json";
+
+/// Intent destination tags are drawn from here up, clear of the fixed action tags (1001–4001).
+const INTENT_TAG_FLOOR: u32 = 1_000_000;
+
+/// Memo form for wallets that can't set a destination tag: `INTENT:<intent_id>`.
+const INTENT_MEMO_PREFIX: &str = "INTENT:";
+
+const DEFAULT_INTENT_TTL_SECS: u64 = 3_600;
+const MAX_INTENT_TTL_SECS: u64 = 7 * 86_400;
+
+/// What a fulfilled intent does on ICP.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum IntentAction {
+    Tip { artist: Principal },
+    NFTSale { nft_id: Nat, buyer: Principal },
+    TokenSwap { artist: Principal },
+    WrapXRP { principal: Principal },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntentStatus {
+    Open,
+    /// Some, but not all, of the amount has arrived.
+    PartiallyPaid,
+    Fulfilled,
+    Expired,
+    Cancelled,
+}
+
+impl IntentStatus {
+    fn accepts_payments(self) -> bool {
+        matches!(self, IntentStatus::Open | IntentStatus::PartiallyPaid)
+    }
+}
+
+/// One payment counted toward an intent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentPayment {
+    pub tx_hash: String,
+    pub sender: String,
+    pub source_tag: Option<u32>,
+    pub units: u128,
+    pub received_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentIntent {
+    pub intent_id: String,
+    pub destination_tag: u32,
+    pub action: IntentAction,
+    pub currency: Issue,
+    /// Drops for XRP, token units for the bridge IOU.
+    pub amount_units: u128,
+    pub received_units: u128,
+    pub payments: Vec<IntentPayment>,
+    pub status: IntentStatus,
+    pub fulfilled_tx: Option<String>,
+    /// Caller's own reference (order number, session, ...).
+    pub reference: Option<String>,
+    pub expires_at: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// What the app asks for. `amount` is drops for XRP, a decimal value for the bridge IOU.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentRequest {
+    pub action: IntentAction,
+    #[serde(default = "default_currency")]
+    pub currency: Issue,
+    pub amount: String,
+    pub expires_in_secs: Option<u64>,
+    pub reference: Option<String>,
+}
+
+fn default_currency() -> Issue {
+    Issue::XRP
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IntentBook {
+    intents: BTreeMap<String, PaymentIntent>,
+    /// Destination tag → intent id. Closed intents keep their tag so late payments are still matched.
+    tags: BTreeMap<u32, String>,
+}
+
+static INTENT_BOOK: Lazy<RwLock<IntentBook>> = Lazy::new(|| {
+    let book = load_json_state::<IntentBook>(INTENT_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load payment intents: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+fn persist(book: &IntentBook) {
+    if let Err(e) = persist_json_state(INTENT_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist payment intents: {:?}", e));
+    }
+}
+
+/// The bridge's own IOU, the only issued currency intents accept.
+fn bridge_iou() -> Option<Issue> {
+    Some(Issue::IOU { currency: get_iou_currency(), issuer: get_bridge_address()? })
+}
+
+/// Amount in intent units, or `None` when `amount` isn't in the intent's currency.
+fn units_in(currency: &Issue, amount: &XRPLAmount) -> Option<u128> {
+    if Issue::of(amount) != *currency {
+        return None;
+    }
+    match amount {
+        XRPLAmount::XRP { drops } => Some(*drops as u128),
+        XRPLAmount::IOU { value, .. } => iou_value_to_units(value, get_iou_decimals()),
+    }
+}
+
+fn amount_of(currency: &Issue, units: u128) -> XRPLAmount {
+    match currency {
+        Issue::XRP => XRPLAmount::XRP { drops: units.min(u64::MAX as u128) as u64 },
+        Issue::IOU { currency, issuer } => XRPLAmount::IOU {
+            currency: currency.clone(),
+            issuer: issuer.clone(),
+            value: units_to_iou_value(units, get_iou_decimals()),
+        },
+    }
+}
+
+/// Whether `currency` settles `action` the way its memo flow does: tips, sales and wraps
+/// are credited in drops; a token swap paid in the bridge IOU is a redemption.
+fn currency_fits(action: &IntentAction, currency: &Issue) -> bool {
+    match action {
+        IntentAction::TokenSwap { .. } => *currency != Issue::XRP,
+        IntentAction::Tip { .. } | IntentAction::NFTSale { .. } | IntentAction::WrapXRP { .. } => *currency == Issue::XRP,
+    }
+}
+
+/// Creates an intent with its own destination tag.
+pub fn create_payment_intent(request: IntentRequest) -> Result<PaymentIntent, XRPLError> {
+    if !currency_allowed(request.currency.currency_code()) {
+        return Err(XRPLError::InvalidTransaction(format!("Currency {} is not allowed", request.currency.currency_code())));
+    }
+    let amount_units = match &request.currency {
+        Issue::XRP => request.amount.parse::<u64>().ok().map(u128::from),
+        iou if Some(iou) == bridge_iou().as_ref() => iou_value_to_units(&request.amount, get_iou_decimals()),
+        _ => return Err(XRPLError::InvalidTransaction("Intents accept XRP or the bridge IOU only".into())),
+    }
+    .filter(|units| *units > 0)
+    .ok_or_else(|| XRPLError::InvalidTransaction(format!("Invalid intent amount {}", request.amount)))?;
+
+    if !currency_fits(&request.action, &request.currency) {
+        return Err(XRPLError::InvalidTransaction(format!(
+            "This intent action cannot be paid in {}",
+            request.currency.currency_code()
+        )));
+    }
+
+    let ttl = request.expires_in_secs.unwrap_or(DEFAULT_INTENT_TTL_SECS).min(MAX_INTENT_TTL_SECS);
+    let now = now_secs();
+
+    let mut book = INTENT_BOOK.write().unwrap();
+    let destination_tag = loop {
+        let tag = rand::thread_rng().gen_range(INTENT_TAG_FLOOR..=u32::MAX);
+        if !book.tags.contains_key(&tag) {
+            break tag;
+        }
+    };
+
+    let intent = PaymentIntent {
+        intent_id: generate_uuid(),
+        destination_tag,
+        action: request.action,
+        currency: request.currency,
+        amount_units,
+        received_units: 0,
+        payments: Vec::new(),
+        status: IntentStatus::Open,
+        fulfilled_tx: None,
+        reference: request.reference,
+        expires_at: now + ttl,
+        created_at: now,
+        updated_at: now,
+    };
+    book.tags.insert(destination_tag, intent.intent_id.clone());
+    book.intents.insert(intent.intent_id.clone(), intent.clone());
+    persist(&book);
+
+    bridge_log_event(
+        "intent",
+        format!("🧾 Intent {} for {} units, tag {}", intent.intent_id, amount_units, destination_tag),
+    );
+    Ok(intent)
+}
+
+pub fn get_payment_intent(intent_id: &str) -> Option<PaymentIntent> {
+    INTENT_BOOK.read().unwrap().intents.get(intent_id).cloned()
+}
+
+/// The intent a payment was counted toward, and whether it is the payment that fulfilled it.
+pub fn intent_of_payment(tx_hash: &str) -> Option<(String, bool)> {
+    let book = INTENT_BOOK.read().unwrap();
+    book.intents.values().find_map(|intent| {
+        intent.payments.iter().any(|p| p.tx_hash.eq_ignore_ascii_case(tx_hash)).then(|| {
+            let fulfilled = intent.fulfilled_tx.as_deref().is_some_and(|f| f.eq_ignore_ascii_case(tx_hash));
+            (intent.intent_id.clone(), fulfilled)
+        })
+    })
+}
+
+pub fn list_payment_intents() -> Vec<PaymentIntent> {
+    INTENT_BOOK.read().unwrap().intents.values().cloned().collect()
+}
+
+fn to_pending_action(intent: &PaymentIntent, tx_hash: &str) -> PendingAction {
+    let amount = Nat::from(intent.amount_units);
+    let tx_hash = tx_hash.to_string();
+    let uuid = intent.intent_id.clone();
+    match &intent.action {
+        IntentAction::Tip { artist } => PendingAction::Tip { artist: *artist, amount, tx_hash, uuid },
+        IntentAction::NFTSale { nft_id, buyer } => PendingAction::NFTSale {
+            nft_id: nft_id.clone(),
+            buyer: *buyer,
+            price: amount,
+            tx_hash,
+            uuid,
+            sale: None,
+        },
+        IntentAction::TokenSwap { artist } => PendingAction::TokenSwap { artist: *artist, amount, tx_hash, uuid, swap: None },
+        IntentAction::WrapXRP { principal } => PendingAction::WrapXRP { principal: *principal, drops: amount, tx_hash, uuid },
+    }
+}
+
+fn refund_payment(currency: &Issue, payment: &IntentPayment, reason: &'static str) {
+    record_refund(
+        RefundSource {
+            original_tx: payment.tx_hash.clone(),
+            sender: payment.sender.clone(),
+            source_tag: payment.source_tag,
+            delivered: amount_of(currency, payment.units),
+            is_refund_echo: false,
+        },
+        RefundDecision::Refund(reason),
+    );
+}
+
+/// Matches a Payment to the bridge against intents by destination tag or `INTENT:` memo.
+/// `None` means the payment isn't for an intent; `Some(None)` means it was handled without
+/// an action yet (partial payment, refund); `Some(Some(_))` fulfils the intent.
+pub fn match_intent_payment(tx_hash: &str, tx: &Value, meta: &Value) -> Option<Option<PendingAction>> {
+    let intent_id = {
+        let book = INTENT_BOOK.read().unwrap();
+        let by_tag = tx["DestinationTag"]
+            .as_u64()
+            .and_then(|tag| book.tags.get(&(tag as u32)).cloned());
+        by_tag.or_else(|| {
+            let memo = memo_text_from_tx(tx)?;
+            let id = memo.trim().strip_prefix(INTENT_MEMO_PREFIX)?;
+            book.intents.contains_key(id).then(|| id.to_string())
+        })
+    }?;
+
+    let source = RefundSource::from_tx(tx_hash, tx, meta)?;
+    let now = now_secs();
+
+    let mut book = INTENT_BOOK.write().unwrap();
+    let intent = book.intents.get_mut(&intent_id)?;
+    if intent.payments.iter().any(|p| p.tx_hash == tx_hash) {
+        return Some(None);
+    }
+
+    let rejection = if !intent.status.accepts_payments() {
+        Some("payment intent is closed")
+    } else if now >= intent.expires_at {
+        Some("payment intent expired")
+    } else {
+        None
+    };
+    let units = units_in(&intent.currency, &source.delivered);
+
+    let (Some(units), None) = (units, rejection) else {
+        let reason = rejection.unwrap_or("payment currency does not match the intent");
+        drop(book);
+        bridge_log_event("intent", format!("↩️ {} for intent {}: {}", tx_hash, intent_id, reason));
+        record_refund(source, RefundDecision::Refund(reason));
+        return Some(None);
+    };
+
+    intent.payments.push(IntentPayment {
+        tx_hash: tx_hash.to_string(),
+        sender: source.sender.clone(),
+        source_tag: source.source_tag,
+        units,
+        received_at: now,
+    });
+    intent.received_units += units;
+    intent.updated_at = now;
+
+    if intent.received_units < intent.amount_units {
+        intent.status = IntentStatus::PartiallyPaid;
+        bridge_log_event(
+            "intent",
+            format!("⏳ Intent {} has {}/{} units", intent_id, intent.received_units, intent.amount_units),
+        );
+        persist(&book);
+        return Some(None);
+    }
+
+    intent.status = IntentStatus::Fulfilled;
+    intent.fulfilled_tx = Some(tx_hash.to_string());
+    let excess = intent.received_units - intent.amount_units;
+    let currency = intent.currency.clone();
+    let action = to_pending_action(intent, tx_hash);
+    persist(&book);
+    drop(book);
+
+    bridge_log_event("intent", format!("✅ Intent {} fulfilled by {}", intent_id, tx_hash));
+    if excess > 0 {
+        record_refund(
+            RefundSource { delivered: amount_of(&currency, excess), ..source },
+            RefundDecision::Refund("overpayment of payment intent"),
+        );
+    }
+    Some(Some(action))
+}
+
+/// Closes an unfulfilled intent and refunds anything already paid toward it.
+fn close_intent(intent_id: &str, status: IntentStatus, reason: &'static str) -> Result<PaymentIntent, XRPLError> {
+    let intent = {
+        let mut book = INTENT_BOOK.write().unwrap();
+        let intent = book
+            .intents
+            .get_mut(intent_id)
+            .ok_or_else(|| XRPLError::Other(format!("Unknown intent {}", intent_id)))?;
+        if !intent.status.accepts_payments() {
+            return Err(XRPLError::InvalidTransaction(format!("Intent {} is {:?}", intent_id, intent.status)));
+        }
+        intent.status = status;
+        intent.updated_at = now_secs();
+        let intent = intent.clone();
+        persist(&book);
+        intent
+    };
+
+    for payment in &intent.payments {
+        refund_payment(&intent.currency, payment, reason);
+    }
+    bridge_log_event("intent", format!("🛑 Intent {} {:?}", intent_id, status));
+    Ok(intent)
+}
+
+pub fn cancel_payment_intent(intent_id: &str) -> Result<PaymentIntent, XRPLError> {
+    close_intent(intent_id, IntentStatus::Cancelled, "payment intent cancelled")
+}
+
+/// Expires overdue intents; partial payments are refunded.
+pub fn expire_payment_intents() -> usize {
+    let now = now_secs();
+    let overdue: Vec<String> = INTENT_BOOK
+        .read()
+        .unwrap()
+        .intents
+        .values()
+        .filter(|i| i.status.accepts_payments() && now >= i.expires_at)
+        .map(|i| i.intent_id.clone())
+        .collect();
+
+    overdue
+        .iter()
+        .filter(|id| close_intent(id, IntentStatus::Expired, "payment intent expired before full payment").is_ok())
+        .count()
+}
+
+pub async fn run_intent_expiry(interval_secs: u64) {
+    loop {
+        expire_payment_intents();
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use candid::{Nat, Principal};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::{get_bridge_address, get_iou_auto_authorize, get_iou_currency, get_iou_decimals};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::{enqueue_action, PendingAction, QueueError};
+use crate::xrpl::approvals::admit_action;
+use crate::xrpl::linking::linked_principal;
+use crate::xrpl::memo::{memo_text_from_tx, parse_memo_string};
+use crate::xrpl::multisig::submit_transfer;
+use crate::xrpl::outbound::submit_and_wait;
+use crate::xrpl::refunds::RefundSource;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::{XRPLActionType, XRPLAmount, XRPLError};
+use crate::util::now_secs;
+
+const ISSUER_STATE_FILE: &str = "iou_issuer.json";
+
+/// `AccountSet` flags (SetFlag values) and the matching `account_info` flag bits.
+const ASF_REQUIRE_AUTH: u32 = 2;
+const ASF_DEFAULT_RIPPLE: u32 = 8;
+const LSF_REQUIRE_AUTH: u64 = 0x0004_0000;
+const LSF_DEFAULT_RIPPLE: u64 = 0x0080_0000;
+
+/// `TrustSet` flag the issuer uses to authorize a holder's line.
+const TF_SETF_AUTH: u32 = 0x0001_0000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TrustLineStatus {
+    /// Holder opened a line; waiting for operator approval.
+    Pending,
+    /// Approved; the authorizing `TrustSet` hasn't validated yet.
+    Approved,
+    Authorized,
+    Rejected,
+}
+
+/// A holder's trust line to the Axia IOU.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustLineRecord {
+    pub holder: String,
+    pub limit: String,
+    pub status: TrustLineStatus,
+    pub request_tx: String,
+    pub authorize_tx: Option<String>,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IssuanceStatus {
+    /// Written before submission; a crash here needs manual review, never a blind resend.
+    Submitting,
+    Issued,
+    Failed,
+}
+
+/// One ICP → XRPL issuance, keyed by the ICP-side reference that backs it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuanceRecord {
+    pub icp_ref: String,
+    pub destination: String,
+    pub units: u128,
+    pub status: IssuanceStatus,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+    pub created_at: u64,
+}
+
+/// One XRPL → ICP redemption (IOUs paid back to the issuer).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedemptionRecord {
+    pub tx_hash: String,
+    pub account: String,
+    pub units: u128,
+    pub principal: Option<String>,
+    pub redeemed_at: u64,
+}
+
+/// A redemption from an unlinked sender that named no principal, waiting for the link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedRedemption {
+    pub tx_hash: String,
+    pub account: String,
+    pub units: u128,
+    pub uuid: String,
+    pub refund: Option<RefundSource>,
+    pub parked_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct IssuerState {
+    trust_lines: BTreeMap<String, TrustLineRecord>,
+    issuances: BTreeMap<String, IssuanceRecord>,
+    redemptions: BTreeMap<String, RedemptionRecord>,
+    #[serde(default)]
+    parked_redemptions: BTreeMap<String, ParkedRedemption>,
+}
+
+/// Issued vs. redeemed on the bridge's books, compared with XRPL (and optionally ICP).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplyReport {
+    pub currency: String,
+    pub issued_units: u128,
+    pub redeemed_units: u128,
+    /// Issuances still in `Submitting`; may or may not be on-ledger.
+    pub in_flight_units: u128,
+    pub ledger_outstanding_units: u128,
+    pub xrpl_obligation_units: u128,
+    pub icp_locked_units: Option<u128>,
+    pub consistent: bool,
+}
+
+static ISSUER_STATE: Lazy<RwLock<IssuerState>> = Lazy::new(|| {
+    let state = load_json_state::<IssuerState>(ISSUER_STATE_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load issuer state: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(state)
+});
+
+fn persist(state: &IssuerState) {
+    if let Err(e) = persist_json_state(ISSUER_STATE_FILE, state) {
+        bridge_log_event("error", format!("Failed to persist issuer state: {:?}", e));
+    }
+}
+
+fn issuer_address() -> Result<String, XRPLError> {
+    get_bridge_address().ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))
+}
+
+/// Converts an XRPL IOU value ("12.5", "1e-3") to ICP token units, rounding down.
+pub fn iou_value_to_units(value: &str, decimals: u32) -> Option<u128> {
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((m, e)) => (m, e.parse::<i32>().ok()?),
+        None => (value, 0),
+    };
+    if mantissa.starts_with('-') {
+        return None;
+    }
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: u128 = format!("{}{}", int_part, frac_part).parse().ok()?;
+
+    let shift = decimals as i32 + exponent - frac_part.len() as i32;
+    if shift >= 0 {
+        digits.checked_mul(10u128.checked_pow(shift as u32)?)
+    } else {
+        Some(digits / 10u128.checked_pow((-shift) as u32)?)
+    }
+}
+
+/// Converts ICP token units back to an XRPL IOU value string.
+pub fn units_to_iou_value(units: u128, decimals: u32) -> String {
+    let scale = 10u128.pow(decimals);
+    let whole = units / scale;
+    let frac = units % scale;
+    if frac == 0 {
+        return whole.to_string();
+    }
+    let frac = format!("{:0width$}", frac, width = decimals as usize);
+    format!("{}.{}", whole, frac.trim_end_matches('0'))
+}
+
+/// Enables DefaultRipple and RequireAuth on the issuing account if they aren't set yet.
+/// RequireAuth can only be turned on while the account has no trust lines.
+pub async fn configure_issuer(rpc: &XRPLRpcClient) -> Result<(), XRPLError> {
+    let account = issuer_address()?;
+    let info = rpc.account_info(&account).await?;
+    let flags = info["Flags"].as_u64().unwrap_or(0);
+
+    if flags & LSF_DEFAULT_RIPPLE == 0 {
+        let tx = json!({ "TransactionType": "AccountSet", "SetFlag": ASF_DEFAULT_RIPPLE });
+        submit_and_wait(rpc, "issuer-default-ripple", tx).await?;
+        bridge_log_event("issuer", "✅ DefaultRipple enabled on issuing account".into());
+    }
+
+    if flags & LSF_REQUIRE_AUTH == 0 {
+        let tx = json!({ "TransactionType": "AccountSet", "SetFlag": ASF_REQUIRE_AUTH });
+        submit_and_wait(rpc, "issuer-require-auth", tx).await?;
+        bridge_log_event("issuer", "✅ RequireAuth enabled on issuing account".into());
+    }
+
+    Ok(())
+}
+
+/// Records a holder's `TrustSet` towards the Axia IOU. Returns false for unrelated lines.
+pub fn observe_trust_set(tx_hash: &str, tx: &Value) -> bool {
+    let Ok(issuer) = issuer_address() else {
+        return false;
+    };
+    let limit = &tx["LimitAmount"];
+    if limit["issuer"].as_str() != Some(issuer.as_str()) || limit["currency"].as_str() != Some(get_iou_currency().as_str()) {
+        return false;
+    }
+    let Some(holder) = tx["Account"].as_str() else {
+        return false;
+    };
+
+    let mut state = ISSUER_STATE.write().unwrap();
+    let status = match state.trust_lines.get(holder).map(|line| line.status) {
+        Some(status @ (TrustLineStatus::Authorized | TrustLineStatus::Rejected | TrustLineStatus::Approved)) => status,
+        _ if get_iou_auto_authorize() => TrustLineStatus::Approved,
+        _ => TrustLineStatus::Pending,
+    };
+
+    let existing = state.trust_lines.get(holder).and_then(|line| line.authorize_tx.clone());
+    state.trust_lines.insert(
+        holder.to_string(),
+        TrustLineRecord {
+            holder: holder.to_string(),
+            limit: limit["value"].as_str().unwrap_or("0").to_string(),
+            status,
+            request_tx: tx_hash.to_string(),
+            authorize_tx: existing,
+            updated_at: now_secs(),
+        },
+    );
+    persist(&state);
+
+    bridge_log_event("issuer", format!("🤝 Trust line from {} ({:?})", holder, status));
+    true
+}
+
+/// Operator decision on a pending trust line.
+pub fn review_trust_line(holder: &str, approve: bool) -> Result<TrustLineRecord, XRPLError> {
+    let mut state = ISSUER_STATE.write().unwrap();
+    let line = state
+        .trust_lines
+        .get_mut(holder)
+        .ok_or_else(|| XRPLError::Other(format!("No trust line from {}", holder)))?;
+
+    if line.status == TrustLineStatus::Authorized {
+        return Err(XRPLError::Other(format!("{} is already authorized", holder)));
+    }
+    line.status = if approve { TrustLineStatus::Approved } else { TrustLineStatus::Rejected };
+    line.updated_at = now_secs();
+
+    let updated = line.clone();
+    persist(&state);
+    Ok(updated)
+}
+
+/// Lists known trust lines to the Axia IOU.
+pub fn list_trust_lines() -> Vec<TrustLineRecord> {
+    ISSUER_STATE.read().unwrap().trust_lines.values().cloned().collect()
+}
+
+/// Sends the issuer-side `TrustSet` (tfSetfAuth) that authorizes a holder's line.
+pub async fn authorize_trust_line(rpc: &XRPLRpcClient, holder: &str) -> Result<Value, XRPLError> {
+    let tx = json!({
+        "TransactionType": "TrustSet",
+        "LimitAmount": { "currency": get_iou_currency(), "issuer": holder, "value": "0" },
+        "Flags": TF_SETF_AUTH,
+    });
+
+    let validated = submit_and_wait(rpc, &format!("trust-auth-{}", holder), tx).await?;
+
+    let mut state = ISSUER_STATE.write().unwrap();
+    if let Some(line) = state.trust_lines.get_mut(holder) {
+        line.status = TrustLineStatus::Authorized;
+        line.authorize_tx = validated["hash"].as_str().map(str::to_string);
+        line.updated_at = now_secs();
+    }
+    persist(&state);
+
+    bridge_log_event("issuer", format!("✅ Authorized trust line for {}", holder));
+    Ok(validated)
+}
+
+/// Authorizes every approved line that isn't authorized on-ledger yet.
+pub async fn authorize_approved_lines(rpc: &XRPLRpcClient) {
+    let approved: Vec<String> = list_trust_lines()
+        .into_iter()
+        .filter(|line| line.status == TrustLineStatus::Approved)
+        .map(|line| line.holder)
+        .collect();
+
+    for holder in approved {
+        if let Err(e) = authorize_trust_line(rpc, &holder).await {
+            bridge_log_event("warn", format!("Authorizing {} failed: {}", holder, e));
+        }
+    }
+}
+
+/// ICP → XRPL: issues `units` of the Axia IOU to `destination`, backed by `icp_ref`.
+/// Idempotent per `icp_ref`: a reference that was already issued returns its record.
+pub async fn issue_iou(
+    rpc: &XRPLRpcClient,
+    icp_ref: &str,
+    destination: &str,
+    units: u128,
+) -> Result<IssuanceRecord, XRPLError> {
+    let issuer = issuer_address()?;
+    if units == 0 {
+        return Err(XRPLError::InvalidTransaction("Issuance amount must be non-zero".into()));
+    }
+
+    {
+        let mut state = ISSUER_STATE.write().unwrap();
+        if let Some(existing) = state.issuances.get(icp_ref) {
+            if existing.status != IssuanceStatus::Failed {
+                return Ok(existing.clone());
+            }
+        }
+        match state.trust_lines.get(destination).map(|line| line.status) {
+            Some(TrustLineStatus::Authorized) => {}
+            other => {
+                return Err(XRPLError::InvalidTransaction(format!(
+                    "{} has no authorized trust line ({:?})",
+                    destination, other
+                )))
+            }
+        }
+        state.issuances.insert(
+            icp_ref.to_string(),
+            IssuanceRecord {
+                icp_ref: icp_ref.to_string(),
+                destination: destination.to_string(),
+                units,
+                status: IssuanceStatus::Submitting,
+                tx_hash: None,
+                error: None,
+                created_at: now_secs(),
+            },
+        );
+        persist(&state);
+    }
+
+    let amount = XRPLAmount::IOU {
+        currency: get_iou_currency(),
+        issuer,
+        value: units_to_iou_value(units, get_iou_decimals()),
+    };
+    let payment = json!({
+        "TransactionType": "Payment",
+        "Destination": destination,
+        "Amount": amount.to_json(),
+    });
+
+    let result = submit_transfer(rpc, &format!("iou-issue-{}", icp_ref), payment).await;
+
+    let mut state = ISSUER_STATE.write().unwrap();
+    let record = state
+        .issuances
+        .get_mut(icp_ref)
+        .ok_or_else(|| XRPLError::Other(format!("Issuance {} vanished", icp_ref)))?;
+    match &result {
+        Ok(tx) => {
+            record.status = IssuanceStatus::Issued;
+            record.tx_hash = tx["hash"].as_str().map(str::to_string);
+        }
+        Err(e) => {
+            record.status = IssuanceStatus::Failed;
+            record.error = Some(e.to_string());
+        }
+    }
+    let record = record.clone();
+    persist(&state);
+
+    result.map(|_| {
+        bridge_log_event("issuer", format!("🪙 Issued {} units to {} for {}", units, destination, icp_ref));
+        record
+    })
+}
+
+/// XRPL → ICP: turns an IOU payment back to the issuer into a `TokenSwap` redemption.
+/// Returns `None` when the payment isn't an Axia IOU redemption, or when it is parked
+/// because its sender is unlinked and named no principal.
+pub fn ingest_redemption(tx_hash: &str, tx: &Value, meta: &Value) -> Option<PendingAction> {
+    let issuer = issuer_address().ok()?;
+    if tx["Destination"].as_str() != Some(issuer.as_str()) {
+        return None;
+    }
+
+    // Partial payments deliver less than `Amount`; only the delivered part was redeemed.
+    let delivered = if meta["delivered_amount"].is_null() { &tx["Amount"] } else { &meta["delivered_amount"] };
+    let XRPLAmount::IOU { currency, value, .. } = XRPLAmount::from_json(delivered)? else {
+        return None;
+    };
+    if currency != get_iou_currency() {
+        return None;
+    }
+
+    let account = tx["Account"].as_str()?.to_string();
+    let units = iou_value_to_units(&value, get_iou_decimals())?;
+
+    let memo = memo_text_from_tx(tx).and_then(|text| parse_memo_string(&text).ok());
+    let principal = memo
+        .as_ref()
+        .filter(|m| m.action == XRPLActionType::TokenSwap)
+        .and_then(|m| m.fields.get("ARTIST"))
+        .and_then(|p| Principal::from_text(p).ok())
+        .or_else(|| linked_principal(&account));
+    let uuid = memo
+        .as_ref()
+        .and_then(|m| m.fields.get("UUID").cloned())
+        .unwrap_or_else(|| tx_hash.to_string());
+
+    {
+        let mut state = ISSUER_STATE.write().unwrap();
+        if state.redemptions.contains_key(tx_hash) {
+            return None;
+        }
+        state.redemptions.insert(
+            tx_hash.to_string(),
+            RedemptionRecord {
+                tx_hash: tx_hash.to_string(),
+                account: account.clone(),
+                units,
+                principal: principal.map(|p| p.to_text()),
+                redeemed_at: now_secs(),
+            },
+        );
+        if principal.is_none() {
+            state.parked_redemptions.insert(
+                tx_hash.to_string(),
+                ParkedRedemption {
+                    tx_hash: tx_hash.to_string(),
+                    account: account.clone(),
+                    units,
+                    uuid: uuid.clone(),
+                    refund: RefundSource::from_tx(tx_hash, tx, meta),
+                    parked_at: now_secs(),
+                },
+            );
+        }
+        persist(&state);
+    }
+
+    bridge_log_event("issuer", format!("🔁 {} redeemed {} units ({})", account, units, tx_hash));
+    let Some(principal) = principal else {
+        bridge_log_event("warn", format!("⏸️ Redemption {} names no principal and {} is unlinked; it waits for the link", tx_hash, account));
+        return None;
+    };
+    Some(redemption_action(tx_hash, principal, units, uuid))
+}
+
+fn redemption_action(tx_hash: &str, principal: Principal, units: u128, uuid: String) -> PendingAction {
+    PendingAction::TokenSwap {
+        artist: principal,
+        amount: Nat::from(units),
+        tx_hash: tx_hash.to_string(),
+        uuid,
+        swap: None,
+    }
+}
+
+/// Whether a redemption is parked until its sender links a principal.
+pub fn is_parked_redemption(tx_hash: &str) -> bool {
+    ISSUER_STATE.read().unwrap().parked_redemptions.keys().any(|hash| hash.eq_ignore_ascii_case(tx_hash))
+}
+
+/// Queues parked redemptions whose sender has linked a principal since.
+pub fn release_linked_redemptions() {
+    let ready: Vec<(ParkedRedemption, Principal)> = {
+        let mut state = ISSUER_STATE.write().unwrap();
+        let ready: Vec<(String, Principal)> = state
+            .parked_redemptions
+            .values()
+            .filter_map(|parked| Some((parked.tx_hash.clone(), linked_principal(&parked.account)?)))
+            .collect();
+        if ready.is_empty() {
+            return;
+        }
+        let ready = ready
+            .into_iter()
+            .filter_map(|(hash, principal)| {
+                if let Some(record) = state.redemptions.get_mut(&hash) {
+                    record.principal = Some(principal.to_text());
+                }
+                Some((state.parked_redemptions.remove(&hash)?, principal))
+            })
+            .collect();
+        persist(&state);
+        ready
+    };
+
+    for (parked, principal) in ready {
+        let action = redemption_action(&parked.tx_hash, principal, parked.units, parked.uuid);
+        let Some(action) = admit_action(&parked.account, action, parked.refund) else { continue };
+        match enqueue_action(action) {
+            Ok(()) => bridge_log_event("issuer", format!("▶️ Sender linked; queued redemption {}", parked.tx_hash)),
+            Err(QueueError::AlreadyExists) => {}
+            Err(e) => bridge_log_event("error", format!("Failed to queue {}: {:?}", parked.tx_hash, e)),
+        }
+    }
+}
+
+/// Redemptions still waiting for their sender to link, for the admin API.
+pub fn list_parked_redemptions() -> Vec<ParkedRedemption> {
+    ISSUER_STATE.read().unwrap().parked_redemptions.values().cloned().collect()
+}
+
+/// Compares the bridge's issuance books with the issuer's obligations on XRPL
+/// and, when given, the amount the ICP side holds locked against them.
+pub async fn supply_report(rpc: &XRPLRpcClient, icp_locked_units: Option<u128>) -> Result<SupplyReport, XRPLError> {
+    let issuer = issuer_address()?;
+    let currency = get_iou_currency();
+    let decimals = get_iou_decimals();
+
+    let balances = rpc.gateway_balances(&issuer).await?;
+    let obligation = balances["obligations"][&currency].as_str().unwrap_or("0");
+    let xrpl_obligation_units = iou_value_to_units(obligation, decimals)
+        .ok_or_else(|| XRPLError::InvalidResponse(format!("Unparseable obligation {}", obligation)))?;
+
+    let state = ISSUER_STATE.read().unwrap();
+    let sum = |status: IssuanceStatus| -> u128 {
+        state.issuances.values().filter(|i| i.status == status).map(|i| i.units).sum()
+    };
+    let issued_units = sum(IssuanceStatus::Issued);
+    let in_flight_units = sum(IssuanceStatus::Submitting);
+    let redeemed_units: u128 = state.redemptions.values().map(|r| r.units).sum();
+    let ledger_outstanding_units = issued_units.saturating_sub(redeemed_units);
+
+    // In-flight issuances may or may not have landed yet.
+    let xrpl_matches = xrpl_obligation_units >= ledger_outstanding_units
+        && xrpl_obligation_units <= ledger_outstanding_units + in_flight_units;
+    let icp_matches = icp_locked_units.map(|locked| locked >= xrpl_obligation_units).unwrap_or(true);
+
+    Ok(SupplyReport {
+        currency,
+        issued_units,
+        redeemed_units,
+        in_flight_units,
+        ledger_outstanding_units,
+        xrpl_obligation_units,
+        icp_locked_units,
+        consistent: xrpl_matches && icp_matches,
+    })
+}
+
+/// Background loop: authorize approved trust lines and flag supply drift.
+pub async fn run_issuer_maintenance(rpc: XRPLRpcClient, interval_secs: u64) {
+    if let Err(e) = configure_issuer(&rpc).await {
+        bridge_log_event("error", format!("Issuer setup failed: {}", e));
+    }
+
+    loop {
+        authorize_approved_lines(&rpc).await;
+        release_linked_redemptions();
+
+        match supply_report(&rpc, None).await {
+            Ok(report) if !report.consistent => {
+                bridge_log_event("error", format!("⚠️ IOU supply drift: {:?}", report));
+            }
+            Ok(_) => {}
+            Err(e) => bridge_log_event("warn", format!("Supply check failed: {}", e)),
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
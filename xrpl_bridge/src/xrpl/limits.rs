@@ -0,0 +1,394 @@
+use std::fs;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::config::get_limit_rules_file;
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::PendingAction;
+use crate::xrpl::params::governed_limit_rules;
+use crate::util::now_secs;
+
+const LIMITS_BOOK_FILE: &str = "limits.json";
+
+/// Usage is kept at least this long, so rules added later see recent history.
+const MIN_RETENTION_SECS: u64 = 86_400;
+
+/// What a rule counts against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitScope {
+    /// Each XRPL sending account.
+    Sender,
+    /// Each ICP principal credited.
+    Recipient,
+    /// Each action type (Tip, NFTSale, ...).
+    ActionType,
+    /// The whole bridge.
+    Global,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BreachAction {
+    /// Hold the action for approval.
+    #[default]
+    Hold,
+    /// Return the payment when it can be refunded; otherwise hold.
+    Refund,
+}
+
+/// A rolling-window cap on XRP value, IOU value and/or number of bridged actions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitRule {
+    pub id: String,
+    pub scope: LimitScope,
+    /// Only count actions of this type (e.g. "WrapXRP"); all types when unset.
+    #[serde(default)]
+    pub action: Option<String>,
+    pub window_secs: u64,
+    #[serde(default)]
+    pub max_drops: Option<u64>,
+    /// Cap on IOU redeemed or paid, in the ICP token's smallest units.
+    #[serde(default)]
+    pub max_iou_units: Option<u64>,
+    #[serde(default)]
+    pub max_count: Option<u64>,
+    #[serde(default)]
+    pub on_breach: BreachAction,
+}
+
+/// One admitted action, as counted by the rules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageEvent {
+    pub tx_hash: String,
+    pub sender: String,
+    pub recipient: Option<String>,
+    pub action: String,
+    /// XRP value; IOU-denominated actions count under `iou_units` instead.
+    pub drops: u64,
+    #[serde(default)]
+    pub iou_units: u64,
+    pub at: u64,
+}
+
+/// Totals under one rule for one key; a breach when over its maximum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleUsage {
+    pub rule_id: String,
+    pub key: String,
+    pub window_secs: u64,
+    /// For a breach, includes the breaching action.
+    pub used_drops: u64,
+    #[serde(default)]
+    pub used_iou_units: u64,
+    pub used_count: u64,
+    pub max_drops: Option<u64>,
+    #[serde(default)]
+    pub max_iou_units: Option<u64>,
+    pub max_count: Option<u64>,
+    pub on_breach: BreachAction,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LimitsBook {
+    events: Vec<UsageEvent>,
+}
+
+static LIMITS_BOOK: Lazy<RwLock<LimitsBook>> = Lazy::new(|| {
+    let book = load_json_state::<LimitsBook>(LIMITS_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load limits book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+static LIMIT_RULES: Lazy<RwLock<Vec<LimitRule>>> = Lazy::new(|| RwLock::new(read_rules().unwrap_or_else(|e| {
+    bridge_log_event("error", format!("❌ Could not load limit rules: {}", e));
+    Vec::new()
+})));
+
+fn persist(book: &LimitsBook) {
+    if let Err(e) = persist_json_state(LIMITS_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist limits book: {:?}", e));
+    }
+}
+
+fn read_rules() -> Result<Vec<LimitRule>, String> {
+    let Some(path) = get_limit_rules_file() else {
+        return Ok(Vec::new());
+    };
+    let raw = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+    let rules: Vec<LimitRule> = serde_json::from_str(&raw).map_err(|e| format!("{}: {}", path, e))?;
+    validate_limit_rules(&rules).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(rules)
+}
+
+pub fn validate_limit_rules(rules: &[LimitRule]) -> Result<(), String> {
+    if let Some(rule) = rules
+        .iter()
+        .find(|r| r.window_secs == 0 || (r.max_drops.is_none() && r.max_iou_units.is_none() && r.max_count.is_none()))
+    {
+        return Err(format!("rule {} needs a window and a max_drops, max_iou_units or max_count", rule.id));
+    }
+    Ok(())
+}
+
+/// Re-reads `LIMIT_RULES_FILE`. A file that fails to load leaves the current rules in force.
+pub fn reload_limit_rules() -> Result<Vec<LimitRule>, String> {
+    let rules = read_rules()?;
+    bridge_log_event("limits", format!("📏 Loaded {} limit rule(s)", rules.len()));
+    *LIMIT_RULES.write().unwrap() = rules.clone();
+    Ok(rules)
+}
+
+/// Rules in force: the governed set when one is active, otherwise `LIMIT_RULES_FILE`.
+pub fn list_limit_rules() -> Vec<LimitRule> {
+    governed_limit_rules().unwrap_or_else(|| LIMIT_RULES.read().unwrap().clone())
+}
+
+fn usage_for(sender: &str, action: &PendingAction, at: u64) -> UsageEvent {
+    let recipient = match action {
+        PendingAction::Tip { artist, .. } => Some(artist.to_text()),
+        PendingAction::NFTSale { buyer, .. } => Some(buyer.to_text()),
+        PendingAction::TokenSwap { artist, .. } => Some(artist.to_text()),
+        PendingAction::WrapXRP { principal, .. } => Some(principal.to_text()),
+        PendingAction::EscrowRegistration { .. } => None,
+    };
+    UsageEvent {
+        tx_hash: action.tx_hash().to_string(),
+        sender: sender.to_string(),
+        // Unresolved principals aren't one recipient.
+        recipient: recipient.filter(|p| *p != candid::Principal::anonymous().to_text()),
+        action: action.kind().to_string(),
+        drops: action.xrp_drops(),
+        iou_units: action.iou_units(),
+        at,
+    }
+}
+
+fn rule_key(rule: &LimitRule, usage: &UsageEvent) -> Option<String> {
+    match rule.scope {
+        LimitScope::Sender => Some(usage.sender.clone()),
+        LimitScope::Recipient => usage.recipient.clone(),
+        LimitScope::ActionType => Some(usage.action.clone()),
+        LimitScope::Global => Some("*".to_string()),
+    }
+}
+
+fn breaches(book: &LimitsBook, rules: &[LimitRule], usage: &UsageEvent) -> Vec<RuleUsage> {
+    rules
+        .iter()
+        .filter(|rule| rule.action.as_deref().is_none_or(|a| a == usage.action))
+        .filter_map(|rule| {
+            let key = rule_key(rule, usage)?;
+            let window_start = usage.at.saturating_sub(rule.window_secs);
+            let (used_drops, used_iou_units, used_count) = book
+                .events
+                .iter()
+                .filter(|e| e.at > window_start)
+                .filter(|e| rule.action.as_deref().is_none_or(|a| a == e.action))
+                .filter(|e| rule_key(rule, e).as_deref() == Some(key.as_str()))
+                .fold((usage.drops, usage.iou_units, 1u64), |(drops, units, count), e| {
+                    (drops.saturating_add(e.drops), units.saturating_add(e.iou_units), count + 1)
+                });
+
+            let over = rule.max_drops.is_some_and(|max| used_drops > max)
+                || rule.max_iou_units.is_some_and(|max| used_iou_units > max)
+                || rule.max_count.is_some_and(|max| used_count > max);
+            over.then(|| RuleUsage {
+                rule_id: rule.id.clone(),
+                key,
+                window_secs: rule.window_secs,
+                used_drops,
+                used_iou_units,
+                used_count,
+                max_drops: rule.max_drops,
+                max_iou_units: rule.max_iou_units,
+                max_count: rule.max_count,
+                on_breach: rule.on_breach,
+            })
+        })
+        .collect()
+}
+
+fn record_usage(book: &mut LimitsBook, usage: UsageEvent, rules: &[LimitRule]) {
+    let retention = rules.iter().map(|r| r.window_secs).max().unwrap_or(0).max(MIN_RETENTION_SECS);
+    let cutoff = usage.at.saturating_sub(retention);
+    book.events.retain(|e| e.at > cutoff);
+    book.events.push(usage);
+}
+
+/// Rules the action would breach, without counting it.
+pub fn limit_breaches(sender: &str, action: &PendingAction) -> Vec<RuleUsage> {
+    let usage = usage_for(sender, action, now_secs());
+    let book = LIMITS_BOOK.read().unwrap();
+    if book.events.iter().any(|e| e.tx_hash == usage.tx_hash) {
+        return Vec::new();
+    }
+    breaches(&book, &list_limit_rules(), &usage)
+}
+
+/// Counts the action if it fits every rule; otherwise returns the breaches without counting.
+/// Re-delivered actions that were already counted always fit.
+pub fn try_count_action(sender: &str, action: &PendingAction) -> Result<(), Vec<RuleUsage>> {
+    let rules = list_limit_rules();
+    let usage = usage_for(sender, action, now_secs());
+
+    let mut book = LIMITS_BOOK.write().unwrap();
+    if book.events.iter().any(|e| e.tx_hash == usage.tx_hash) {
+        return Ok(());
+    }
+    let breached = breaches(&book, &rules, &usage);
+    if !breached.is_empty() {
+        return Err(breached);
+    }
+    record_usage(&mut book, usage, &rules);
+    persist(&book);
+    Ok(())
+}
+
+/// Counts an action regardless of the rules (an approved override). Idempotent per action.
+pub fn count_action(sender: &str, action: &PendingAction) {
+    let rules = list_limit_rules();
+    let usage = usage_for(sender, action, now_secs());
+
+    let mut book = LIMITS_BOOK.write().unwrap();
+    if book.events.iter().any(|e| e.tx_hash == usage.tx_hash) {
+        return;
+    }
+    record_usage(&mut book, usage, &rules);
+    persist(&book);
+}
+
+/// Window totals per rule for one key (sender, principal, action type, or `*`).
+pub fn limit_usage(key: &str) -> Vec<RuleUsage> {
+    let now = now_secs();
+    let book = LIMITS_BOOK.read().unwrap();
+    list_limit_rules()
+        .into_iter()
+        .filter_map(|rule| {
+            let window_start = now.saturating_sub(rule.window_secs);
+            let events: Vec<&UsageEvent> = book
+                .events
+                .iter()
+                .filter(|e| e.at > window_start)
+                .filter(|e| rule.action.as_deref().is_none_or(|a| a == e.action))
+                .filter(|e| rule_key(&rule, e).as_deref() == Some(key))
+                .collect();
+            (!events.is_empty()).then(|| RuleUsage {
+                rule_id: rule.id.clone(),
+                key: key.to_string(),
+                window_secs: rule.window_secs,
+                used_drops: events.iter().map(|e| e.drops).sum(),
+                used_iou_units: events.iter().fold(0u64, |units, e| units.saturating_add(e.iou_units)),
+                used_count: events.len() as u64,
+                max_drops: rule.max_drops,
+                max_iou_units: rule.max_iou_units,
+                max_count: rule.max_count,
+                on_breach: rule.on_breach,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(scope: LimitScope, window_secs: u64, max_drops: Option<u64>, max_count: Option<u64>) -> LimitRule {
+        LimitRule {
+            id: format!("{:?}", scope),
+            scope,
+            action: None,
+            window_secs,
+            max_drops,
+            max_iou_units: None,
+            max_count,
+            on_breach: BreachAction::Hold,
+        }
+    }
+
+    fn usage(tx_hash: &str, sender: &str, drops: u64, at: u64) -> UsageEvent {
+        UsageEvent {
+            tx_hash: tx_hash.to_string(),
+            sender: sender.to_string(),
+            recipient: None,
+            action: "Tip".to_string(),
+            drops,
+            iou_units: 0,
+            at,
+        }
+    }
+
+    #[test]
+    fn drops_cap_counts_only_the_rolling_window() {
+        let rules = vec![rule(LimitScope::Sender, 3_600, Some(100), None)];
+        let mut book = LimitsBook::default();
+        record_usage(&mut book, usage("A", "rS", 60, 1_000), &rules);
+
+        // 60 + 50 inside the hour breaches, with the new action included in the totals.
+        let breached = breaches(&book, &rules, &usage("B", "rS", 50, 2_000));
+        assert_eq!(breached.len(), 1);
+        assert_eq!((breached[0].used_drops, breached[0].used_count), (110, 2));
+        assert_eq!(breached[0].key, "rS");
+
+        // Exactly at the cap is fine; once the first event leaves the window so is the rest.
+        assert!(breaches(&book, &rules, &usage("B", "rS", 40, 2_000)).is_empty());
+        assert!(breaches(&book, &rules, &usage("B", "rS", 50, 4_600)).is_empty());
+    }
+
+    #[test]
+    fn keys_and_action_filters_separate_usage() {
+        let mut per_sender = rule(LimitScope::Sender, 3_600, None, Some(1));
+        per_sender.action = Some("Tip".to_string());
+        let global = rule(LimitScope::Global, 3_600, None, Some(2));
+        let rules = vec![per_sender, global];
+
+        let mut book = LimitsBook::default();
+        record_usage(&mut book, usage("A", "rOne", 1, 1_000), &rules);
+
+        // Another sender only counts toward the global rule.
+        assert!(breaches(&book, &rules, &usage("B", "rTwo", 1, 1_100)).is_empty());
+        let mut wrap = usage("B", "rOne", 1, 1_100);
+        wrap.action = "WrapXRP".to_string();
+        assert!(breaches(&book, &rules, &wrap).is_empty());
+
+        let breached = breaches(&book, &rules, &usage("B", "rOne", 1, 1_100));
+        assert_eq!(breached.iter().map(|b| b.rule_id.as_str()).collect::<Vec<_>>(), ["Sender"]);
+
+        record_usage(&mut book, usage("B", "rTwo", 1, 1_100), &rules);
+        let breached = breaches(&book, &rules, &usage("C", "rThree", 1, 1_200));
+        assert_eq!(breached.iter().map(|b| b.rule_id.as_str()).collect::<Vec<_>>(), ["Global"]);
+    }
+
+    #[test]
+    fn iou_cap_counts_redeemed_units() {
+        let mut cap = rule(LimitScope::Sender, 3_600, None, None);
+        cap.max_iou_units = Some(1_000);
+        let rules = vec![cap];
+        let redeem = |tx_hash: &str, units: u64| UsageEvent { iou_units: units, ..usage(tx_hash, "rS", 0, 1_000) };
+
+        let mut book = LimitsBook::default();
+        record_usage(&mut book, redeem("A", 600), &rules);
+        assert!(breaches(&book, &rules, &redeem("B", 400)).is_empty());
+
+        let breached = breaches(&book, &rules, &redeem("B", 500));
+        assert_eq!((breached[0].used_iou_units, breached[0].used_drops), (1_100, 0));
+        // XRP value doesn't count against an IOU cap.
+        assert!(breaches(&book, &rules, &usage("B", "rS", 5_000, 1_000)).is_empty());
+    }
+
+    #[test]
+    fn recording_prunes_events_past_the_longest_window() {
+        let rules = vec![rule(LimitScope::Global, MIN_RETENTION_SECS * 2, Some(1_000), None)];
+        let mut book = LimitsBook::default();
+        record_usage(&mut book, usage("A", "rS", 1, 1_000), &rules);
+        record_usage(&mut book, usage("B", "rS", 1, 999 + MIN_RETENTION_SECS * 2), &rules);
+        assert_eq!(book.events.len(), 2);
+        record_usage(&mut book, usage("C", "rS", 1, 1_000 + MIN_RETENTION_SECS * 2), &rules);
+        assert_eq!(book.events.iter().map(|e| e.tx_hash.as_str()).collect::<Vec<_>>(), ["B", "C"]);
+    }
+}
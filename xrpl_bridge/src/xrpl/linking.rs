@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::RwLock;
+
+use candid::{CandidType, Principal};
+use ed25519_consensus::{Signature as Ed25519Signature, VerificationKey};
+use ic_agent::Agent;
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{Signature as Secp256k1Signature, VerifyingKey};
+use once_cell::sync::Lazy;
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::config::{get_bridge_address, BridgeConfig};
+use crate::ic_trigger::register_xrpl_account_link;
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::xrpl::address::encode_classic_address;
+use crate::xrpl::memo::generate_uuid;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::XRPLError;
+use crate::util::{from_hex, now_secs, to_hex};
+
+const LINK_BOOK_FILE: &str = "account_links.json";
+
+/// How long a user has to sign a challenge.
+const CHALLENGE_TTL_SECS: u64 = 600;
+
+const CHALLENGE_PREFIX: &str = "NAMORA-LINK";
+
+/// `account_info` flag set when the master key may no longer sign.
+const LSF_DISABLE_MASTER: u64 = 0x0010_0000;
+
+/// Ed25519 public keys on XRPL are 32 bytes behind this marker byte.
+const ED25519_KEY_PREFIX: u8 = 0xED;
+
+#[derive(Debug)]
+pub enum LinkError {
+    InvalidPrincipal(String),
+    UnknownChallenge(String),
+    ChallengeExpired(String),
+    InvalidPublicKey,
+    InvalidSignature,
+    /// The signing key is neither the account's master key nor its regular key.
+    KeyNotAuthorized(String),
+    Ledger(XRPLError),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::InvalidPrincipal(p) => write!(f, "Invalid principal: {}", p),
+            LinkError::UnknownChallenge(nonce) => write!(f, "Unknown link challenge: {}", nonce),
+            LinkError::ChallengeExpired(nonce) => write!(f, "Link challenge expired: {}", nonce),
+            LinkError::InvalidPublicKey => write!(f, "Invalid XRPL public key"),
+            LinkError::InvalidSignature => write!(f, "Signature does not match the challenge"),
+            LinkError::KeyNotAuthorized(account) => write!(f, "Key is not authorized to sign for {}", account),
+            LinkError::Ledger(e) => write!(f, "Ledger lookup failed: {}", e),
+        }
+    }
+}
+
+/// A one-time message the user signs with their XRPL key to prove control of an account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkChallenge {
+    pub nonce: String,
+    pub principal: String,
+    pub message: String,
+    /// `message` as hex, for wallets that sign raw bytes.
+    pub message_hex: String,
+    pub expires_at: u64,
+}
+
+/// A signed challenge, as submitted by the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkProof {
+    pub nonce: String,
+    pub account: String,
+    /// Hex; 33-byte compressed secp256k1 or `ED`-prefixed Ed25519 key.
+    pub public_key: String,
+    /// Hex; DER for secp256k1 (over SHA-512Half of the message), raw for Ed25519.
+    pub signature: String,
+}
+
+/// What the identity canister receives; it carries the proof so the canister can re-check it.
+#[derive(Debug, Clone, Serialize, Deserialize, CandidType)]
+pub struct XRPLAccountLinkProof {
+    pub account: String,
+    pub principal: Principal,
+    pub public_key: String,
+    pub signature: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkKey {
+    Master,
+    Regular,
+}
+
+/// A verified XRPL account ↔ principal link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountLink {
+    pub account: String,
+    pub principal: String,
+    pub public_key: String,
+    pub signature: String,
+    pub message: String,
+    pub key: LinkKey,
+    /// Whether the identity canister has accepted the link.
+    pub registered: bool,
+    pub registration_error: Option<String>,
+    pub linked_at: u64,
+    pub updated_at: u64,
+}
+
+impl AccountLink {
+    fn to_proof(&self) -> Option<XRPLAccountLinkProof> {
+        Some(XRPLAccountLinkProof {
+            account: self.account.clone(),
+            principal: Principal::from_text(&self.principal).ok()?,
+            public_key: self.public_key.clone(),
+            signature: self.signature.clone(),
+            message: self.message.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LinkBook {
+    /// Keyed by classic XRPL address.
+    links: BTreeMap<String, AccountLink>,
+    /// Outstanding challenges, keyed by nonce.
+    challenges: BTreeMap<String, LinkChallenge>,
+}
+
+static LINK_BOOK: Lazy<RwLock<LinkBook>> = Lazy::new(|| {
+    let book = load_json_state::<LinkBook>(LINK_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load account link book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+fn persist(book: &LinkBook) {
+    if let Err(e) = persist_json_state(LINK_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist account link book: {:?}", e));
+    }
+}
+
+/// Issues a challenge for `principal` to sign with the XRPL key of the account being linked.
+pub fn issue_link_challenge(principal: &str) -> Result<LinkChallenge, LinkError> {
+    Principal::from_text(principal).map_err(|_| LinkError::InvalidPrincipal(principal.to_string()))?;
+
+    let now = now_secs();
+    let nonce = generate_uuid();
+    let expires_at = now + CHALLENGE_TTL_SECS;
+    let bridge = get_bridge_address().unwrap_or_default();
+    let message = format!(
+        "{}|BRIDGE:{}|PRINCIPAL:{}|NONCE:{}|EXPIRES:{}",
+        CHALLENGE_PREFIX, bridge, principal, nonce, expires_at
+    );
+    let challenge = LinkChallenge {
+        nonce: nonce.clone(),
+        principal: principal.to_string(),
+        message_hex: to_hex(message.as_bytes()),
+        message,
+        expires_at,
+    };
+
+    let mut book = LINK_BOOK.write().unwrap();
+    book.challenges.retain(|_, c| c.expires_at > now);
+    book.challenges.insert(nonce, challenge.clone());
+    persist(&book);
+
+    Ok(challenge)
+}
+
+/// Classic address of the account whose master key is `public_key`.
+fn account_for_key(public_key: &[u8]) -> String {
+    let account_id: [u8; 20] = Ripemd160::digest(Sha256::digest(public_key)).into();
+    encode_classic_address(&account_id)
+}
+
+/// Checks `signature` over `message` the way XRPL keypairs sign, returning the key's master address.
+fn verify_signature(public_key: &str, message: &[u8], signature: &str) -> Result<String, LinkError> {
+    let key = from_hex(public_key).ok_or(LinkError::InvalidPublicKey)?;
+    let sig = from_hex(signature).ok_or(LinkError::InvalidSignature)?;
+
+    if key.len() == 33 && key[0] == ED25519_KEY_PREFIX {
+        let vk = VerificationKey::try_from(&key[1..]).map_err(|_| LinkError::InvalidPublicKey)?;
+        let sig: [u8; 64] = sig.as_slice().try_into().map_err(|_| LinkError::InvalidSignature)?;
+        vk.verify(&Ed25519Signature::from(sig), message)
+            .map_err(|_| LinkError::InvalidSignature)?;
+    } else {
+        let vk = VerifyingKey::from_sec1_bytes(&key).map_err(|_| LinkError::InvalidPublicKey)?;
+        let sig = Secp256k1Signature::from_der(&sig).map_err(|_| LinkError::InvalidSignature)?;
+        let sig = sig.normalize_s().unwrap_or(sig);
+        let digest = Sha512::digest(message);
+        vk.verify_prehash(&digest[..32], &sig)
+            .map_err(|_| LinkError::InvalidSignature)?;
+    }
+
+    Ok(account_for_key(&key))
+}
+
+/// Verifies a signed challenge and records the link. The key must currently be allowed to
+/// sign for the account: its enabled master key, or its regular key.
+pub async fn verify_link_proof(rpc: &XRPLRpcClient, proof: LinkProof) -> Result<AccountLink, LinkError> {
+    let challenge = LINK_BOOK
+        .read()
+        .unwrap()
+        .challenges
+        .get(&proof.nonce)
+        .cloned()
+        .ok_or_else(|| LinkError::UnknownChallenge(proof.nonce.clone()))?;
+    if challenge.expires_at <= now_secs() {
+        return Err(LinkError::ChallengeExpired(proof.nonce));
+    }
+
+    let signer = verify_signature(&proof.public_key, challenge.message.as_bytes(), &proof.signature)?;
+
+    let account_data = rpc.account_info(&proof.account).await.map_err(LinkError::Ledger)?;
+    let master_disabled = account_data["Flags"].as_u64().unwrap_or(0) & LSF_DISABLE_MASTER != 0;
+    let key = if signer == proof.account && !master_disabled {
+        LinkKey::Master
+    } else if account_data["RegularKey"].as_str() == Some(signer.as_str()) {
+        LinkKey::Regular
+    } else {
+        return Err(LinkError::KeyNotAuthorized(proof.account));
+    };
+
+    let now = now_secs();
+    let link = AccountLink {
+        account: proof.account.clone(),
+        principal: challenge.principal.clone(),
+        public_key: proof.public_key.to_uppercase(),
+        signature: proof.signature.to_uppercase(),
+        message: challenge.message,
+        key,
+        registered: false,
+        registration_error: None,
+        linked_at: now,
+        updated_at: now,
+    };
+
+    {
+        let mut book = LINK_BOOK.write().unwrap();
+        // One use per challenge, even if two proofs race.
+        if book.challenges.remove(&proof.nonce).is_none() {
+            return Err(LinkError::UnknownChallenge(proof.nonce));
+        }
+        if let Some(previous) = book.links.get(&link.account).filter(|l| l.principal != link.principal) {
+            bridge_log_event(
+                "link",
+                format!("🔗 {} relinked from {} to {}", link.account, previous.principal, link.principal),
+            );
+        }
+        book.links.insert(link.account.clone(), link.clone());
+        persist(&book);
+    }
+
+    bridge_log_event("link", format!("🔗 Linked {} → {} ({:?} key)", link.account, link.principal, key));
+    Ok(link)
+}
+
+/// Pushes a verified link to the identity canister. Links resolve locally either way;
+/// unregistered ones are retried by `run_link_registration`.
+pub async fn register_link(agent: &Agent, config: &BridgeConfig, account: &str) -> Option<AccountLink> {
+    let link = get_account_link(account)?;
+    let result = match link.to_proof() {
+        Some(proof) => register_xrpl_account_link(agent, config, &proof).await.map_err(|e| e.to_string()),
+        None => Err(format!("Invalid principal {}", link.principal)),
+    };
+    if let Err(e) = &result {
+        bridge_log_event("error", format!("❌ Registering link for {} failed: {}", account, e));
+    }
+
+    let mut book = LINK_BOOK.write().unwrap();
+    let stored = book.links.get_mut(account)?;
+    // A relink while we were calling out is registered on the next pass.
+    if stored.signature != link.signature {
+        return Some(stored.clone());
+    }
+    stored.registered = result.is_ok();
+    stored.registration_error = result.err();
+    stored.updated_at = now_secs();
+    let stored = stored.clone();
+    persist(&book);
+    Some(stored)
+}
+
+/// Verifies a signed challenge and registers the resulting link with the identity canister.
+pub async fn link_account(
+    agent: &Agent,
+    config: &BridgeConfig,
+    rpc: &XRPLRpcClient,
+    proof: LinkProof,
+) -> Result<AccountLink, LinkError> {
+    let link = verify_link_proof(rpc, proof).await?;
+    Ok(register_link(agent, config, &link.account).await.unwrap_or(link))
+}
+
+/// Principal a verified link ties `account` to, if any.
+pub fn linked_principal(account: &str) -> Option<Principal> {
+    let book = LINK_BOOK.read().unwrap();
+    book.links
+        .get(account)
+        .and_then(|link| Principal::from_text(&link.principal).ok())
+}
+
+pub fn get_account_link(account: &str) -> Option<AccountLink> {
+    LINK_BOOK.read().unwrap().links.get(account).cloned()
+}
+
+pub fn list_account_links() -> Vec<AccountLink> {
+    LINK_BOOK.read().unwrap().links.values().cloned().collect()
+}
+
+pub async fn run_link_registration(agent: Agent, config: BridgeConfig, interval_secs: u64) {
+    loop {
+        for link in list_account_links().into_iter().filter(|l| !l.registered) {
+            register_link(&agent, &config, &link.account).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use k256::ecdsa::SigningKey as Secp256k1SigningKey;
+
+    const MESSAGE: &[u8] = b"NAMORA-LINK test challenge";
+
+    #[test]
+    fn secp256k1_keys_map_to_their_account() {
+        // The genesis account's master key.
+        let key = from_hex("0330E7FC9D56BB25D6893BA3F317AE5BCF33B3291BD63DB32654A313222F7FD020").unwrap();
+        assert_eq!(account_for_key(&key), "rHb9CJAWyB4rj91VRWn96DkukG4bwdtyTh");
+    }
+
+    #[test]
+    fn secp256k1_proofs_verify_including_high_s() {
+        let sk = Secp256k1SigningKey::from_bytes(&[7u8; 32].into()).unwrap();
+        let public_key = to_hex(sk.verifying_key().to_encoded_point(true).as_bytes());
+        let digest = Sha512::digest(MESSAGE);
+        let sig: Secp256k1Signature = sk.sign_prehash(&digest[..32]).unwrap();
+
+        let account = verify_signature(&public_key, MESSAGE, &to_hex(sig.to_der().as_bytes())).unwrap();
+        assert_eq!(account, account_for_key(&from_hex(&public_key).unwrap()));
+
+        // XRPL accepts either S; the flipped one must verify too.
+        let (r, s) = sig.split_scalars();
+        let flipped = Secp256k1Signature::from_scalars(r, -s).unwrap();
+        assert!(verify_signature(&public_key, MESSAGE, &to_hex(flipped.to_der().as_bytes())).is_ok());
+
+        assert!(matches!(
+            verify_signature(&public_key, b"another message", &to_hex(sig.to_der().as_bytes())),
+            Err(LinkError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn ed25519_proofs_verify() {
+        let sk = ed25519_consensus::SigningKey::from([9u8; 32]);
+        let public_key = format!("ED{}", to_hex(sk.verification_key().as_bytes()));
+        let sig = to_hex(&sk.sign(MESSAGE).to_bytes());
+
+        let account = verify_signature(&public_key, MESSAGE, &sig).unwrap();
+        assert_eq!(account, account_for_key(&from_hex(&public_key).unwrap()));
+        assert!(account.starts_with('r'));
+
+        assert!(matches!(verify_signature(&public_key, b"another message", &sig), Err(LinkError::InvalidSignature)));
+        assert!(matches!(verify_signature("ED00", MESSAGE, &sig), Err(LinkError::InvalidPublicKey)));
+    }
+}
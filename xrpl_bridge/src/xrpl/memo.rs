@@ -2,9 +2,9 @@ use std::collections::HashMap;
 use candid::{Nat, Principal};
 use std::fmt;
 
-use rand::{distributions::Alphanumeric, Rng};
 
 use crate::xrpl::types::XRPLActionType;
+use crate::util::{from_hex, to_hex};
 
 #[derive(Debug, Clone)]
 pub struct ParsedMemo {
@@ -44,6 +44,7 @@ pub fn parse_memo_string(raw: &str) -> Result<ParsedMemo, MemoError> {
         "TIP" => XRPLActionType::Tip,
         "NFTSALE" => XRPLActionType::NFTSale,
         "TOKENSWAP" => XRPLActionType::TokenSwap,
+        "WRAP" => XRPLActionType::WrapXRP,
         _ => return Err(MemoError::UnknownActionType),
     };
 
@@ -64,6 +65,7 @@ pub fn validate_parsed_memo(memo: &ParsedMemo) -> Result<(), MemoError> {
         XRPLActionType::Tip => vec!["ARTIST", "UUID"],
         XRPLActionType::NFTSale => vec!["NFT", "BUYER", "UUID"],
         XRPLActionType::TokenSwap => vec!["TOKEN", "AMOUNT", "UUID"],
+        XRPLActionType::WrapXRP => vec!["PRINCIPAL", "UUID"],
     };
 
     for field in required_fields {
@@ -103,13 +105,9 @@ pub fn extract_nat_from_memo(memo: &ParsedMemo, key: &str) -> Result<Nat, MemoEr
     }
 }
 
-/// 🆔 Generates a simple random UUID (8-character alphanumeric).
+/// 🆔 Generates a random (v4) UUID.
 pub fn generate_uuid() -> String {
-    rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(8)
-        .map(char::from)
-        .collect()
+    uuid::Uuid::new_v4().to_string()
 }
 
 /// 🔍 Checks whether a memo contains a specific key.
@@ -123,6 +121,7 @@ pub fn reconstruct_memo(memo: &ParsedMemo) -> String {
         XRPLActionType::Tip => "TIP",
         XRPLActionType::NFTSale => "NFTSALE",
         XRPLActionType::TokenSwap => "TOKENSWAP",
+        XRPLActionType::WrapXRP => "WRAP",
     }.to_string()];
 
     for (k, v) in &memo.fields {
@@ -147,4 +146,15 @@ pub fn decode_memo(raw: &str) -> Result<ParsedMemo, String> {
             })
             .collect(),
     })
-}
\ No newline at end of file
+}
+
+/// 📜 Encodes text as a single-entry `Memos` array for an outbound transaction.
+pub fn memos_from_text(text: &str) -> serde_json::Value {
+    serde_json::json!([{ "Memo": { "MemoType": "746578742F706C61696E", "MemoData": to_hex(text.as_bytes()) } }])
+}
+
+/// 📜 Decodes the first hex `MemoData` of a raw XRPL transaction into text.
+pub fn memo_text_from_tx(tx: &serde_json::Value) -> Option<String> {
+    let hex = tx["Memos"].as_array()?.first()?["Memo"]["MemoData"].as_str()?;
+    String::from_utf8(from_hex(hex)?).ok()
+}
@@ -5,3 +5,26 @@ pub mod state;
 pub mod dispatcher;
 pub mod token_mirroring;
 pub mod memo;
+pub mod rpc;
+pub mod sequence;
+pub mod outbound;
+pub mod fees;
+pub mod multisig;
+pub mod ingest;
+pub mod issuer;
+pub mod swap;
+pub mod escrow;
+pub mod checks;
+pub mod refunds;
+pub mod intents;
+pub mod payment_request;
+pub mod address;
+pub mod linking;
+pub mod screening;
+pub mod limits;
+pub mod approvals;
+pub mod pause;
+pub mod params;
+pub mod reserves;
+pub mod reconciliation;
+pub mod paychan;
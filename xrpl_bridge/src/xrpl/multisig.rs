@@ -0,0 +1,617 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ed25519_consensus::{Signature, SigningKey, VerificationKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::time::{sleep, Duration};
+
+use crate::config::{
+    get_bridge_address, get_cosign_dir, get_cosign_request_key, get_cosign_request_pubkey, get_cosigner_max_drops,
+    get_cosigner_max_iou_value,     get_multisig_threshold_drops, get_signer_list, get_signer_quorum, SignerEntryConfig,
+};
+use crate::log::bridge_log_event;
+use crate::xrpl::fees::{get_fee_snapshot, open_ledger_fee, recommended_fee};
+use crate::xrpl::params::max_fee_drops;
+use crate::xrpl::pause::ensure_outbound_allowed;
+use crate::xrpl::outbound::{await_validation, classify_engine_result, submit_and_wait, SlotOutcome};
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::screening::is_deny_listed;
+use crate::xrpl::sequence::{allocate_slot, release_slot};
+use crate::xrpl::types::XRPLError;
+use crate::util::{from_hex, now_secs, to_hex};
+
+/// Co-signing is slow, so multisigned transactions get a much longer expiry window (~20 min).
+const COSIGN_LEDGER_WINDOW: u32 = 300;
+
+const REQUEST_FILE: &str = "request.json";
+const STATUS_FILE: &str = "status.json";
+
+/// Fields through which a transaction can move value out of the bridge account.
+const OUTBOUND_VALUE_FIELDS: [&str; 3] = ["Amount", "SendMax", "TakerGets"];
+
+/// Transaction types the bridge sends through `submit_transfer`; co-signers refuse anything else.
+const COSIGNABLE_TYPES: [&str; 3] = ["Payment", "OfferCreate", "NFTokenCreateOffer"];
+
+/// An unsigned transaction waiting for co-signatures, dropped as `<cosign_dir>/<request_id>/request.json`.
+/// `signature` is the bridge's Ed25519 signature over the other fields (see `request_digest`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CosignRequest {
+    pub request_id: String,
+    pub job_id: String,
+    pub tx_json: Value,
+    pub quorum: u32,
+    pub last_ledger: u32,
+    pub created_at: u64,
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// A co-signer's answer, dropped as `<request_id>/<signer_account>.json`.
+/// `tx_json` is the `sign_for` output for that signer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub account: String,
+    pub tx_json: Value,
+}
+
+/// Written to `<request_id>/status.json` so operators and signers can see where a request stands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "state")]
+pub enum CosignStatus {
+    Pending { weight: u32, quorum: u32, signers: Vec<String> },
+    Submitted { tx_hash: String },
+    Expired,
+    Failed { reason: String },
+}
+
+fn request_dir(request_id: &str) -> PathBuf {
+    Path::new(&get_cosign_dir()).join(request_id)
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), XRPLError> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn write_status(request_id: &str, status: &CosignStatus) {
+    if let Err(e) = write_json(&request_dir(request_id).join(STATUS_FILE), status) {
+        bridge_log_event("warn", format!("Could not write cosign status for {}: {}", request_id, e));
+    }
+}
+
+/// Builds a `SignerListSet` installing the configured signers and quorum on the bridge account.
+pub fn build_signer_list_set(account: &str, signers: &[SignerEntryConfig], quorum: u32) -> Value {
+    let entries: Vec<Value> = signers
+        .iter()
+        .map(|s| json!({ "SignerEntry": { "Account": s.account, "SignerWeight": s.weight } }))
+        .collect();
+
+    json!({
+        "TransactionType": "SignerListSet",
+        "Account": account,
+        "SignerQuorum": quorum,
+        "SignerEntries": entries,
+    })
+}
+
+/// Installs the configured SignerList (signed by the current single key).
+pub async fn configure_signer_list(rpc: &XRPLRpcClient) -> Result<Value, XRPLError> {
+    let account = get_bridge_address()
+        .ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))?;
+    let (signers, quorum) = signer_config()?;
+
+    let tx = build_signer_list_set(&account, &signers, quorum);
+    submit_and_wait(rpc, &format!("signer-list-{}", now_secs()), tx).await
+}
+
+fn signer_config() -> Result<(Vec<SignerEntryConfig>, u32), XRPLError> {
+    let signers = get_signer_list();
+    let quorum = get_signer_quorum()
+        .ok_or_else(|| XRPLError::Other("XRPL_SIGNER_QUORUM not set".into()))?;
+
+    let total: u32 = signers.iter().map(|s| s.weight as u32).sum();
+    if signers.is_empty() || quorum == 0 || total < quorum {
+        return Err(XRPLError::Other(format!(
+            "Signer list cannot reach quorum {} (total weight {})",
+            quorum, total
+        )));
+    }
+
+    Ok((signers, quorum))
+}
+
+/// True when an outbound transaction moves more than the multisig threshold.
+/// Issued-currency amounts always count as above it.
+pub fn requires_multisig(tx_json: &Value) -> bool {
+    match get_multisig_threshold_drops() {
+        Some(threshold) => exceeds_threshold(tx_json, threshold),
+        None => false,
+    }
+}
+
+/// Checks every value-bearing field (`Amount`, `SendMax`, `TakerGets`) against the threshold.
+fn exceeds_threshold(tx_json: &Value, threshold: u64) -> bool {
+    OUTBOUND_VALUE_FIELDS.iter().any(|field| match &tx_json[*field] {
+        Value::String(drops) => drops.parse::<u64>().map(|d| d > threshold).unwrap_or(true),
+        Value::Object(_) => true,
+        _ => false,
+    })
+}
+
+/// Multisig cost is base × (1 + signers). Co-signed fees can't be escalated, so this starts
+/// high but stays under the fee cap, and fails if even the current open-ledger cost exceeds it.
+fn multisig_fee(signer_count: usize) -> Result<u64, XRPLError> {
+    let multiplier = signer_count as u64 + 1;
+    let floor = open_ledger_fee(&get_fee_snapshot()) * multiplier;
+    cap_multisig_fee(recommended_fee(1) * multiplier, floor, max_fee_drops())
+}
+
+fn cap_multisig_fee(wanted: u64, floor: u64, cap: u64) -> Result<u64, XRPLError> {
+    if floor > cap {
+        return Err(XRPLError::Other(format!(
+            "Multisig fee of at least {} drops exceeds the {} drop cap (XRPL_MAX_FEE_DROPS)",
+            floor, cap
+        )));
+    }
+    Ok(wanted.min(cap))
+}
+
+/// SHA-256 over every request field except the signature.
+fn request_digest(request: &CosignRequest) -> Vec<u8> {
+    let body = json!({
+        "request_id": request.request_id,
+        "job_id": request.job_id,
+        "tx_json": request.tx_json,
+        "quorum": request.quorum,
+        "last_ledger": request.last_ledger,
+        "created_at": request.created_at,
+    });
+    Sha256::digest(body.to_string().as_bytes()).to_vec()
+}
+
+/// Signs a request with the bridge's request key so co-signers can tell it came from the bridge.
+fn sign_request(request: &mut CosignRequest, seed_hex: &str) -> Result<(), XRPLError> {
+    let seed: [u8; 32] = from_hex(seed_hex)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| XRPLError::Other("XRPL_COSIGN_REQUEST_KEY must be a 32-byte hex Ed25519 seed".into()))?;
+    let signature = SigningKey::from(seed).sign(&request_digest(request));
+    request.signature = Some(to_hex(&signature.to_bytes()));
+    Ok(())
+}
+
+fn verify_request(request: &CosignRequest, pubkey_hex: &str) -> Result<(), String> {
+    let key: [u8; 32] = from_hex(pubkey_hex)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("XRPL_COSIGN_REQUEST_PUBKEY must be a 32-byte hex Ed25519 key")?;
+    let signature: [u8; 64] = request
+        .signature
+        .as_deref()
+        .and_then(from_hex)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("request is not signed")?;
+
+    VerificationKey::try_from(key)
+        .map_err(|_| "invalid request public key".to_string())?
+        .verify(&Signature::from(signature), &request_digest(request))
+        .map_err(|_| "request signature does not verify".to_string())
+}
+
+/// What a co-signer is willing to sign, checked against the transaction itself rather than
+/// anything else the request claims. IOU amounts are refused outright without `max_iou`.
+fn check_policy(
+    tx_json: &Value,
+    bridge: &str,
+    current_ledger: u32,
+    max_fee: u64,
+    max_drops: Option<u64>,
+    max_iou: Option<f64>,
+) -> Result<(), String> {
+    if tx_json["Account"].as_str() != Some(bridge) {
+        return Err("not sent from the bridge account".into());
+    }
+
+    let kind = tx_json["TransactionType"].as_str().unwrap_or_default();
+    if !COSIGNABLE_TYPES.contains(&kind) {
+        return Err(format!("{} is not a co-signable transaction type", kind));
+    }
+    if tx_json["Destination"].as_str() == Some(bridge) {
+        return Err("destination is the bridge itself".into());
+    }
+
+    let fee = tx_json["Fee"].as_str().and_then(|f| f.parse::<u64>().ok()).ok_or("missing Fee")?;
+    if fee > max_fee {
+        return Err(format!("fee {} exceeds the {} drop cap", fee, max_fee));
+    }
+
+    let last_ledger = tx_json["LastLedgerSequence"].as_u64().ok_or("missing LastLedgerSequence")? as u32;
+    if last_ledger <= current_ledger || last_ledger > current_ledger + COSIGN_LEDGER_WINDOW {
+        return Err(format!("LastLedgerSequence {} outside the co-signing window", last_ledger));
+    }
+
+    for field in OUTBOUND_VALUE_FIELDS {
+        match &tx_json[field] {
+            Value::String(drops) => {
+                if let Some(max) = max_drops {
+                    match drops.parse::<u64>() {
+                        Ok(d) if d <= max => {}
+                        _ => return Err(format!("{} of {} drops exceeds the {} drop cap", field, drops, max)),
+                    }
+                }
+            }
+            Value::Object(iou) => {
+                let max = max_iou.ok_or_else(|| format!("{} is an IOU amount and no IOU cap is set", field))?;
+                let value = iou["value"].as_str().unwrap_or_default();
+                match value.parse::<f64>() {
+                    Ok(v) if v.is_finite() && v <= max => {}
+                    _ => return Err(format!("{} of {} IOU exceeds the {} cap", field, value, max)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Everything a co-signer checks before signing: the bridge's signature on the request,
+/// its own policy, and the screening deny list.
+fn validate_request(request: &CosignRequest, current_ledger: u32) -> Result<(), String> {
+    let pubkey = get_cosign_request_pubkey().ok_or("XRPL_COSIGN_REQUEST_PUBKEY not set")?;
+    verify_request(request, &pubkey)?;
+
+    let bridge = get_bridge_address().ok_or("XRPL_BRIDGE_ADDRESS not set")?;
+    check_policy(
+        &request.tx_json,
+        &bridge,
+        current_ledger,
+        max_fee_drops(),
+        get_cosigner_max_drops(),
+        get_cosigner_max_iou_value(),
+    )?;
+
+    match request.tx_json["Destination"].as_str() {
+        Some(destination) if is_deny_listed(destination) => Err(format!("destination {} is deny-listed", destination)),
+        _ => Ok(()),
+    }
+}
+
+/// Routes an outbound transfer: large ones go through co-signing, the rest are single-signed.
+pub async fn submit_transfer(rpc: &XRPLRpcClient, job_id: &str, tx_json: Value) -> Result<Value, XRPLError> {
+    if requires_multisig(&tx_json) {
+        submit_multisigned_and_wait(rpc, job_id, tx_json).await
+    } else {
+        submit_and_wait(rpc, job_id, tx_json).await
+    }
+}
+
+/// Builds the unsigned transaction, collects co-signatures through the drop directory
+/// until the quorum is met, then submits and waits for validation.
+pub async fn submit_multisigned_and_wait(
+    rpc: &XRPLRpcClient,
+    job_id: &str,
+    mut tx_json: Value,
+) -> Result<Value, XRPLError> {
+    ensure_outbound_allowed(job_id)?;
+    let (signers, quorum) = signer_config()?;
+    let account = get_bridge_address()
+        .ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))?;
+
+    let request_key = get_cosign_request_key()
+        .ok_or_else(|| XRPLError::Other("XRPL_COSIGN_REQUEST_KEY not set".into()))?;
+    let fee = multisig_fee(signers.len())?;
+
+    let slot = allocate_slot(job_id)?;
+
+    let prepared = async {
+        let last_ledger = rpc.current_ledger_index().await? + COSIGN_LEDGER_WINDOW;
+
+        tx_json["Account"] = json!(account);
+        tx_json["Fee"] = json!(fee.to_string());
+        tx_json["LastLedgerSequence"] = json!(last_ledger);
+        tx_json["SigningPubKey"] = json!("");
+        slot.apply_to(&mut tx_json);
+
+        let mut request = CosignRequest {
+            request_id: format!("{}-{}", job_id, now_secs()),
+            job_id: job_id.to_string(),
+            tx_json: tx_json.clone(),
+            quorum,
+            last_ledger,
+            created_at: now_secs(),
+            signature: None,
+        };
+        sign_request(&mut request, &request_key)?;
+
+        fs::create_dir_all(request_dir(&request.request_id))?;
+        write_json(&request_dir(&request.request_id).join(REQUEST_FILE), &request)?;
+        Ok::<_, XRPLError>(request)
+    }
+    .await;
+
+    let request = match prepared {
+        Ok(r) => r,
+        Err(e) => {
+            release_slot(job_id, false);
+            return Err(e);
+        }
+    };
+
+    bridge_log_event(
+        "multisig",
+        format!("✍️ Waiting for {} signer weight on {} ({:?})", quorum, request.request_id, slot),
+    );
+
+    let signer_array = loop {
+        let collected = collect_signatures(&request, &signers);
+        let weight: u32 = collected.keys().filter_map(|a| signer_weight(&signers, a)).sum();
+
+        if weight >= quorum {
+            break collected.into_values().collect::<Vec<Value>>();
+        }
+
+        write_status(
+            &request.request_id,
+            &CosignStatus::Pending { weight, quorum, signers: collected.keys().cloned().collect() },
+        );
+
+        if let Ok(current) = rpc.current_ledger_index().await {
+            if current > request.last_ledger {
+                release_slot(job_id, false);
+                write_status(&request.request_id, &CosignStatus::Expired);
+                return Err(XRPLError::TransactionTimeout(format!(
+                    "{}: quorum not reached before ledger {}",
+                    request.request_id, request.last_ledger
+                )));
+            }
+        }
+
+        sleep(Duration::from_secs(3)).await;
+    };
+
+    let mut combined = request.tx_json.clone();
+    combined["Signers"] = Value::Array(signer_array);
+
+    let result = match rpc.submit_multisigned(&combined).await {
+        Ok(r) => r,
+        Err(e) => {
+            write_status(&request.request_id, &CosignStatus::Failed { reason: e.to_string() });
+            return Err(e);
+        }
+    };
+
+    let engine_result = result["engine_result"].as_str().unwrap_or("unknown");
+    let tx_hash = result["tx_json"]["hash"].as_str().unwrap_or_default().to_string();
+
+    match classify_engine_result(engine_result) {
+        SlotOutcome::InFlight => {
+            bridge_log_event("multisig", format!("📤 {} submitted as {}: {}", job_id, tx_hash, engine_result));
+            write_status(&request.request_id, &CosignStatus::Submitted { tx_hash: tx_hash.clone() });
+            await_validation(rpc, job_id, &tx_hash, request.last_ledger).await
+        }
+        outcome => {
+            release_slot(job_id, outcome == SlotOutcome::Consumed);
+            write_status(&request.request_id, &CosignStatus::Failed { reason: engine_result.to_string() });
+            Err(XRPLError::TransactionRejected(format!("{}: {}", job_id, engine_result)))
+        }
+    }
+}
+
+fn signer_weight(signers: &[SignerEntryConfig], account: &str) -> Option<u32> {
+    signers.iter().find(|s| s.account == account).map(|s| s.weight as u32)
+}
+
+/// Reads dropped partial signatures, keeping only those from listed signers
+/// over exactly the requested transaction. Returns account → `Signer` object.
+fn collect_signatures(request: &CosignRequest, signers: &[SignerEntryConfig]) -> BTreeMap<String, Value> {
+    let mut collected = BTreeMap::new();
+
+    let Ok(entries) = fs::read_dir(request_dir(&request.request_id)) else {
+        return collected;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == REQUEST_FILE || name == STATUS_FILE || !name.ends_with(".json") {
+            continue;
+        }
+
+        let partial: PartialSignature = match fs::read(entry.path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        {
+            Some(p) => p,
+            None => continue,
+        };
+
+        if signer_weight(signers, &partial.account).is_none() {
+            bridge_log_event("warn", format!("Ignoring signature from unlisted signer {}", partial.account));
+            continue;
+        }
+
+        if !signs_same_transaction(&request.tx_json, &partial.tx_json) {
+            bridge_log_event("warn", format!("Signature from {} covers a different transaction", partial.account));
+            continue;
+        }
+
+        let signer = partial.tx_json["Signers"]
+            .as_array()
+            .and_then(|list| list.iter().find(|s| s["Signer"]["Account"] == partial.account.as_str()))
+            .cloned();
+
+        if let Some(signer) = signer {
+            collected.insert(partial.account, signer);
+        }
+    }
+
+    collected
+}
+
+/// Every field of the requested transaction must be present and unchanged in the signed copy.
+fn signs_same_transaction(requested: &Value, signed: &Value) -> bool {
+    match requested.as_object() {
+        Some(fields) => fields.iter().all(|(k, v)| &signed[k] == v),
+        None => false,
+    }
+}
+
+/// Co-signer side of the protocol: signs every open request in the drop directory that
+/// this signer hasn't answered yet and that passes `validate_request`. Refusals are written
+/// to `<request_id>/<signer_account>.rejected`. Returns how many requests were signed.
+pub async fn cosign_pending_requests(
+    rpc: &XRPLRpcClient,
+    signer_account: &str,
+    secret: &str,
+) -> Result<usize, XRPLError> {
+    let mut signed = 0;
+    let Ok(entries) = fs::read_dir(get_cosign_dir()) else {
+        return Ok(0);
+    };
+    let current_ledger = rpc.current_ledger_index().await?;
+
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let answer = dir.join(format!("{}.json", signer_account));
+        let rejection = dir.join(format!("{}.rejected", signer_account));
+        if answer.exists() || rejection.exists() || !is_still_pending(&dir) {
+            continue;
+        }
+
+        let Some(request) = fs::read(dir.join(REQUEST_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CosignRequest>(&bytes).ok())
+        else {
+            continue;
+        };
+
+        if let Err(reason) = validate_request(&request, current_ledger) {
+            bridge_log_event("warn", format!("🛑 {} refused {}: {}", signer_account, request.request_id, reason));
+            fs::write(&rejection, reason)?;
+            continue;
+        }
+
+        let result = rpc.sign_for(&request.tx_json, signer_account, secret).await?;
+        write_json(
+            &answer,
+            &PartialSignature { account: signer_account.to_string(), tx_json: result.tx_json },
+        )?;
+
+        bridge_log_event("multisig", format!("🖊️ {} co-signed {}", signer_account, request.request_id));
+        signed += 1;
+    }
+
+    Ok(signed)
+}
+
+fn is_still_pending(dir: &Path) -> bool {
+    fs::read(dir.join(STATUS_FILE))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<CosignStatus>(&bytes).ok())
+        .map(|status| matches!(status, CosignStatus::Pending { .. }))
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRIDGE: &str = "rBridgeAccount";
+
+    fn payment(amount: Value) -> Value {
+        json!({
+            "TransactionType": "Payment",
+            "Account": BRIDGE,
+            "Destination": "rUser",
+            "Amount": amount,
+            "Fee": "60",
+            "LastLedgerSequence": 1_100,
+        })
+    }
+
+    #[test]
+    fn threshold_checks_every_value_field() {
+        assert!(!exceeds_threshold(&payment(json!("1000")), 5_000));
+        assert!(exceeds_threshold(&payment(json!("6000")), 5_000));
+        assert!(exceeds_threshold(&payment(json!({ "currency": "AXA", "issuer": BRIDGE, "value": "1" })), 5_000));
+
+        let offer = json!({ "TransactionType": "OfferCreate", "TakerGets": "9000", "TakerPays": "1" });
+        assert!(exceeds_threshold(&offer, 5_000));
+
+        let mut cross = payment(json!({ "currency": "USD", "issuer": "rIssuer", "value": "1" }));
+        cross["SendMax"] = json!("9000");
+        assert!(exceeds_threshold(&cross, 5_000));
+    }
+
+    #[test]
+    fn multisig_fee_is_capped_or_refused() {
+        assert_eq!(cap_multisig_fee(120, 40, 1_000).unwrap(), 120);
+        assert_eq!(cap_multisig_fee(3_000, 400, 1_000).unwrap(), 1_000);
+        assert!(cap_multisig_fee(3_000, 2_000, 1_000).is_err());
+    }
+
+    fn request(seed: [u8; 32]) -> CosignRequest {
+        let mut request = CosignRequest {
+            request_id: "refund-abc-1".into(),
+            job_id: "refund-abc".into(),
+            tx_json: payment(json!("6000")),
+            quorum: 2,
+            last_ledger: 1_100,
+            created_at: 1,
+            signature: None,
+        };
+        sign_request(&mut request, &to_hex(&seed)).unwrap();
+        request
+    }
+
+    #[test]
+    fn requests_are_authenticated() {
+        let seed = [7u8; 32];
+        let pubkey = to_hex(&VerificationKey::from(&SigningKey::from(seed)).to_bytes());
+
+        let signed = request(seed);
+        assert!(verify_request(&signed, &pubkey).is_ok());
+
+        let mut tampered = signed.clone();
+        tampered.tx_json["Destination"] = json!("rAttacker");
+        assert!(verify_request(&tampered, &pubkey).is_err());
+
+        let mut unsigned = signed;
+        unsigned.signature = None;
+        assert!(verify_request(&unsigned, &pubkey).is_err());
+
+        let other = to_hex(&VerificationKey::from(&SigningKey::from([8u8; 32])).to_bytes());
+        assert!(verify_request(&request(seed), &other).is_err());
+    }
+
+    #[test]
+    fn policy_rejects_unexpected_transactions() {
+        let tx = payment(json!("6000"));
+        assert!(check_policy(&tx, BRIDGE, 1_000, 1_000, None, None).is_ok());
+        assert!(check_policy(&tx, BRIDGE, 1_000, 1_000, Some(5_000), None).is_err());
+        assert!(check_policy(&tx, BRIDGE, 1_000, 50, None, None).is_err());
+        assert!(check_policy(&tx, BRIDGE, 1_100, 1_000, None, None).is_err());
+        assert!(check_policy(&tx, "rSomeoneElse", 1_000, 1_000, None, None).is_err());
+
+        let mut rekey = tx.clone();
+        rekey["TransactionType"] = json!("SetRegularKey");
+        assert!(check_policy(&rekey, BRIDGE, 1_000, 1_000, None, None).is_err());
+
+        let mut to_self = tx;
+        to_self["Destination"] = json!(BRIDGE);
+        assert!(check_policy(&to_self, BRIDGE, 1_000, 1_000, None, None).is_err());
+
+        let iou = payment(json!({ "currency": "AXA", "issuer": BRIDGE, "value": "250" }));
+        assert!(check_policy(&iou, BRIDGE, 1_000, 1_000, None, None).is_err());
+        assert!(check_policy(&iou, BRIDGE, 1_000, 1_000, None, Some(100.0)).is_err());
+        assert!(check_policy(&iou, BRIDGE, 1_000, 1_000, None, Some(1_000.0)).is_ok());
+
+        let mut offer = payment(json!("1"));
+        offer["TransactionType"] = json!("OfferCreate");
+        offer["TakerGets"] = json!({ "currency": "AXA", "issuer": BRIDGE, "value": "NaN" });
+        assert!(check_policy(&offer, BRIDGE, 1_000, 1_000, None, Some(1_000.0)).is_err());
+    }
+}
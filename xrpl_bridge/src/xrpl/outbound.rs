@@ -0,0 +1,349 @@
+use serde_json::{json, Value};
+use tokio::time::{sleep, Duration};
+
+use crate::config::{get_bridge_address, get_bridge_secret};
+use crate::log::bridge_log_event;
+use crate::xrpl::fees::{record_fee_paid, recommended_fee};
+use crate::xrpl::pause::ensure_outbound_allowed;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::sequence::{allocate_sequence, allocate_slot, release_slot, SequenceSlot};
+use crate::xrpl::types::{XRPLError, XRPLSubmitResult};
+
+/// Ledgers an outbound transaction may wait before it expires.
+const LEDGER_WINDOW: u32 = 20;
+
+/// Ledgers to wait for inclusion before resubmitting with a higher fee.
+const ESCALATE_AFTER_LEDGERS: u32 = 3;
+
+/// Maximum number of fee bumps for one job.
+const MAX_FEE_ESCALATIONS: u32 = 4;
+
+/// How the submit step left the sequencing slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotOutcome {
+    /// Provisionally applied or queued; wait for validation.
+    InFlight,
+    /// Applied on-ledger (success or `tec`); the slot is spent.
+    Consumed,
+    /// Never applied; the slot can be handed out again.
+    Unused,
+}
+
+/// Maps a preliminary `engine_result` to what it means for the sequencing slot.
+/// Only `tem`/`tef` results are final refusals; `ter`/`tel` results (and anything
+/// unrecognised) may still have been relayed and can validate later.
+pub fn classify_engine_result(engine_result: &str) -> SlotOutcome {
+    match engine_result {
+        // Sequence / ticket already used by something else.
+        "tefPAST_SEQ" | "tefNO_TICKET" => SlotOutcome::Consumed,
+        r if r.starts_with("tec") => SlotOutcome::Consumed,
+        r if r.starts_with("tem") || r.starts_with("tef") => SlotOutcome::Unused,
+        _ => SlotOutcome::InFlight,
+    }
+}
+
+/// One signed-and-submitted version of a job's transaction.
+#[derive(Debug, Clone)]
+struct Attempt {
+    number: u32,
+    tx_hash: String,
+    submitted_at_ledger: u32,
+    last_ledger: u32,
+}
+
+enum WatchResult {
+    Validated(Value, u32),
+    StillPending,
+    Expired,
+}
+
+/// Submits an outbound transaction from the bridge wallet using a ticket when available.
+/// Single attempt: the job keeps its slot until `await_validation` settles it.
+pub async fn submit_outbound(
+    rpc: &XRPLRpcClient,
+    job_id: &str,
+    tx_json: Value,
+) -> Result<XRPLSubmitResult, XRPLError> {
+    ensure_outbound_allowed(job_id)?;
+    let slot = allocate_slot(job_id)?;
+
+    match sign_and_submit(rpc, job_id, slot, tx_json, 0).await {
+        Ok((submitted, SlotOutcome::InFlight)) => Ok(submitted),
+        Ok((submitted, outcome)) => {
+            release_slot(job_id, outcome == SlotOutcome::Consumed);
+            Err(XRPLError::TransactionRejected(format!("{}: {}", job_id, submitted.status)))
+        }
+        // Nothing was signed or sent, so the slot is untouched.
+        Err(e) => {
+            release_slot(job_id, false);
+            Err(e)
+        }
+    }
+}
+
+/// Submits with a ticket/sequence and drives the job to a final result,
+/// resubmitting on the same slot with a higher fee while it is stuck.
+pub async fn submit_and_wait(
+    rpc: &XRPLRpcClient,
+    job_id: &str,
+    tx_json: Value,
+) -> Result<Value, XRPLError> {
+    ensure_outbound_allowed(job_id)?;
+    allocate_slot(job_id)?;
+    drive_to_validation(rpc, job_id, tx_json).await
+}
+
+/// Same as `submit_and_wait` but on a plain sequence (used for `TicketCreate`).
+pub async fn submit_with_sequence(
+    rpc: &XRPLRpcClient,
+    job_id: &str,
+    tx_json: Value,
+) -> Result<Value, XRPLError> {
+    ensure_outbound_allowed(job_id)?;
+    allocate_sequence(job_id)?;
+    drive_to_validation(rpc, job_id, tx_json).await
+}
+
+async fn drive_to_validation(
+    rpc: &XRPLRpcClient,
+    job_id: &str,
+    tx_json: Value,
+) -> Result<Value, XRPLError> {
+    let slot = crate::xrpl::sequence::leased_slot(job_id)
+        .ok_or_else(|| XRPLError::TransactionInvalidSequence(format!("{} has no slot", job_id)))?;
+
+    let mut attempts: Vec<Attempt> = Vec::new();
+    let mut attempt_no = 0;
+
+    loop {
+        match sign_and_submit(rpc, job_id, slot, tx_json.clone(), attempt_no).await {
+            Ok((submitted, SlotOutcome::InFlight)) => {
+                let last_ledger = submitted.ledger_index as u32;
+                attempts.push(Attempt {
+                    number: attempt_no,
+                    tx_hash: submitted.tx_hash,
+                    submitted_at_ledger: last_ledger.saturating_sub(LEDGER_WINDOW),
+                    last_ledger,
+                });
+            }
+            Ok((submitted, outcome)) if attempts.is_empty() => {
+                release_slot(job_id, outcome == SlotOutcome::Consumed);
+                return Err(XRPLError::TransactionRejected(format!("{}: {}", job_id, submitted.status)));
+            }
+            // A replacement was refused (often because an earlier attempt already got in):
+            // keep watching the attempts we have.
+            Ok((submitted, _)) => {
+                bridge_log_event("outbound", format!("Replacement for {} refused: {}", job_id, submitted.status));
+            }
+            // Failed before anything was sent, so the slot is untouched.
+            Err(e) if attempts.is_empty() => {
+                release_slot(job_id, false);
+                return Err(e);
+            }
+            Err(e) => bridge_log_event("warn", format!("Escalation of {} failed: {}", job_id, e)),
+        }
+
+        let can_escalate = attempt_no < MAX_FEE_ESCALATIONS;
+        match watch_attempts(rpc, &attempts, can_escalate).await {
+            WatchResult::Validated(tx, number) => {
+                release_slot(job_id, true);
+                record_fee_paid(job_id, &tx, number);
+                return validated_result(job_id, tx);
+            }
+            WatchResult::Expired => {
+                release_slot(job_id, false);
+                let last = attempts.last().map(|a| a.tx_hash.clone()).unwrap_or_default();
+                return Err(XRPLError::TransactionTimeout(format!("{} ({}) never validated", job_id, last)));
+            }
+            WatchResult::StillPending => {
+                attempt_no += 1;
+                bridge_log_event(
+                    "outbound",
+                    format!("⏫ {} not included yet, resubmitting at {} drops", job_id, recommended_fee(attempt_no)),
+                );
+            }
+        }
+    }
+}
+
+/// Polls every attempt until one validates, all expire, or (if allowed) it is time to escalate.
+async fn watch_attempts(rpc: &XRPLRpcClient, attempts: &[Attempt], can_escalate: bool) -> WatchResult {
+    let Some(latest) = attempts.last() else {
+        return WatchResult::Expired;
+    };
+    let final_ledger = attempts.iter().map(|a| a.last_ledger).max().unwrap_or(0);
+
+    loop {
+        for attempt in attempts.iter().rev() {
+            match rpc.tx(&attempt.tx_hash).await {
+                Ok(tx) if tx["validated"] == true => return WatchResult::Validated(tx, attempt.number),
+                Ok(_) | Err(XRPLError::TransactionNotFound(_)) => {}
+                Err(e) => bridge_log_event("warn", format!("Validation poll for {} failed: {}", attempt.tx_hash, e)),
+            }
+        }
+
+        // Only a validated ledger past every LastLedgerSequence proves no attempt can still get in.
+        if let Ok(validated) = rpc.validated_ledger_index().await {
+            if validated > final_ledger && !any_validated(rpc, attempts).await {
+                return WatchResult::Expired;
+            }
+        }
+        if let Ok(current) = rpc.current_ledger_index().await {
+            if can_escalate && current >= latest.submitted_at_ledger + ESCALATE_AFTER_LEDGERS {
+                return WatchResult::StillPending;
+            }
+        }
+
+        sleep(Duration::from_secs(4)).await;
+    }
+}
+
+/// Final re-check after expiry so an attempt validated between polls is not missed.
+async fn any_validated(rpc: &XRPLRpcClient, attempts: &[Attempt]) -> bool {
+    for attempt in attempts {
+        if let Ok(tx) = rpc.tx(&attempt.tx_hash).await {
+            if tx["validated"] == true {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn validated_result(job_id: &str, tx: Value) -> Result<Value, XRPLError> {
+    let hash = tx["hash"].as_str().unwrap_or_default();
+    let code = tx["meta"]["TransactionResult"].as_str().unwrap_or("unknown");
+    if code == "tesSUCCESS" {
+        bridge_log_event("outbound", format!("✅ {} validated: {}", job_id, hash));
+        Ok(tx)
+    } else {
+        Err(XRPLError::TransactionRejected(format!("{} validated with {}", hash, code)))
+    }
+}
+
+/// Fills common fields, prices the fee for this attempt, signs with the bridge secret and submits.
+/// `XRPLSubmitResult::ledger_index` carries the transaction's `LastLedgerSequence`;
+/// `status` carries the preliminary `engine_result`.
+async fn sign_and_submit(
+    rpc: &XRPLRpcClient,
+    job_id: &str,
+    slot: SequenceSlot,
+    mut tx_json: Value,
+    attempt: u32,
+) -> Result<(XRPLSubmitResult, SlotOutcome), XRPLError> {
+    let account = get_bridge_address()
+        .ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))?;
+    let secret = get_bridge_secret()
+        .ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_SECRET not set".into()))?;
+
+    let last_ledger = rpc.current_ledger_index().await? + LEDGER_WINDOW;
+
+    // A caller-supplied Fee acts as a floor.
+    let floor = tx_json["Fee"].as_str().and_then(|f| f.parse::<u64>().ok()).unwrap_or(0);
+    let fee = recommended_fee(attempt).max(floor);
+
+    tx_json["Account"] = json!(account);
+    tx_json["Fee"] = json!(fee.to_string());
+    tx_json["LastLedgerSequence"] = json!(last_ledger);
+    slot.apply_to(&mut tx_json);
+
+    let signed = rpc.sign(&tx_json, &secret).await?;
+
+    // Once a blob has been handed to the node it may have been relayed even if we never
+    // saw the response, so a transport failure here leaves the attempt in flight.
+    let (engine_result, outcome) = match rpc.submit_blob(&signed.tx_blob).await {
+        Ok(result) => {
+            let engine_result = result["engine_result"].as_str().unwrap_or("unknown").to_string();
+            let outcome = classify_engine_result(&engine_result);
+            (engine_result, outcome)
+        }
+        Err(e) => {
+            bridge_log_event(
+                "warn",
+                format!("Submit of {} ({}) failed, watching until ledger {}: {}", job_id, signed.hash, last_ledger, e),
+            );
+            ("unknown".to_string(), SlotOutcome::InFlight)
+        }
+    };
+
+    bridge_log_event(
+        "outbound",
+        format!(
+            "📤 {} attempt {} as {} ({:?}, fee {}): {}",
+            job_id, attempt, signed.hash, slot, fee, engine_result
+        ),
+    );
+
+    Ok((
+        XRPLSubmitResult {
+            tx_hash: signed.hash,
+            status: engine_result,
+            ledger_index: last_ledger as u64,
+        },
+        outcome,
+    ))
+}
+
+/// Polls until a single submitted transaction validates or its `LastLedgerSequence` passes.
+/// Settles the job's slot either way and returns the validated transaction.
+pub async fn await_validation(
+    rpc: &XRPLRpcClient,
+    job_id: &str,
+    tx_hash: &str,
+    last_ledger: u32,
+) -> Result<Value, XRPLError> {
+    let attempt = Attempt {
+        number: 0,
+        tx_hash: tx_hash.to_string(),
+        submitted_at_ledger: last_ledger.saturating_sub(LEDGER_WINDOW),
+        last_ledger,
+    };
+
+    match watch_attempts(rpc, &[attempt], false).await {
+        WatchResult::Validated(tx, number) => {
+            release_slot(job_id, true);
+            record_fee_paid(job_id, &tx, number);
+            validated_result(job_id, tx)
+        }
+        _ => {
+            release_slot(job_id, false);
+            Err(XRPLError::TransactionTimeout(format!(
+                "{} not validated by ledger {}",
+                tx_hash, last_ledger
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn success_and_queued_stay_in_flight() {
+        assert_eq!(classify_engine_result("tesSUCCESS"), SlotOutcome::InFlight);
+        assert_eq!(classify_engine_result("terQUEUED"), SlotOutcome::InFlight);
+    }
+
+    #[test]
+    fn retry_and_local_results_may_still_be_pending() {
+        assert_eq!(classify_engine_result("terPRE_SEQ"), SlotOutcome::InFlight);
+        assert_eq!(classify_engine_result("terINSUF_FEE_B"), SlotOutcome::InFlight);
+        assert_eq!(classify_engine_result("telINSUF_FEE_P"), SlotOutcome::InFlight);
+        assert_eq!(classify_engine_result("telCAN_NOT_QUEUE"), SlotOutcome::InFlight);
+        assert_eq!(classify_engine_result("unknown"), SlotOutcome::InFlight);
+    }
+
+    #[test]
+    fn claimed_results_consume_the_slot() {
+        assert_eq!(classify_engine_result("tecUNFUNDED_PAYMENT"), SlotOutcome::Consumed);
+        assert_eq!(classify_engine_result("tefPAST_SEQ"), SlotOutcome::Consumed);
+        assert_eq!(classify_engine_result("tefNO_TICKET"), SlotOutcome::Consumed);
+    }
+
+    #[test]
+    fn malformed_and_failed_results_free_the_slot() {
+        assert_eq!(classify_engine_result("temBAD_AMOUNT"), SlotOutcome::Unused);
+        assert_eq!(classify_engine_result("tefMAX_LEDGER"), SlotOutcome::Unused);
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::RwLock;
+
+use candid::{CandidType, Nat};
+use ic_agent::Agent;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    get_approval_threshold_drops, get_max_fee_drops, get_minimum_tip_drops, get_params_key, get_params_method,
+    get_params_module, get_refund_fee_drops, BridgeConfig,
+};
+use crate::ic_trigger::fetch_governed_config;
+use crate::log::bridge_log_event;
+use crate::state::db::{append_jsonl_record, load_json_state, persist_json_state};
+use crate::xrpl::limits::{list_limit_rules, validate_limit_rules, LimitRule};
+use crate::util::now_secs;
+
+const PARAMS_STATE_FILE: &str = "params.json";
+const PARAMS_HISTORY_FILE: &str = "params_history.jsonl";
+
+/// Schema the governance canister must tag the parameter blob with.
+pub const PARAMS_SCHEMA_ID: &str = "xrpl_bridge.parameters.v1";
+
+// Local hard bounds: a governed set outside these is refused whatever its version.
+const MIN_TIP_DROPS_RANGE: (u64, u64) = (1, 100_000_000);
+const MAX_FEE_DROPS_RANGE: (u64, u64) = (10, 1_000_000);
+const MAX_REFUND_FEE_DROPS: u64 = 1_000_000;
+const APPROVAL_THRESHOLD_DROPS_RANGE: (u64, u64) = (1_000_000, 100_000_000_000);
+const MAX_LIMIT_WINDOW_SECS: u64 = 30 * 86_400;
+
+/// Subset of admin2's `Config` record (the shape `getConfig` returns).
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct GovernedConfig {
+    /// JSON-encoded `BridgeParameters`.
+    pub value: Vec<u8>,
+    #[serde(rename = "schemaId")]
+    pub schema_id: String,
+    pub version: Nat,
+}
+
+/// Bridge parameters governed on ICP. Until a set is applied, the env vars they replace are used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeParameters {
+    pub min_tip_drops: u64,
+    pub max_fee_drops: u64,
+    pub refund_fee_drops: u64,
+    /// Absent keeps the local `APPROVAL_THRESHOLD_DROPS`; a set can't turn the threshold off.
+    #[serde(default)]
+    pub approval_threshold_drops: Option<u64>,
+    /// Replace the rules from `LIMIT_RULES_FILE`; absent keeps them.
+    #[serde(default)]
+    pub limit_rules: Option<Vec<LimitRule>>,
+    /// `XRP` and the issued-currency codes swaps and intents may use.
+    pub allowed_currencies: Vec<String>,
+}
+
+/// A validated parameter set as applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSet {
+    pub version: u64,
+    pub params: BridgeParameters,
+    pub applied_at: u64,
+}
+
+/// Active parameters and how the last sync went, for the admin API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterStatus {
+    /// `None` while running on env defaults.
+    pub active: Option<ParameterSet>,
+    pub effective: BridgeParameters,
+    pub last_synced_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ParameterState {
+    /// Last-known-good set; kept across restarts and used while the canister is unreachable.
+    active: Option<ParameterSet>,
+    last_synced_at: Option<u64>,
+    last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ParameterChange {
+    from_version: Option<u64>,
+    to_version: u64,
+    params: BridgeParameters,
+    at: u64,
+}
+
+static PARAM_STATE: Lazy<RwLock<ParameterState>> = Lazy::new(|| {
+    let state = load_json_state::<ParameterState>(PARAMS_STATE_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load governed parameters: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(state)
+});
+
+fn persist(state: &ParameterState) {
+    if let Err(e) = persist_json_state(PARAMS_STATE_FILE, state) {
+        bridge_log_event("error", format!("Failed to persist governed parameters: {:?}", e));
+    }
+}
+
+fn active_params() -> Option<BridgeParameters> {
+    PARAM_STATE.read().unwrap().active.as_ref().map(|set| set.params.clone())
+}
+
+pub fn min_tip_drops() -> u64 {
+    active_params().map_or_else(get_minimum_tip_drops, |p| p.min_tip_drops)
+}
+
+pub fn max_fee_drops() -> u64 {
+    active_params().map_or_else(get_max_fee_drops, |p| p.max_fee_drops)
+}
+
+pub fn refund_fee_drops() -> u64 {
+    active_params().map_or_else(get_refund_fee_drops, |p| p.refund_fee_drops)
+}
+
+pub fn approval_threshold_drops() -> Option<u64> {
+    active_params()
+        .and_then(|p| p.approval_threshold_drops)
+        .or_else(get_approval_threshold_drops)
+}
+
+/// Governed limit rules, if the active parameter set carries any.
+pub fn governed_limit_rules() -> Option<Vec<LimitRule>> {
+    active_params().and_then(|p| p.limit_rules)
+}
+
+/// Whether swaps and intents may use `currency`; anything goes until a set is active.
+pub fn currency_allowed(currency: &str) -> bool {
+    active_params().is_none_or(|p| p.allowed_currencies.iter().any(|c| c == currency))
+}
+
+/// The parameters in force: the active set (with local values where it leaves some out),
+/// or the env defaults.
+fn effective_params() -> BridgeParameters {
+    let params = active_params().unwrap_or_else(|| BridgeParameters {
+        min_tip_drops: get_minimum_tip_drops(),
+        max_fee_drops: get_max_fee_drops(),
+        refund_fee_drops: get_refund_fee_drops(),
+        approval_threshold_drops: None,
+        limit_rules: None,
+        allowed_currencies: Vec::new(),
+    });
+    BridgeParameters {
+        approval_threshold_drops: approval_threshold_drops(),
+        limit_rules: Some(list_limit_rules()),
+        ..params
+    }
+}
+
+pub fn parameter_status() -> ParameterStatus {
+    let state = PARAM_STATE.read().unwrap().clone();
+    ParameterStatus {
+        active: state.active,
+        effective: effective_params(),
+        last_synced_at: state.last_synced_at,
+        last_error: state.last_error,
+    }
+}
+
+fn in_range(name: &str, value: u64, (min, max): (u64, u64)) -> Result<(), String> {
+    if value < min || value > max {
+        return Err(format!("{} {} outside {}..={}", name, value, min, max));
+    }
+    Ok(())
+}
+
+/// `XRP`, standard three-character codes, or 40-hex non-standard codes.
+fn valid_currency_code(code: &str) -> bool {
+    match code.len() {
+        3 => code.chars().all(|c| c.is_ascii_alphanumeric()),
+        40 => code.chars().all(|c| c.is_ascii_hexdigit()),
+        _ => false,
+    }
+}
+
+/// Checks a governed set against the local hard bounds.
+pub fn validate_parameters(params: &BridgeParameters) -> Result<(), String> {
+    in_range("min_tip_drops", params.min_tip_drops, MIN_TIP_DROPS_RANGE)?;
+    in_range("max_fee_drops", params.max_fee_drops, MAX_FEE_DROPS_RANGE)?;
+    in_range("refund_fee_drops", params.refund_fee_drops, (0, MAX_REFUND_FEE_DROPS))?;
+    if let Some(threshold) = params.approval_threshold_drops {
+        in_range("approval_threshold_drops", threshold, APPROVAL_THRESHOLD_DROPS_RANGE)?;
+    }
+
+    if let Some(rules) = &params.limit_rules {
+        // Leaving the field out keeps the local rules; an empty list would drop every limit.
+        if rules.is_empty() {
+            return Err("limit_rules is empty".to_string());
+        }
+        validate_limit_rules(rules)?;
+        if let Some(rule) = rules.iter().find(|r| r.window_secs > MAX_LIMIT_WINDOW_SECS) {
+            return Err(format!("rule {} window exceeds {} secs", rule.id, MAX_LIMIT_WINDOW_SECS));
+        }
+    }
+
+    if params.allowed_currencies.is_empty() {
+        return Err("allowed_currencies is empty".to_string());
+    }
+    if let Some(code) = params.allowed_currencies.iter().find(|c| !valid_currency_code(c)) {
+        return Err(format!("invalid currency code {}", code));
+    }
+    Ok(())
+}
+
+/// Validates and applies a governed set in one step. Older versions are refused;
+/// the same version again is a no-op.
+pub fn apply_parameter_set(version: u64, params: BridgeParameters) -> Result<Option<ParameterSet>, String> {
+    let mut state = PARAM_STATE.write().unwrap();
+    let current = state.active.as_ref().map(|set| set.version);
+    match current {
+        Some(current) if version == current => return Ok(None),
+        Some(current) if version < current => {
+            return Err(format!("version {} is older than active version {}", version, current));
+        }
+        _ => {}
+    }
+    validate_parameters(&params).map_err(|e| format!("version {} rejected: {}", version, e))?;
+
+    let set = ParameterSet { version, params, applied_at: now_secs() };
+    let change = ParameterChange {
+        from_version: current,
+        to_version: version,
+        params: set.params.clone(),
+        at: set.applied_at,
+    };
+    state.active = Some(set.clone());
+    persist(&state);
+    drop(state);
+
+    if let Err(e) = append_jsonl_record(PARAMS_HISTORY_FILE, &change) {
+        bridge_log_event("error", format!("Failed to write parameter history: {:?}", e));
+    }
+    bridge_log_event(
+        "params",
+        format!("🏛️ Applied governed parameters v{} (was {})", version, current.map_or("env defaults".to_string(), |v| format!("v{}", v))),
+    );
+    Ok(Some(set))
+}
+
+/// Pulls the parameter set from the governance canister and applies it if newer.
+/// On any failure the last-known-good set stays in force.
+pub async fn sync_parameters(agent: &Agent, config: &BridgeConfig) -> ParameterStatus {
+    let fetched = fetch_governed_config(agent, config, &get_params_method(), &get_params_module(), &get_params_key())
+        .await
+        .map_err(|e| format!("governance canister unreachable: {}", e))
+        .and_then(|found| found.ok_or_else(|| "no parameter set published".to_string()))
+        .and_then(|cfg| {
+            if cfg.schema_id != PARAMS_SCHEMA_ID {
+                return Err(format!("unexpected schema {}", cfg.schema_id));
+            }
+            let version = u64::try_from(&cfg.version.0).map_err(|_| format!("version {} out of range", cfg.version))?;
+            let params = serde_json::from_slice::<BridgeParameters>(&cfg.value)
+                .map_err(|e| format!("version {} unreadable: {}", version, e))?;
+            Ok((version, params))
+        })
+        .and_then(|(version, params)| apply_parameter_set(version, params));
+
+    {
+        let mut state = PARAM_STATE.write().unwrap();
+        match fetched {
+            Ok(_) => {
+                state.last_synced_at = Some(now_secs());
+                state.last_error = None;
+            }
+            Err(e) => {
+                if state.last_error.as_ref() != Some(&e) {
+                    bridge_log_event("error", format!("❌ Parameter sync failed, keeping last-known-good: {}", e));
+                }
+                state.last_error = Some(e);
+            }
+        }
+        persist(&state);
+    }
+    parameter_status()
+}
+
+pub async fn run_parameter_sync(agent: Agent, config: BridgeConfig, interval_secs: u64) {
+    loop {
+        sync_parameters(&agent, &config).await;
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
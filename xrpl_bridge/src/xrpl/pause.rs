@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use candid::CandidType;
+use ic_agent::Agent;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{get_pause_method, get_pause_module, BridgeConfig};
+use crate::ic_trigger::fetch_pause_flag;
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::PendingAction;
+use crate::xrpl::types::XRPLError;
+use crate::util::now_secs;
+
+const PAUSE_STATE_FILE: &str = "pause.json";
+
+/// Subset of admin2's `EmergencyState` record the bridge reads.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct EmergencyFlag {
+    #[serde(rename = "killSwitch")]
+    pub kill_switch: bool,
+    #[serde(rename = "readOnly")]
+    pub read_only: bool,
+    pub note: Option<String>,
+}
+
+/// A part of the bridge that can be paused on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PauseFlow {
+    Global,
+    /// Dispatch of queued XRPL → ICP actions.
+    Inbound,
+    /// Every transaction the bridge submits to XRPL.
+    Outbound,
+    /// Dispatch of one action type (Tip, NFTSale, ...).
+    Action(String),
+}
+
+impl PauseFlow {
+    /// `global`, `inbound`, `outbound` or `action:<Type>`.
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "global" => Some(PauseFlow::Global),
+            "inbound" => Some(PauseFlow::Inbound),
+            "outbound" => Some(PauseFlow::Outbound),
+            _ => raw
+                .strip_prefix("action:")
+                .filter(|kind| PendingAction::KINDS.contains(kind))
+                .map(|kind| PauseFlow::Action(kind.to_string())),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            PauseFlow::Global => "global".to_string(),
+            PauseFlow::Inbound => "inbound".to_string(),
+            PauseFlow::Outbound => "outbound".to_string(),
+            PauseFlow::Action(kind) => format!("action:{}", kind),
+        }
+    }
+
+    /// Module name the flag is kept under on the pause canister.
+    fn module(&self) -> String {
+        let prefix = get_pause_module();
+        match self {
+            PauseFlow::Global => prefix,
+            PauseFlow::Inbound => format!("{}.inbound", prefix),
+            PauseFlow::Outbound => format!("{}.outbound", prefix),
+            PauseFlow::Action(kind) => format!("{}.action.{}", prefix, kind),
+        }
+    }
+
+    fn all() -> Vec<PauseFlow> {
+        let mut flows = vec![PauseFlow::Global, PauseFlow::Inbound, PauseFlow::Outbound];
+        flows.extend(PendingAction::KINDS.iter().map(|kind| PauseFlow::Action(kind.to_string())));
+        flows
+    }
+}
+
+/// One flow's canister flag and local override.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowPause {
+    /// Last value read from the pause canister.
+    pub canister: bool,
+    pub canister_note: Option<String>,
+    /// Set through the admin API; wins over the canister either way.
+    pub override_paused: Option<bool>,
+    pub override_by: Option<String>,
+    pub override_at: Option<u64>,
+}
+
+impl FlowPause {
+    pub fn paused(&self) -> bool {
+        self.override_paused.unwrap_or(self.canister)
+    }
+}
+
+/// Effective pause state, as shown by the monitor and admin API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PauseStatus {
+    pub global: bool,
+    pub inbound: bool,
+    pub outbound: bool,
+    /// Action types whose dispatch is paused on their own.
+    pub actions: Vec<String>,
+    pub flows: BTreeMap<String, FlowPause>,
+    pub last_polled_at: Option<u64>,
+    pub last_poll_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PauseBook {
+    /// Keyed by flow label; kept across restarts so a paused bridge comes back paused.
+    flows: BTreeMap<String, FlowPause>,
+    last_polled_at: Option<u64>,
+    last_poll_error: Option<String>,
+}
+
+static PAUSE_BOOK: Lazy<RwLock<PauseBook>> = Lazy::new(|| {
+    let book = load_json_state::<PauseBook>(PAUSE_STATE_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load pause state: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+fn persist(book: &PauseBook) {
+    if let Err(e) = persist_json_state(PAUSE_STATE_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist pause state: {:?}", e));
+    }
+}
+
+fn flow_paused(book: &PauseBook, flow: &PauseFlow) -> bool {
+    book.flows.get(&flow.label()).is_some_and(FlowPause::paused)
+}
+
+/// True when the action may not be dispatched to ICP right now.
+pub fn dispatch_paused(action: &PendingAction) -> bool {
+    let book = PAUSE_BOOK.read().unwrap();
+    flow_paused(&book, &PauseFlow::Global)
+        || flow_paused(&book, &PauseFlow::Inbound)
+        || flow_paused(&book, &PauseFlow::Action(action.kind().to_string()))
+}
+
+/// True when nothing may be submitted to XRPL right now.
+pub fn outbound_paused() -> bool {
+    let book = PAUSE_BOOK.read().unwrap();
+    flow_paused(&book, &PauseFlow::Global) || flow_paused(&book, &PauseFlow::Outbound)
+}
+
+/// Refuses an outbound submission while outbound flow is paused.
+pub fn ensure_outbound_allowed(job_id: &str) -> Result<(), XRPLError> {
+    if outbound_paused() {
+        return Err(XRPLError::Other(format!("Outbound submissions are paused; {} not sent", job_id)));
+    }
+    Ok(())
+}
+
+pub fn pause_status() -> PauseStatus {
+    let book = PAUSE_BOOK.read().unwrap();
+    PauseStatus {
+        global: flow_paused(&book, &PauseFlow::Global),
+        inbound: flow_paused(&book, &PauseFlow::Inbound),
+        outbound: flow_paused(&book, &PauseFlow::Outbound),
+        actions: PendingAction::KINDS
+            .iter()
+            .filter(|kind| flow_paused(&book, &PauseFlow::Action(kind.to_string())))
+            .map(|kind| kind.to_string())
+            .collect(),
+        flows: book.flows.clone(),
+        last_polled_at: book.last_polled_at,
+        last_poll_error: book.last_poll_error.clone(),
+    }
+}
+
+fn log_change(label: &str, was: bool, now: bool, by: &str) {
+    if was != now {
+        let verb = if now { "⏸️ Paused" } else { "▶️ Resumed" };
+        bridge_log_event("pause", format!("{} {} ({})", verb, label, by));
+    }
+}
+
+/// Operator override for one flow: `Some(true)` pauses, `Some(false)` runs it
+/// whatever the canister says, `None` goes back to following the canister.
+pub fn set_pause_override(flow: &PauseFlow, paused: Option<bool>, operator: &str) -> PauseStatus {
+    {
+        let mut book = PAUSE_BOOK.write().unwrap();
+        let label = flow.label();
+        let entry = book.flows.entry(label.clone()).or_default();
+        let was = entry.paused();
+        entry.override_paused = paused;
+        entry.override_by = paused.map(|_| operator.to_string());
+        entry.override_at = paused.map(|_| now_secs());
+        log_change(&label, was, entry.paused(), &format!("override by {}", operator));
+        persist(&book);
+    }
+    pause_status()
+}
+
+/// Reads every flow's flag from the pause canister. A failed poll keeps the
+/// last-known flags in force.
+pub async fn refresh_pause_flags(agent: &Agent, config: &BridgeConfig) {
+    let method = get_pause_method();
+    let mut flags = Vec::new();
+    let mut error = None;
+    for flow in PauseFlow::all() {
+        match fetch_pause_flag(agent, config, &method, &flow.module()).await {
+            Ok(flag) => flags.push((flow, flag)),
+            Err(e) => {
+                error = Some(format!("{}: {}", flow.module(), e));
+                break;
+            }
+        }
+    }
+
+    let mut book = PAUSE_BOOK.write().unwrap();
+    if let Some(error) = error {
+        if book.last_poll_error.as_ref() != Some(&error) {
+            bridge_log_event("error", format!("❌ Pause flag poll failed, keeping last-known flags: {}", error));
+        }
+        book.last_poll_error = Some(error);
+        persist(&book);
+        return;
+    }
+
+    for (flow, flag) in flags {
+        let label = flow.label();
+        let entry = book.flows.entry(label.clone()).or_default();
+        let was = entry.paused();
+        entry.canister = flag.as_ref().is_some_and(|f| f.kill_switch || f.read_only);
+        entry.canister_note = flag.and_then(|f| f.note);
+        log_change(&label, was, entry.paused(), "pause canister");
+    }
+    book.last_polled_at = Some(now_secs());
+    book.last_poll_error = None;
+    persist(&book);
+}
+
+pub async fn run_pause_polling(agent: Agent, config: BridgeConfig, interval_secs: u64) {
+    loop {
+        refresh_pause_flags(&agent, &config).await;
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use candid::{Nat, Principal};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::get_bridge_address;
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::{enqueue_action, PendingAction, QueueError};
+use crate::xrpl::approvals::{admit_action, flag_for_approval};
+use crate::xrpl::outbound::submit_and_wait;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::screening::screen_off_ledger_source;
+use crate::xrpl::types::XRPLError;
+use crate::util::{RIPPLE_EPOCH_OFFSET, now_secs};
+
+const PAYCHAN_BOOK_FILE: &str = "paychan_book.json";
+
+/// Channel state read from the ledger is reused for this long between claims.
+const CHANNEL_REFRESH_SECS: u64 = 30;
+
+/// Claims are refused on channels closing sooner than this; we must still be able to redeem.
+const CLOSE_SAFETY_SECS: u64 = 600;
+
+/// A payment channel from a tipper to the bridge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelRecord {
+    pub channel_id: String,
+    pub source: String,
+    pub public_key: String,
+    /// Total drops funded into the channel.
+    pub capacity_drops: u64,
+    /// Drops already paid out on-ledger.
+    pub ledger_balance_drops: u64,
+    /// Ripple-epoch seconds; set once the source asks to close.
+    pub expiration: Option<u64>,
+    pub cancel_after: Option<u64>,
+    /// Highest verified claim and its signature, to redeem with `PaymentChannelClaim`.
+    pub best_claim_drops: u64,
+    pub best_claim_signature: Option<String>,
+    /// Drops credited to ICP as tips so far.
+    pub credited_drops: u64,
+    /// Drops redeemed on-ledger by the bridge.
+    pub redeemed_drops: u64,
+    pub last_redeem_tx: Option<String>,
+    pub closed: bool,
+    pub refreshed_at: u64,
+    pub updated_at: u64,
+}
+
+/// A signed off-ledger claim: the channel owes the bridge `amount_drops` in total.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelClaim {
+    pub channel_id: String,
+    /// Cumulative, as a decimal string of drops.
+    pub amount_drops: String,
+    pub signature: String,
+    /// Artist the increment over the previous claim is tipped to.
+    pub artist: String,
+    #[serde(default)]
+    pub uuid: Option<String>,
+}
+
+/// Outcome of an accepted claim.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimReceipt {
+    pub channel_id: String,
+    pub cumulative_drops: u64,
+    pub credited_drops: u64,
+    pub remaining_drops: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ChannelBook {
+    channels: BTreeMap<String, ChannelRecord>,
+}
+
+static CHANNEL_BOOK: Lazy<RwLock<ChannelBook>> = Lazy::new(|| {
+    let book = load_json_state::<ChannelBook>(PAYCHAN_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load payment channel book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+/// Serializes claim handling so concurrent claims on one channel can't double-credit.
+static CLAIM_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+fn ripple_now() -> u64 {
+    now_secs().saturating_sub(RIPPLE_EPOCH_OFFSET)
+}
+
+fn persist(book: &ChannelBook) {
+    if let Err(e) = persist_json_state(PAYCHAN_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist payment channel book: {:?}", e));
+    }
+}
+
+fn parse_drops(value: &Value) -> Option<u64> {
+    value.as_str().and_then(|v| v.parse().ok())
+}
+
+/// Earliest time the channel can be closed by its source, if any.
+fn closes_at(channel: &ChannelRecord) -> Option<u64> {
+    match (channel.expiration, channel.cancel_after) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Reads the channel from the validated ledger into the book, keeping our claim state.
+pub async fn refresh_channel(rpc: &XRPLRpcClient, channel_id: &str) -> Result<ChannelRecord, XRPLError> {
+    let bridge = get_bridge_address().ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))?;
+
+    let node = match rpc.ledger_entry(json!({ "payment_channel": channel_id })).await {
+        Ok(node) => node,
+        Err(XRPLError::InvalidResponse(msg)) if msg.contains("entryNotFound") => {
+            // The channel is gone from the ledger: it was closed.
+            let mut book = CHANNEL_BOOK.write().unwrap();
+            let record = book
+                .channels
+                .get_mut(channel_id)
+                .ok_or_else(|| XRPLError::Other(format!("Unknown payment channel {}", channel_id)))?;
+            record.closed = true;
+            record.updated_at = now_secs();
+            let record = record.clone();
+            persist(&book);
+            return Ok(record);
+        }
+        Err(e) => return Err(e),
+    };
+
+    if node["Destination"].as_str() != Some(bridge.as_str()) {
+        return Err(XRPLError::InvalidTransaction(format!("Channel {} does not pay the bridge", channel_id)));
+    }
+    let (Some(source), Some(public_key), Some(capacity), Some(balance)) = (
+        node["Account"].as_str(),
+        node["PublicKey"].as_str(),
+        parse_drops(&node["Amount"]),
+        parse_drops(&node["Balance"]),
+    ) else {
+        return Err(XRPLError::InvalidResponse(format!("Incomplete PayChannel node for {}", channel_id)));
+    };
+
+    let mut book = CHANNEL_BOOK.write().unwrap();
+    let record = book.channels.entry(channel_id.to_string()).or_insert_with(|| ChannelRecord {
+        channel_id: channel_id.to_string(),
+        source: source.to_string(),
+        public_key: public_key.to_string(),
+        capacity_drops: 0,
+        ledger_balance_drops: 0,
+        expiration: None,
+        cancel_after: None,
+        best_claim_drops: 0,
+        best_claim_signature: None,
+        credited_drops: 0,
+        redeemed_drops: 0,
+        last_redeem_tx: None,
+        closed: false,
+        refreshed_at: 0,
+        updated_at: 0,
+    });
+    record.capacity_drops = capacity;
+    record.ledger_balance_drops = balance;
+    record.expiration = node["Expiration"].as_u64();
+    record.cancel_after = node["CancelAfter"].as_u64();
+    record.refreshed_at = now_secs();
+    record.updated_at = now_secs();
+    let record = record.clone();
+    persist(&book);
+
+    Ok(record)
+}
+
+/// Verifies a claim and credits its increment over the previous best claim to ICP as a tip.
+/// Stale or replayed claims (not above the best claim) are rejected, as are claims on a
+/// channel whose funder fails screening (quarantined until a reviewer releases it).
+pub async fn accept_channel_claim(rpc: &XRPLRpcClient, claim: ChannelClaim) -> Result<ClaimReceipt, XRPLError> {
+    let amount: u64 = claim
+        .amount_drops
+        .parse()
+        .map_err(|_| XRPLError::InvalidTransaction(format!("Bad claim amount {}", claim.amount_drops)))?;
+    let artist = Principal::from_text(&claim.artist)
+        .map_err(|_| XRPLError::InvalidTransaction(format!("Bad artist principal {}", claim.artist)))?;
+
+    let _guard = CLAIM_LOCK.lock().await;
+
+    let cached = CHANNEL_BOOK.read().unwrap().channels.get(&claim.channel_id).cloned();
+    let channel = match cached {
+        Some(c) if now_secs().saturating_sub(c.refreshed_at) < CHANNEL_REFRESH_SECS => c,
+        _ => refresh_channel(rpc, &claim.channel_id).await?,
+    };
+
+    if channel.closed {
+        return Err(XRPLError::InvalidTransaction(format!("Channel {} is closed", channel.channel_id)));
+    }
+    if closes_at(&channel).is_some_and(|t| t <= ripple_now() + CLOSE_SAFETY_SECS) {
+        return Err(XRPLError::InvalidTransaction(format!("Channel {} is closing", channel.channel_id)));
+    }
+    if amount <= channel.best_claim_drops {
+        return Err(XRPLError::InvalidTransaction(format!(
+            "Claim {} does not exceed the best claim {}",
+            amount, channel.best_claim_drops
+        )));
+    }
+    if amount > channel.capacity_drops {
+        return Err(XRPLError::TransactionInsufficientFunds(format!(
+            "Claim {} exceeds channel capacity {}",
+            amount, channel.capacity_drops
+        )));
+    }
+
+    if !rpc
+        .channel_verify(&channel.channel_id, amount, &claim.signature, &channel.public_key)
+        .await?
+    {
+        return Err(XRPLError::TransactionInvalidSignature(format!("Claim on {} failed verification", channel.channel_id)));
+    }
+
+    // The channel's funder is the payer: screen it before anything is credited. A refused
+    // claim loses nothing, since the next (cumulative) claim covers it.
+    let flag = screen_off_ledger_source(&format!("paychan:{}", channel.channel_id), "PaymentChannelClaim", &channel.source).await?;
+
+    // Durable first: once the best claim is on disk it can always be redeemed.
+    {
+        let mut book = CHANNEL_BOOK.write().unwrap();
+        if let Some(record) = book.channels.get_mut(&channel.channel_id) {
+            record.best_claim_drops = amount;
+            record.best_claim_signature = Some(claim.signature.clone());
+            record.updated_at = now_secs();
+        }
+        persist(&book);
+    }
+
+    // Drops already paid out before we tracked the channel were never ours to credit.
+    let increment = amount.saturating_sub(channel.credited_drops.max(channel.ledger_balance_drops));
+    if increment > 0 {
+        let action = PendingAction::Tip {
+            artist,
+            amount: Nat::from(increment),
+            tx_hash: format!("paychan:{}:{}", channel.channel_id, amount),
+            uuid: claim.uuid.clone().unwrap_or_else(|| format!("{}:{}", channel.channel_id, amount)),
+        };
+        if let Some(flag) = flag {
+            flag_for_approval(action.tx_hash(), &flag);
+        }
+        // Channel funds aren't ours until redeemed, so a limit breach is held rather than refunded.
+        if let Some(action) = admit_action(&channel.source, action, None) {
+            match enqueue_action(action) {
+                Ok(()) | Err(QueueError::AlreadyExists) => {}
+                Err(e) => return Err(XRPLError::Other(format!("Could not queue channel tip: {:?}", e))),
+            }
+        }
+
+        let mut book = CHANNEL_BOOK.write().unwrap();
+        if let Some(record) = book.channels.get_mut(&channel.channel_id) {
+            record.credited_drops = amount;
+        }
+        persist(&book);
+    }
+
+    bridge_log_event(
+        "paychan",
+        format!("💧 Channel {} claim {} drops (+{} to {})", channel.channel_id, amount, increment, artist),
+    );
+
+    Ok(ClaimReceipt {
+        channel_id: channel.channel_id,
+        cumulative_drops: amount,
+        credited_drops: increment,
+        remaining_drops: channel.capacity_drops - amount,
+    })
+}
+
+/// Redeems the best claim on-ledger with `PaymentChannelClaim`.
+pub async fn redeem_channel(rpc: &XRPLRpcClient, channel_id: &str) -> Result<Option<Value>, XRPLError> {
+    let channel = CHANNEL_BOOK
+        .read()
+        .unwrap()
+        .channels
+        .get(channel_id)
+        .cloned()
+        .ok_or_else(|| XRPLError::Other(format!("Unknown payment channel {}", channel_id)))?;
+
+    let Some(signature) = channel.best_claim_signature.clone() else {
+        return Ok(None);
+    };
+    if channel.closed || channel.best_claim_drops <= channel.redeemed_drops.max(channel.ledger_balance_drops) {
+        return Ok(None);
+    }
+
+    let amount = channel.best_claim_drops.to_string();
+    let tx = json!({
+        "TransactionType": "PaymentChannelClaim",
+        "Channel": channel_id,
+        "Balance": amount,
+        "Amount": amount,
+        "Signature": signature,
+        "PublicKey": channel.public_key,
+    });
+
+    let validated = submit_and_wait(rpc, &format!("paychan-{}-{}", channel_id, amount), tx).await?;
+
+    let mut book = CHANNEL_BOOK.write().unwrap();
+    if let Some(record) = book.channels.get_mut(channel_id) {
+        record.redeemed_drops = channel.best_claim_drops;
+        record.ledger_balance_drops = record.ledger_balance_drops.max(channel.best_claim_drops);
+        record.last_redeem_tx = validated["hash"].as_str().map(str::to_string);
+        record.updated_at = now_secs();
+    }
+    persist(&book);
+
+    bridge_log_event(
+        "paychan",
+        format!("✅ Redeemed {} drops from channel {}", channel.best_claim_drops, channel_id),
+    );
+    Ok(Some(validated))
+}
+
+pub fn get_channel(channel_id: &str) -> Option<ChannelRecord> {
+    CHANNEL_BOOK.read().unwrap().channels.get(channel_id).cloned()
+}
+
+pub fn list_channels() -> Vec<ChannelRecord> {
+    CHANNEL_BOOK.read().unwrap().channels.values().cloned().collect()
+}
+
+/// Refreshes open channels and redeems every unredeemed best claim.
+pub async fn run_channel_redemption(rpc: XRPLRpcClient, interval_secs: u64) {
+    loop {
+        for channel in list_channels().into_iter().filter(|c| !c.closed) {
+            if let Err(e) = refresh_channel(&rpc, &channel.channel_id).await {
+                bridge_log_event("warn", format!("Channel {} refresh failed: {}", channel.channel_id, e));
+            }
+            if let Err(e) = redeem_channel(&rpc, &channel.channel_id).await {
+                bridge_log_event("error", format!("❌ Channel {} redemption failed: {}", channel.channel_id, e));
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
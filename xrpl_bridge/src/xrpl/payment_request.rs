@@ -0,0 +1,118 @@
+use candid::Principal;
+use qrcode::render::svg;
+use qrcode::{Color, QrCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::get_bridge_address;
+use crate::xrpl::address::bridge_x_address;
+use crate::xrpl::memo::{generate_uuid, memos_from_text, parse_memo_string, validate_parsed_memo};
+use crate::xrpl::params::min_tip_drops;
+use crate::xrpl::types::{TipRequest, XRPLActionType};
+
+/// Destination tag the verifier routes to tips.
+const TIP_DESTINATION_TAG: u32 = 1001;
+
+/// A tip ready for any XRPL wallet to sign: the unsigned Payment, deep links and a QR code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentRequest {
+    pub uuid: String,
+    pub tx_json: Value,
+    pub memo: String,
+    /// Bridge address with the destination tag folded in.
+    pub x_address: String,
+    /// `xrpl:` deep link to the X-address (what the QR code encodes).
+    pub uri: String,
+    /// `ripple:` deep link with classic address + `dt`, for wallets without X-address support.
+    pub ripple_uri: String,
+    /// SVG rendering of `uri`.
+    pub qr_svg: String,
+    /// The same QR code as rows of `1` (dark) / `0` (light) modules.
+    pub qr_matrix: Vec<String>,
+}
+
+/// Drops as a decimal XRP string, without trailing zeros.
+fn drops_to_xrp(drops: u64) -> String {
+    let whole = drops / 1_000_000;
+    let frac = drops % 1_000_000;
+    if frac == 0 {
+        whole.to_string()
+    } else {
+        format!("{}.{:06}", whole, frac).trim_end_matches('0').to_string()
+    }
+}
+
+fn encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+/// Builds the signed-by-the-wallet form of a tip. The bridge owns the memo format,
+/// so a memo passed in the request must itself validate as a `TIP` memo.
+pub fn build_tip_payment_request(request: &TipRequest) -> Result<PaymentRequest, String> {
+    let destination = get_bridge_address().ok_or("XRPL_BRIDGE_ADDRESS not set")?;
+    Principal::from_text(&request.artist_principal)
+        .map_err(|_| format!("Invalid artist principal {}", request.artist_principal))?;
+
+    let minimum = min_tip_drops();
+    if request.amount < minimum {
+        return Err(format!("Tip of {} drops is below the {} drop minimum", request.amount, minimum));
+    }
+
+    let (uuid, memo) = match &request.memo {
+        Some(memo) => {
+            let parsed = parse_memo_string(memo).map_err(|e| e.to_string())?;
+            validate_parsed_memo(&parsed).map_err(|e| e.to_string())?;
+            if parsed.action != XRPLActionType::Tip {
+                return Err("Memo is not a TIP memo".into());
+            }
+            if parsed.fields.get("ARTIST") != Some(&request.artist_principal) {
+                return Err("Memo ARTIST does not match artist_principal".into());
+            }
+            (parsed.fields.get("UUID").cloned().unwrap_or_default(), memo.clone())
+        }
+        None => {
+            let uuid = generate_uuid();
+            let memo = format!("TIP|ARTIST:{}|UUID:{}", request.artist_principal, uuid);
+            (uuid, memo)
+        }
+    };
+    // Any other tag would route the payment as a different action than the TIP memo says.
+    let tag = match request.destination_tag {
+        None | Some(TIP_DESTINATION_TAG) => TIP_DESTINATION_TAG,
+        Some(other) => return Err(format!("Destination tag {} is not the tip tag {}", other, TIP_DESTINATION_TAG)),
+    };
+
+    let tx_json = json!({
+        "TransactionType": "Payment",
+        "Destination": destination,
+        "DestinationTag": tag,
+        "Amount": request.amount.to_string(),
+        "Memos": memos_from_text(&memo),
+    });
+
+    let x_address = bridge_x_address(&destination, Some(tag)).map_err(|e| e.to_string())?;
+    let amount = drops_to_xrp(request.amount);
+    let memo_param = encode(&memo);
+    let uri = format!("xrpl:{}?amount={}&memo={}", x_address, amount, memo_param);
+    let ripple_uri = format!("ripple:{}?amount={}&dt={}&memo={}", destination, amount, tag, memo_param);
+
+    let code = QrCode::new(uri.as_bytes()).map_err(|e| format!("QR encoding failed: {}", e))?;
+    let qr_svg = code.render::<svg::Color>().min_dimensions(256, 256).build();
+    let width = code.width();
+    let qr_matrix = code
+        .to_colors()
+        .chunks(width)
+        .map(|row| row.iter().map(|c| if *c == Color::Dark { '1' } else { '0' }).collect())
+        .collect();
+
+    Ok(PaymentRequest {
+        uuid,
+        tx_json,
+        memo,
+        x_address,
+        uri,
+        ripple_uri,
+        qr_svg,
+        qr_matrix,
+    })
+}
@@ -0,0 +1,620 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use candid::{CandidType, Int, Nat};
+use ic_agent::Agent;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{get_bridge_address, get_recon_payment_log_method, get_recon_settle_secs, BridgeConfig};
+use crate::ic_trigger::fetch_payment_log_records;
+use crate::log::bridge_log_event;
+use crate::monitor::record_error;
+use crate::state::db::{append_jsonl_record, load_json_state, load_jsonl_records, persist_json_state};
+use crate::wrapped_xrp::{is_parked_deposit, WRAP_DEPOSIT_TAG};
+use crate::xrpl::approvals::{get_held_action, HeldStatus};
+use crate::xrpl::intents::intent_of_payment;
+use crate::xrpl::issuer::is_parked_redemption;
+use crate::xrpl::memo::{generate_uuid, memo_text_from_tx, parse_memo_string};
+use crate::xrpl::refunds::get_refund;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::screening::{get_quarantined, QuarantineStatus};
+use crate::xrpl::types::{XRPLActionType, XRPLAmount, XRPLError};
+use crate::util::{RIPPLE_EPOCH_OFFSET, now_secs};
+
+const RECON_STATE_FILE: &str = "reconciliation.json";
+const RECON_HISTORY_FILE: &str = "reconciliation.jsonl";
+/// The bridge's processed ledger, written by `append_to_tx_log`.
+const TX_LOG_FILE: &str = "tx_log.jsonl";
+
+/// Subset of payment_log's `PaymentRecord`, as written by `logPayment`.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct PaymentLogRecord {
+    pub uuid: String,
+    pub sender: String,
+    pub action: String,
+    pub amount: Nat,
+    /// Nanoseconds since the Unix epoch.
+    pub timestamp: Int,
+}
+
+/// One line of the processed ledger.
+#[derive(Debug, Clone, Deserialize)]
+struct TxLogEntry {
+    tx_hash: String,
+    timestamp: u64,
+}
+
+/// What to reconcile: XRPL close times (Unix secs) or validated ledger indexes, inclusive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "by", rename_all = "snake_case")]
+pub enum ReconRange {
+    Time { from: u64, to: u64 },
+    Ledgers { from: u32, to: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiscrepancyKind {
+    /// A deposit with no entry where one was expected.
+    Missing,
+    /// More than one entry for one deposit.
+    Duplicated,
+    /// The recorded amount differs from what XRPL delivered.
+    AmountMismatch,
+    /// An entry no bridge-account transaction accounts for.
+    Orphaned,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Discrepancy {
+    pub kind: DiscrepancyKind,
+    /// `processed` (the bridge's tx log) or `payment_log`.
+    pub source: String,
+    pub tx_hash: Option<String>,
+    pub uuid: Option<String>,
+    pub xrpl_drops: Option<u64>,
+    pub recorded_amount: Option<String>,
+    pub count: usize,
+    pub detail: String,
+}
+
+/// A deposit that isn't expected to have been credited, and why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedDeposit {
+    pub tx_hash: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub report_id: String,
+    pub generated_at: u64,
+    pub range: ReconRange,
+    /// Close-time window orphans were looked for in.
+    pub window_from: Option<u64>,
+    pub window_to: Option<u64>,
+    pub xrpl_deposits: usize,
+    pub processed_entries: usize,
+    /// `None` when the payment log canister couldn't be read.
+    pub payment_log_records: Option<usize>,
+    pub matched: usize,
+    pub excluded: Vec<ExcludedDeposit>,
+    pub discrepancies: Vec<Discrepancy>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ReconState {
+    /// End of the last scheduled window; the next run starts here.
+    last_window_to: Option<u64>,
+    latest: Option<ReconciliationReport>,
+}
+
+/// A validated transaction touching the bridge account.
+struct BridgeTx {
+    hash: String,
+    close_time: u64,
+    /// Memo UUID, when the transaction carries a bridge memo.
+    uuid: Option<String>,
+    /// Inbound payment the bridge should have credited on ICP.
+    deposit: bool,
+    /// Wrap deposit: credited through the wXRP ledger, not the payment log.
+    wrap: bool,
+    drops: Option<u64>,
+    in_range: bool,
+}
+
+static RECON_STATE: Lazy<RwLock<ReconState>> = Lazy::new(|| {
+    let state = load_json_state::<ReconState>(RECON_STATE_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load reconciliation state: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(state)
+});
+
+fn persist(state: &ReconState) {
+    if let Err(e) = persist_json_state(RECON_STATE_FILE, state) {
+        bridge_log_event("error", format!("Failed to persist reconciliation state: {:?}", e));
+    }
+}
+
+fn secs_to_ns(secs: u64) -> Int {
+    Int::from(secs as u128 * 1_000_000_000)
+}
+
+fn ns_to_secs(ns: &Int) -> u64 {
+    u64::try_from(&ns.0 / 1_000_000_000u64).unwrap_or(0)
+}
+
+fn is_xrpl_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn bridge_tx(entry: &Value, bridge: &str, range: &ReconRange) -> Option<BridgeTx> {
+    // API v2 returns `tx_json` + `hash`; v1 returns `tx` with the hash inside.
+    let tx = if entry["tx_json"].is_object() { &entry["tx_json"] } else { &entry["tx"] };
+    let meta = &entry["meta"];
+    if entry["validated"] != true {
+        return None;
+    }
+    let hash = entry["hash"].as_str().or_else(|| tx["hash"].as_str())?.to_uppercase();
+    let close_time = tx["date"].as_u64().or_else(|| entry["close_time"].as_u64())? + RIPPLE_EPOCH_OFFSET;
+
+    let memo = memo_text_from_tx(tx).and_then(|text| parse_memo_string(&text).ok());
+    let uuid = memo.as_ref().and_then(|memo| memo.fields.get("UUID").cloned());
+    let wrap = tx["DestinationTag"].as_u64() == Some(WRAP_DEPOSIT_TAG as u64)
+        || memo.is_some_and(|memo| memo.action == XRPLActionType::WrapXRP);
+    // Every payment in is credited somehow (memo, tag, intent, redemption) or accounted
+    // for by an exclusion; payments the bridge sends itself are not deposits.
+    let deposit = tx["TransactionType"] == "Payment"
+        && tx["Destination"].as_str() == Some(bridge)
+        && tx["Account"].as_str() != Some(bridge)
+        && meta["TransactionResult"] == "tesSUCCESS";
+    let delivered = if meta["delivered_amount"].is_null() { &tx["Amount"] } else { &meta["delivered_amount"] };
+
+    let in_range = match range {
+        ReconRange::Time { from, to } => (*from..=*to).contains(&close_time),
+        ReconRange::Ledgers { .. } => true,
+    };
+    Some(BridgeTx {
+        hash,
+        close_time,
+        uuid,
+        deposit,
+        wrap,
+        drops: XRPLAmount::from_json(delivered).and_then(|a| a.drops()),
+        in_range,
+    })
+}
+
+/// Bridge-account transactions for the range. Time ranges reach back `settle` seconds
+/// further, so entries for deposits just before the window aren't taken for orphans.
+async fn fetch_bridge_txs(rpc: &XRPLRpcClient, bridge: &str, range: &ReconRange, settle: u64) -> Result<Vec<BridgeTx>, XRPLError> {
+    let (ledger_min, ledger_max, earliest) = match range {
+        ReconRange::Time { from, .. } => (-1, -1, Some(from.saturating_sub(settle))),
+        ReconRange::Ledgers { from, to } => (*from as i64, *to as i64, None),
+    };
+
+    let mut txs = Vec::new();
+    let mut marker = None;
+    loop {
+        let (page, next) = rpc.account_tx_page(bridge, ledger_min, ledger_max, marker).await?;
+        let mut reached_start = false;
+        for entry in &page {
+            let Some(tx) = bridge_tx(entry, bridge, range) else { continue };
+            if earliest.is_some_and(|earliest| tx.close_time < earliest) {
+                reached_start = true;
+                break;
+            }
+            if matches!(range, ReconRange::Time { to, .. } if tx.close_time > *to) {
+                continue;
+            }
+            txs.push(tx);
+        }
+        match next {
+            Some(next) if !reached_start => marker = Some(next),
+            _ => break,
+        }
+    }
+    Ok(txs)
+}
+
+/// Why a deposit without a credit is expected to have none, if it is.
+fn exclusion_reason(tx_hash: &str) -> Option<String> {
+    // An intent is credited once, under the payment that fulfilled it; an overpayment
+    // refund on that payment doesn't change that.
+    match intent_of_payment(tx_hash) {
+        Some((_, true)) => return None,
+        Some((intent_id, false)) => return Some(format!("partial payment toward intent {}", intent_id)),
+        None => {}
+    }
+    if is_parked_deposit(tx_hash) {
+        return Some("wrap deposit awaiting a linked principal".to_string());
+    }
+    if is_parked_redemption(tx_hash) {
+        return Some("IOU redemption awaiting a linked principal".to_string());
+    }
+    if let Some(refund) = get_refund(tx_hash) {
+        return Some(format!("refund {:?}", refund.status));
+    }
+    if let Some(held) = get_held_action(tx_hash).filter(|h| h.status != HeldStatus::Approved) {
+        return Some(format!("held {:?}", held.status));
+    }
+    if let Some(record) = get_quarantined(tx_hash).filter(|q| q.status != QuarantineStatus::Released) {
+        return Some(format!("quarantine {:?}", record.status));
+    }
+    None
+}
+
+fn discrepancy(kind: DiscrepancyKind, source: &str, tx: Option<&BridgeTx>, count: usize, detail: String) -> Discrepancy {
+    Discrepancy {
+        kind,
+        source: source.to_string(),
+        tx_hash: tx.map(|t| t.hash.clone()),
+        uuid: tx.and_then(|t| t.uuid.clone()),
+        xrpl_drops: tx.and_then(|t| t.drops),
+        recorded_amount: None,
+        count,
+        detail,
+    }
+}
+
+/// Checks one deposit against the processed ledger and, when it could be read, the payment log.
+fn deposit_discrepancies(
+    tx: &BridgeTx,
+    processed_counts: &HashMap<String, usize>,
+    log_by_uuid: Option<&HashMap<&str, Vec<&PaymentLogRecord>>>,
+) -> Vec<Discrepancy> {
+    let mut found = Vec::new();
+    match processed_counts.get(&tx.hash).copied().unwrap_or(0) {
+        0 => found.push(discrepancy(DiscrepancyKind::Missing, "processed", Some(tx), 0, "no processed entry".into())),
+        1 => {}
+        n => found.push(discrepancy(DiscrepancyKind::Duplicated, "processed", Some(tx), n, format!("processed {} times", n))),
+    }
+
+    // Wrap deposits credit through the wXRP ledger, not the payment log.
+    if let (Some(uuid), Some(log_by_uuid), false) = (&tx.uuid, log_by_uuid, tx.wrap) {
+        let records = log_by_uuid.get(uuid.as_str()).map(Vec::as_slice).unwrap_or_default();
+        match records {
+            [] => found.push(discrepancy(DiscrepancyKind::Missing, "payment_log", Some(tx), 0, "no payment log record".into())),
+            [record] => {
+                if let Some(drops) = tx.drops.filter(|drops| record.amount != *drops) {
+                    let mut d = discrepancy(
+                        DiscrepancyKind::AmountMismatch,
+                        "payment_log",
+                        Some(tx),
+                        1,
+                        format!("XRPL delivered {} drops, payment log recorded {}", drops, record.amount.0),
+                    );
+                    d.recorded_amount = Some(record.amount.0.to_string());
+                    found.push(d);
+                }
+            }
+            many => found.push(discrepancy(
+                DiscrepancyKind::Duplicated,
+                "payment_log",
+                Some(tx),
+                many.len(),
+                format!("logged {} times", many.len()),
+            )),
+        }
+    }
+    found
+}
+
+/// Joins XRPL deposits, the processed ledger and the payment log by tx hash and UUID.
+pub async fn reconcile(agent: &Agent, config: &BridgeConfig, rpc: &XRPLRpcClient, range: ReconRange) -> Result<ReconciliationReport, XRPLError> {
+    let bridge = get_bridge_address().ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))?;
+    let settle = get_recon_settle_secs();
+    let txs = fetch_bridge_txs(rpc, &bridge, &range, settle).await?;
+
+    let (window_from, window_to) = match &range {
+        ReconRange::Time { from, to } => (Some(*from), Some(*to)),
+        // Without a time bound, skip the first `settle` seconds of what was fetched.
+        ReconRange::Ledgers { .. } => (
+            txs.iter().map(|t| t.close_time).min().map(|t| t + settle),
+            txs.iter().map(|t| t.close_time).max(),
+        ),
+    };
+    let in_window = |at: u64| window_from.is_some_and(|from| at >= from) && window_to.is_some_and(|to| at <= to);
+
+    let mut errors = Vec::new();
+    let processed = load_jsonl_records::<TxLogEntry>(TX_LOG_FILE)
+        .map_err(|e| XRPLError::Other(format!("processed ledger unreadable: {:?}", e)))?;
+    let log_from = match &range {
+        ReconRange::Time { from, .. } => Some(from.saturating_sub(settle)),
+        ReconRange::Ledgers { .. } => txs.iter().map(|t| t.close_time).min().map(|t| t.saturating_sub(settle)),
+    };
+    // Read up to now: credits for deposits late in the window may have been logged since.
+    let payment_log = match log_from {
+        Some(log_from) => fetch_payment_log_records(agent, config, &get_recon_payment_log_method(), secs_to_ns(log_from), secs_to_ns(now_secs()))
+            .await
+            .map_err(|e| errors.push(format!("payment log: {}", e)))
+            .ok(),
+        None => Some(Vec::new()),
+    };
+
+    let mut processed_counts: HashMap<String, usize> = HashMap::new();
+    for entry in &processed {
+        *processed_counts.entry(entry.tx_hash.to_uppercase()).or_default() += 1;
+    }
+    let mut log_by_uuid: HashMap<&str, Vec<&PaymentLogRecord>> = HashMap::new();
+    for record in payment_log.iter().flatten() {
+        log_by_uuid.entry(record.uuid.as_str()).or_default().push(record);
+    }
+
+    let mut excluded = Vec::new();
+    let mut discrepancies = Vec::new();
+    let mut matched = 0;
+    let deposits: Vec<&BridgeTx> = txs.iter().filter(|t| t.deposit && t.in_range).collect();
+    for tx in &deposits {
+        if let Some(reason) = exclusion_reason(&tx.hash) {
+            excluded.push(ExcludedDeposit { tx_hash: tx.hash.clone(), reason });
+            continue;
+        }
+        let found = deposit_discrepancies(tx, &processed_counts, payment_log.as_ref().map(|_| &log_by_uuid));
+        if found.is_empty() {
+            matched += 1;
+        }
+        discrepancies.extend(found);
+    }
+
+    let known_hashes: HashSet<&str> = txs.iter().map(|t| t.hash.as_str()).collect();
+    let known_uuids: HashSet<&str> = txs.iter().filter_map(|t| t.uuid.as_deref()).collect();
+    let mut candidates: Vec<String> = Vec::new();
+    for entry in processed.iter().filter(|e| in_window(e.timestamp)) {
+        let hash = entry.tx_hash.to_uppercase();
+        // Synthetic references (intents, escrows keyed locally) have no XRPL hash to match.
+        if is_xrpl_hash(&hash) && !known_hashes.contains(hash.as_str()) && !candidates.contains(&hash) {
+            candidates.push(hash);
+        }
+    }
+    for hash in candidates {
+        // Held actions released late are processed long after their deposit closed.
+        match rpc.tx(&hash).await {
+            Ok(found) if found["validated"] == true => continue,
+            Ok(_) | Err(XRPLError::TransactionNotFound(_)) => {}
+            Err(e) => {
+                errors.push(format!("lookup {}: {}", hash, e));
+                continue;
+            }
+        }
+        let mut d = discrepancy(
+            DiscrepancyKind::Orphaned,
+            "processed",
+            None,
+            processed_counts.get(&hash).copied().unwrap_or(1),
+            "no validated XRPL transaction with this hash".into(),
+        );
+        d.tx_hash = Some(hash);
+        discrepancies.push(d);
+    }
+    for record in payment_log.iter().flatten().filter(|r| in_window(ns_to_secs(&r.timestamp))) {
+        if !known_uuids.contains(record.uuid.as_str()) {
+            let mut d = discrepancy(
+                DiscrepancyKind::Orphaned,
+                "payment_log",
+                None,
+                1,
+                format!("{} from {} matches no bridge-account memo", record.action, record.sender),
+            );
+            d.uuid = Some(record.uuid.clone());
+            d.recorded_amount = Some(record.amount.0.to_string());
+            discrepancies.push(d);
+        }
+    }
+
+    let report = ReconciliationReport {
+        report_id: generate_uuid(),
+        generated_at: now_secs(),
+        range,
+        window_from,
+        window_to,
+        xrpl_deposits: deposits.len(),
+        processed_entries: processed.len(),
+        payment_log_records: payment_log.as_ref().map(Vec::len),
+        matched,
+        excluded,
+        discrepancies,
+        errors,
+    };
+    record_report(&report);
+    Ok(report)
+}
+
+fn record_report(report: &ReconciliationReport) {
+    if report.discrepancies.is_empty() {
+        bridge_log_event(
+            "reconcile",
+            format!("🧮 Reconciliation {}: {} deposit(s) matched", report.report_id, report.matched),
+        );
+    } else {
+        let alert = format!(
+            "🚨 Reconciliation {}: {} discrepancy(ies) across {} deposit(s)",
+            report.report_id,
+            report.discrepancies.len(),
+            report.xrpl_deposits
+        );
+        bridge_log_event("error", alert.clone());
+        record_error(&alert);
+    }
+    if !report.errors.is_empty() {
+        bridge_log_event("warn", format!("⚠️ Reconciliation {} incomplete: {}", report.report_id, report.errors.join("; ")));
+    }
+
+    if let Err(e) = append_jsonl_record(RECON_HISTORY_FILE, report) {
+        bridge_log_event("error", format!("Failed to write reconciliation report: {:?}", e));
+    }
+    let mut state = RECON_STATE.write().unwrap();
+    state.latest = Some(report.clone());
+    persist(&state);
+}
+
+pub fn latest_reconciliation() -> Option<ReconciliationReport> {
+    RECON_STATE.read().unwrap().latest.clone()
+}
+
+pub fn get_reconciliation(report_id: &str) -> Option<ReconciliationReport> {
+    load_jsonl_records::<ReconciliationReport>(RECON_HISTORY_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not read reconciliation history: {:?}", e));
+            Vec::new()
+        })
+        .into_iter()
+        .find(|report| report.report_id == report_id)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One row per discrepancy.
+pub fn report_to_csv(report: &ReconciliationReport) -> String {
+    let mut csv = String::from("report_id,kind,source,tx_hash,uuid,xrpl_drops,recorded_amount,count,detail\n");
+    for d in &report.discrepancies {
+        let row = [
+            report.report_id.clone(),
+            format!("{:?}", d.kind),
+            d.source.clone(),
+            d.tx_hash.clone().unwrap_or_default(),
+            d.uuid.clone().unwrap_or_default(),
+            d.xrpl_drops.map(|v| v.to_string()).unwrap_or_default(),
+            d.recorded_amount.clone().unwrap_or_default(),
+            d.count.to_string(),
+            d.detail.clone(),
+        ];
+        csv.push_str(&row.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Reconciles each window since the last run, ending `RECON_SETTLE_SECS` ago so
+/// recent deposits have had time to reach ICP.
+pub async fn run_reconciliation(agent: Agent, config: BridgeConfig, rpc: XRPLRpcClient, interval_secs: u64) {
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+
+        let to = now_secs().saturating_sub(get_recon_settle_secs());
+        let from = RECON_STATE
+            .read()
+            .unwrap()
+            .last_window_to
+            .map_or(to.saturating_sub(interval_secs), |last| last + 1);
+        if from > to {
+            continue;
+        }
+
+        match reconcile(&agent, &config, &rpc, ReconRange::Time { from, to }).await {
+            // An incomplete run (e.g. payment log unreadable) covers the window again next time.
+            Ok(report) if report.errors.is_empty() => {
+                let mut state = RECON_STATE.write().unwrap();
+                state.last_window_to = Some(to);
+                persist(&state);
+            }
+            Ok(_) => {}
+            Err(e) => bridge_log_event("error", format!("❌ Reconciliation failed: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const HASH: &str = "E08D6E9754025BA2534A78707605E0601F03ACE063687A0CA1BDDACFCD1698C7";
+
+    fn deposit_entry(memo_uuid: &str) -> Value {
+        let memo_hex: String = format!("TIP|ARTIST:2vxsx-fae|UUID:{}", memo_uuid).bytes().map(|b| format!("{:02X}", b)).collect();
+        json!({
+            "validated": true,
+            "hash": HASH,
+            "tx_json": {
+                "TransactionType": "Payment",
+                "Account": "rSender",
+                "Destination": "rBridge",
+                "DeliverMax": "1500000",
+                "date": 800_000_000,
+                "Memos": [{ "Memo": { "MemoData": memo_hex } }],
+            },
+            "meta": { "TransactionResult": "tesSUCCESS", "delivered_amount": "1500000" },
+        })
+    }
+
+    fn log_record(uuid: &str, amount: u64) -> PaymentLogRecord {
+        PaymentLogRecord {
+            uuid: uuid.to_string(),
+            sender: "rSender".to_string(),
+            action: "Tip".to_string(),
+            amount: Nat::from(amount),
+            timestamp: Int::from(0),
+        }
+    }
+
+    fn kinds(found: &[Discrepancy]) -> Vec<(DiscrepancyKind, &str)> {
+        found.iter().map(|d| (d.kind, d.source.as_str())).collect()
+    }
+
+    #[test]
+    fn bridge_payments_with_memos_are_deposits() {
+        let range = ReconRange::Time { from: 0, to: u64::MAX };
+        let tx = bridge_tx(&deposit_entry("u-1"), "rBridge", &range).unwrap();
+        assert!(tx.deposit && tx.in_range);
+        assert_eq!((tx.uuid.as_deref(), tx.drops), (Some("u-1"), Some(1_500_000)));
+        assert_eq!(tx.close_time, 800_000_000 + RIPPLE_EPOCH_OFFSET);
+
+        let outside = ReconRange::Time { from: 0, to: 1 };
+        assert!(!bridge_tx(&deposit_entry("u-1"), "rBridge", &outside).unwrap().in_range);
+        assert!(!bridge_tx(&deposit_entry("u-1"), "rOther", &range).unwrap().deposit);
+
+        // Tag- and intent-routed payments carry no memo but are still credited.
+        let mut bare = deposit_entry("u-1");
+        bare["tx_json"].as_object_mut().unwrap().remove("Memos");
+        let tx = bridge_tx(&bare, "rBridge", &range).unwrap();
+        assert!(tx.deposit && tx.uuid.is_none() && !tx.wrap);
+        // The bridge paying itself is not a deposit.
+        assert!(!bridge_tx(&deposit_entry("u-1"), "rSender", &range).unwrap().deposit);
+    }
+
+    #[test]
+    fn deposits_match_by_hash_and_uuid() {
+        let range = ReconRange::Time { from: 0, to: u64::MAX };
+        let tx = bridge_tx(&deposit_entry("u-1"), "rBridge", &range).unwrap();
+        let processed = HashMap::from([(HASH.to_string(), 1)]);
+
+        let good = log_record("u-1", 1_500_000);
+        let log = HashMap::from([("u-1", vec![&good])]);
+        assert!(deposit_discrepancies(&tx, &processed, Some(&log)).is_empty());
+
+        // An unreadable payment log only checks the processed ledger.
+        assert!(deposit_discrepancies(&tx, &processed, None).is_empty());
+        assert_eq!(
+            kinds(&deposit_discrepancies(&tx, &HashMap::new(), None)),
+            [(DiscrepancyKind::Missing, "processed")]
+        );
+
+        let twice = HashMap::from([(HASH.to_string(), 2)]);
+        let found = deposit_discrepancies(&tx, &twice, Some(&HashMap::new()));
+        assert_eq!(
+            kinds(&found),
+            [(DiscrepancyKind::Duplicated, "processed"), (DiscrepancyKind::Missing, "payment_log")]
+        );
+        assert_eq!(found[0].count, 2);
+
+        let short = log_record("u-1", 1_000_000);
+        let found = deposit_discrepancies(&tx, &processed, Some(&HashMap::from([("u-1", vec![&short])])));
+        assert_eq!(kinds(&found), [(DiscrepancyKind::AmountMismatch, "payment_log")]);
+        assert_eq!(found[0].recorded_amount.as_deref(), Some("1000000"));
+
+        let found = deposit_discrepancies(&tx, &processed, Some(&HashMap::from([("u-1", vec![&good, &good])])));
+        assert_eq!(kinds(&found), [(DiscrepancyKind::Duplicated, "payment_log")]);
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::{get_refund_max_per_sender, get_refund_window_secs, get_refunds_enabled};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::xrpl::memo::{memo_text_from_tx, memos_from_text};
+use crate::xrpl::multisig::submit_transfer;
+use crate::xrpl::params::refund_fee_drops;
+use crate::xrpl::pause::{ensure_outbound_allowed, outbound_paused};
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::{VerifierError, XRPLAmount, XRPLError};
+use crate::util::now_secs;
+
+const REFUND_BOOK_FILE: &str = "refunds.json";
+
+/// Memo prefix on our own refunds; inbound payments carrying it are never refunded back.
+const REFUND_MEMO_PREFIX: &str = "REFUND|";
+
+/// Whether a verification failure earns the sender their money back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefundDecision {
+    Refund(&'static str),
+    Keep(&'static str),
+}
+
+/// Refund policy per `VerifierError`. Sender mistakes are refunded; duplicates,
+/// foreign destinations and bridge-side faults are kept for manual review.
+pub fn classify_verifier_error(err: &VerifierError) -> RefundDecision {
+    match err {
+        VerifierError::InvalidTag(_) => RefundDecision::Refund("unknown destination tag"),
+        VerifierError::MemoParseFailed(_) | VerifierError::InvalidMemoFormat => RefundDecision::Refund("invalid memo"),
+        VerifierError::UnknownAction => RefundDecision::Refund("unknown memo action"),
+        VerifierError::InsufficientAmount(_, _) => RefundDecision::Refund("amount below minimum"),
+        VerifierError::ReplayDetected(_) => RefundDecision::Keep("duplicate transaction"),
+        VerifierError::InvalidDestination(_) => RefundDecision::Keep("not addressed to the bridge"),
+        VerifierError::Internal(_) => RefundDecision::Keep("internal verification error"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RefundStatus {
+    /// Queued for sending.
+    Pending,
+    /// Written before submission; a crash here needs manual review, never a blind resend.
+    Submitting,
+    Sent,
+    /// Refundable, but the sender hit the per-sender limit.
+    Withheld,
+    /// Not refunded by policy (see `reason`).
+    Kept,
+    Failed,
+}
+
+/// One inbound payment the bridge could not route, and what happened to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundRecord {
+    pub original_tx: String,
+    pub sender: String,
+    pub source_tag: Option<u32>,
+    pub delivered: XRPLAmount,
+    pub refund_drops: u64,
+    pub fee_drops: u64,
+    pub reason: String,
+    pub status: RefundStatus,
+    pub refund_tx: Option<String>,
+    pub error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RefundBook {
+    refunds: BTreeMap<String, RefundRecord>,
+}
+
+static REFUND_BOOK: Lazy<RwLock<RefundBook>> = Lazy::new(|| {
+    let book = load_json_state::<RefundBook>(REFUND_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load refund book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+fn persist(book: &RefundBook) {
+    if let Err(e) = persist_json_state(REFUND_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist refund book: {:?}", e));
+    }
+}
+
+/// Refunds already granted to `sender` inside the current window.
+fn recent_refunds(book: &RefundBook, sender: &str, now: u64) -> usize {
+    let window_start = now.saturating_sub(get_refund_window_secs());
+    book.refunds
+        .values()
+        .filter(|r| r.sender == sender && r.created_at >= window_start)
+        .filter(|r| matches!(r.status, RefundStatus::Pending | RefundStatus::Submitting | RefundStatus::Sent))
+        .count()
+}
+
+/// The inbound payment a refund would return.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefundSource {
+    pub original_tx: String,
+    pub sender: String,
+    pub source_tag: Option<u32>,
+    /// What to consider for return; for an overpayment, only the excess.
+    pub delivered: XRPLAmount,
+    pub is_refund_echo: bool,
+}
+
+impl RefundSource {
+    pub fn from_tx(tx_hash: &str, tx: &Value, meta: &Value) -> Option<Self> {
+        let delivered = if meta["delivered_amount"].is_null() { &tx["Amount"] } else { &meta["delivered_amount"] };
+        Some(Self {
+            original_tx: tx_hash.to_string(),
+            sender: tx["Account"].as_str()?.to_string(),
+            source_tag: tx["SourceTag"].as_u64().map(|t| t as u32),
+            delivered: XRPLAmount::from_json(delivered)?,
+            is_refund_echo: memo_text_from_tx(tx).is_some_and(|m| m.starts_with(REFUND_MEMO_PREFIX)),
+        })
+    }
+}
+
+/// Records an unroutable payment and decides whether it will be refunded.
+/// Idempotent per original transaction.
+pub fn queue_refund(tx_hash: &str, tx: &Value, meta: &Value, err: &VerifierError) -> Option<RefundRecord> {
+    let source = RefundSource::from_tx(tx_hash, tx, meta)?;
+    bridge_log_event("refund", format!("↩️ {} from {} failed verification: {:?}", tx_hash, source.sender, err));
+    Some(record_refund(source, classify_verifier_error(err)))
+}
+
+/// Applies the refund policy (enabled, echo, XRP-only, fee, per-sender limit) to a decision
+/// and persists the outcome. Idempotent per original transaction.
+pub fn record_refund(source: RefundSource, decision: RefundDecision) -> RefundRecord {
+    let now = now_secs();
+    let fee_drops = refund_fee_drops();
+    let refund_drops = source.delivered.drops().unwrap_or(0).saturating_sub(fee_drops);
+
+    let mut book = REFUND_BOOK.write().unwrap();
+    if let Some(existing) = book.refunds.get(&source.original_tx) {
+        return existing.clone();
+    }
+
+    let (status, reason) = match decision {
+        RefundDecision::Keep(reason) => (RefundStatus::Kept, reason.to_string()),
+        RefundDecision::Refund(reason) => {
+            if !get_refunds_enabled() {
+                (RefundStatus::Kept, format!("{} (refunds disabled)", reason))
+            } else if source.is_refund_echo {
+                (RefundStatus::Kept, format!("{} (payment is itself a refund)", reason))
+            } else if source.delivered.drops().is_none() {
+                (RefundStatus::Kept, format!("{} (non-XRP payment)", reason))
+            } else if refund_drops == 0 {
+                (RefundStatus::Kept, format!("{} (amount does not cover the refund fee)", reason))
+            } else if recent_refunds(&book, &source.sender, now) >= get_refund_max_per_sender() {
+                (RefundStatus::Withheld, format!("{} (sender refund limit reached)", reason))
+            } else {
+                (RefundStatus::Pending, reason.to_string())
+            }
+        }
+    };
+
+    let record = RefundRecord {
+        original_tx: source.original_tx.clone(),
+        sender: source.sender,
+        source_tag: source.source_tag,
+        delivered: source.delivered,
+        refund_drops,
+        fee_drops,
+        reason,
+        status,
+        refund_tx: None,
+        error: None,
+        created_at: now,
+        updated_at: now,
+    };
+    book.refunds.insert(source.original_tx, record.clone());
+    persist(&book);
+
+    bridge_log_event(
+        "refund",
+        format!("↩️ {} from {}: {} — {:?}", record.original_tx, record.sender, record.reason, record.status),
+    );
+    record
+}
+
+fn update_refund(original_tx: &str, f: impl FnOnce(&mut RefundRecord)) -> Option<RefundRecord> {
+    let mut book = REFUND_BOOK.write().unwrap();
+    let record = book.refunds.get_mut(original_tx)?;
+    f(record);
+    record.updated_at = now_secs();
+    let record = record.clone();
+    persist(&book);
+    Some(record)
+}
+
+/// Sends the return Payment for a pending refund.
+pub async fn send_refund(rpc: &XRPLRpcClient, original_tx: &str) -> Result<RefundRecord, XRPLError> {
+    let record = get_refund(original_tx).ok_or_else(|| XRPLError::Other(format!("Unknown refund {}", original_tx)))?;
+    if record.status != RefundStatus::Pending {
+        return Err(XRPLError::InvalidTransaction(format!("Refund {} is {:?}", original_tx, record.status)));
+    }
+    // Checked before `Submitting`, so a paused refund stays Pending rather than needing review.
+    ensure_outbound_allowed(&format!("refund-{}", original_tx))?;
+
+    update_refund(original_tx, |r| r.status = RefundStatus::Submitting);
+
+    let memo = format!("{}TX:{}|REASON:{}", REFUND_MEMO_PREFIX, original_tx, record.reason);
+    let mut tx = json!({
+        "TransactionType": "Payment",
+        "Destination": record.sender,
+        "Amount": record.refund_drops.to_string(),
+        "Memos": memos_from_text(&memo),
+    });
+    if let Some(tag) = record.source_tag {
+        tx["DestinationTag"] = json!(tag);
+    }
+
+    match submit_transfer(rpc, &format!("refund-{}", original_tx), tx).await {
+        Ok(validated) => {
+            let hash = validated["hash"].as_str().map(str::to_string);
+            bridge_log_event(
+                "refund",
+                format!("✅ Refunded {} drops to {} for {}", record.refund_drops, record.sender, original_tx),
+            );
+            Ok(update_refund(original_tx, |r| {
+                r.status = RefundStatus::Sent;
+                r.refund_tx = hash;
+                r.error = None;
+            })
+            .unwrap_or(record))
+        }
+        Err(e) => {
+            // Only a validated rejection proves nothing was paid; anything else stays in Submitting for review.
+            let rejected = matches!(e, XRPLError::TransactionRejected(_));
+            update_refund(original_tx, |r| {
+                if rejected {
+                    r.status = RefundStatus::Failed;
+                }
+                r.error = Some(e.to_string());
+            });
+            Err(e)
+        }
+    }
+}
+
+/// Operator override: releases a withheld or failed refund for sending.
+pub fn release_refund(original_tx: &str) -> Result<RefundRecord, XRPLError> {
+    let record = get_refund(original_tx).ok_or_else(|| XRPLError::Other(format!("Unknown refund {}", original_tx)))?;
+    if !matches!(record.status, RefundStatus::Withheld | RefundStatus::Failed) || record.refund_drops == 0 {
+        return Err(XRPLError::InvalidTransaction(format!("Refund {} is {:?}", original_tx, record.status)));
+    }
+    update_refund(original_tx, |r| r.status = RefundStatus::Pending)
+        .ok_or_else(|| XRPLError::Other(format!("Unknown refund {}", original_tx)))
+}
+
+pub fn get_refund(original_tx: &str) -> Option<RefundRecord> {
+    REFUND_BOOK.read().unwrap().refunds.get(original_tx).cloned()
+}
+
+pub fn list_refunds() -> Vec<RefundRecord> {
+    REFUND_BOOK.read().unwrap().refunds.values().cloned().collect()
+}
+
+pub async fn run_refund_processing(rpc: XRPLRpcClient, interval_secs: u64) {
+    loop {
+        let pending = if outbound_paused() { Vec::new() } else { list_refunds() };
+        for record in pending.into_iter().filter(|r| r.status == RefundStatus::Pending) {
+            if let Err(e) = send_refund(&rpc, &record.original_tx).await {
+                bridge_log_event("error", format!("❌ Refund for {} failed: {}", record.original_tx, e));
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
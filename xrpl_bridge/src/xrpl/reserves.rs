@@ -0,0 +1,304 @@
+use std::sync::RwLock;
+
+use candid::{CandidType, Nat};
+use ic_agent::identity::BasicIdentity;
+use ic_agent::{Agent, Identity};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::{
+    get_bridge_address, get_iou_enabled, get_reserves_publish, get_reserves_publish_method, get_reserves_signing_pem,
+    get_reserves_tolerance_bps, BridgeConfig,
+};
+use crate::ic_trigger::{fetch_bridged_token_supply, publish_reserve_attestation};
+use crate::icrc_ledger::IcrcLedger;
+use crate::log::bridge_log_event;
+use crate::monitor::record_error;
+use crate::state::db::{append_jsonl_record, load_json_state, persist_json_state};
+use crate::xrpl::issuer::supply_report;
+use crate::xrpl::memo::generate_uuid;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::XRPLError;
+use crate::util::{now_secs, to_hex};
+
+const RESERVES_STATE_FILE: &str = "reserves.json";
+const RESERVES_HISTORY_FILE: &str = "reserves.jsonl";
+
+/// One asset's backing against what it backs. XRP: spendable bridge XRP against
+/// wXRP supply. Bridge-issued IOU: ICP-locked tokens against XRPL obligations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReserveLine {
+    pub asset: String,
+    /// `xrpl` or `icp`: where the backing is held.
+    pub backing_side: String,
+    pub backing_units: Option<u128>,
+    pub liability_units: Option<u128>,
+    pub shortfall_units: u128,
+    pub surplus_units: u128,
+    /// Shortfall within `tolerance_bps` of the liability; false when a side couldn't be read.
+    pub within_tolerance: bool,
+}
+
+/// An IOU the bridge account holds from another issuer; reported, not matched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IouHolding {
+    pub currency: String,
+    pub issuer: String,
+    pub balance: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReserveReport {
+    pub report_id: String,
+    pub generated_at: u64,
+    pub ledger_index: u32,
+    pub bridge_account: String,
+    pub balance_drops: u64,
+    /// Base reserve plus one increment per owned object; not spendable.
+    pub account_reserve_drops: u64,
+    pub lines: Vec<ReserveLine>,
+    pub holdings: Vec<IouHolding>,
+    pub tolerance_bps: u64,
+    pub healthy: bool,
+    /// Sides that couldn't be read this run.
+    pub errors: Vec<String>,
+}
+
+/// A report with its Ed25519 signature over the SHA-256 of the report's JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReserveAttestation {
+    pub report: ReserveReport,
+    pub digest: String,
+    pub signer: Option<String>,
+    /// DER-encoded, hex.
+    pub public_key: Option<String>,
+    pub signature: Option<String>,
+    pub sign_error: Option<String>,
+    pub published: bool,
+    pub publish_error: Option<String>,
+}
+
+/// What the bridge publishes to the `namora_ai` or `treasury` canister.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct PublishedAttestation {
+    /// Exact bytes the digest was taken over.
+    #[serde(rename = "reportJson")]
+    pub report_json: String,
+    pub digest: String,
+    pub signer: String,
+    #[serde(rename = "publicKey")]
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub healthy: bool,
+    #[serde(rename = "generatedAt")]
+    pub generated_at: u64,
+}
+
+static LATEST_ATTESTATION: Lazy<RwLock<Option<ReserveAttestation>>> = Lazy::new(|| {
+    let latest = load_json_state::<ReserveAttestation>(RESERVES_STATE_FILE).unwrap_or_else(|e| {
+        bridge_log_event("error", format!("Could not load reserve attestation: {:?}", e));
+        None
+    });
+    RwLock::new(latest)
+});
+
+fn nat_to_u128(n: &Nat) -> Option<u128> {
+    u128::try_from(&n.0).ok()
+}
+
+pub fn latest_attestation() -> Option<ReserveAttestation> {
+    LATEST_ATTESTATION.read().unwrap().clone()
+}
+
+fn reserve_line(asset: &str, backing_side: &str, backing: Option<u128>, liability: Option<u128>, tolerance_bps: u64) -> ReserveLine {
+    let (shortfall_units, surplus_units, within_tolerance) = match (backing, liability) {
+        (Some(backing), Some(liability)) => {
+            let shortfall = liability.saturating_sub(backing);
+            let allowed = liability.saturating_mul(tolerance_bps as u128) / 10_000;
+            (shortfall, backing.saturating_sub(liability), shortfall <= allowed)
+        }
+        _ => (0, 0, false),
+    };
+    ReserveLine {
+        asset: asset.to_string(),
+        backing_side: backing_side.to_string(),
+        backing_units: backing,
+        liability_units: liability,
+        shortfall_units,
+        surplus_units,
+        within_tolerance,
+    }
+}
+
+/// Reads both sides and builds an unsigned report. XRPL failures abort; an
+/// unreadable ICP side is recorded in `errors` and makes the report unhealthy.
+pub async fn build_reserve_report(agent: &Agent, config: &BridgeConfig, rpc: &XRPLRpcClient) -> Result<ReserveReport, XRPLError> {
+    let account = get_bridge_address().ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))?;
+    let tolerance_bps = get_reserves_tolerance_bps();
+    let mut errors = Vec::new();
+
+    let info = rpc.account_info(&account).await?;
+    let balance_drops = info["Balance"]
+        .as_str()
+        .and_then(|b| b.parse::<u64>().ok())
+        .ok_or_else(|| XRPLError::InvalidResponse("account_info: missing Balance".into()))?;
+    let owner_count = info["OwnerCount"].as_u64().unwrap_or(0);
+    let (reserve_base, reserve_inc) = rpc.reserve_requirements().await?;
+    let account_reserve_drops = reserve_base.saturating_add(owner_count.saturating_mul(reserve_inc));
+    let ledger_index = rpc.current_ledger_index().await?;
+
+    // Surplus XRP is expected (tips and sale proceeds stay with the bridge); only a shortfall alerts.
+    let wxrp_supply = match IcrcLedger::new(agent, &config.wxrp_ledger_canister_id) {
+        Ok(ledger) => ledger.icrc1_total_supply().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+    .and_then(|n| nat_to_u128(&n).ok_or_else(|| format!("supply {} out of range", n)))
+    .map_err(|e| errors.push(format!("wXRP supply: {}", e)))
+    .ok();
+    let spendable = balance_drops.saturating_sub(account_reserve_drops) as u128;
+    let mut lines = vec![reserve_line("XRP", "xrpl", Some(spendable), wxrp_supply, tolerance_bps)];
+
+    if get_iou_enabled() {
+        let locked = fetch_bridged_token_supply(agent, config)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|n| nat_to_u128(&n).ok_or_else(|| format!("locked {} out of range", n)))
+            .map_err(|e| errors.push(format!("ICP locked supply: {}", e)))
+            .ok();
+        let supply = supply_report(rpc, locked).await?;
+        lines.push(reserve_line(&supply.currency, "icp", locked, Some(supply.xrpl_obligation_units), tolerance_bps));
+    }
+
+    // From the bridge's side, a positive line balance is an IOU it holds; negative is one it issued.
+    let holdings = rpc
+        .account_lines(&account, None)
+        .await?
+        .iter()
+        .filter(|line| line["balance"].as_str().and_then(|b| b.parse::<f64>().ok()).is_some_and(|b| b > 0.0))
+        .map(|line| IouHolding {
+            currency: line["currency"].as_str().unwrap_or_default().to_string(),
+            issuer: line["account"].as_str().unwrap_or_default().to_string(),
+            balance: line["balance"].as_str().unwrap_or_default().to_string(),
+        })
+        .collect();
+
+    let healthy = errors.is_empty() && lines.iter().all(|line| line.within_tolerance);
+    Ok(ReserveReport {
+        report_id: generate_uuid(),
+        generated_at: now_secs(),
+        ledger_index,
+        bridge_account: account,
+        balance_drops,
+        account_reserve_drops,
+        lines,
+        holdings,
+        tolerance_bps,
+        healthy,
+        errors,
+    })
+}
+
+/// Signs the SHA-256 of `report_json` with the identity in `RESERVES_SIGNING_PEM`.
+fn sign_report(report_json: &[u8]) -> Result<(String, Vec<u8>, Vec<u8>), String> {
+    let pem = get_reserves_signing_pem();
+    let identity = BasicIdentity::from_pem_file(&pem).map_err(|e| format!("{}: {}", pem, e))?;
+    let signer = identity.sender()?;
+    let signed = identity.sign_arbitrary(&Sha256::digest(report_json))?;
+    match (signed.public_key, signed.signature) {
+        (Some(public_key), Some(signature)) => Ok((signer.to_text(), public_key, signature)),
+        _ => Err("identity returned no signature".to_string()),
+    }
+}
+
+/// Builds, signs, records and (if enabled) publishes one attestation, alerting on a shortfall.
+pub async fn attest_reserves(agent: &Agent, config: &BridgeConfig, rpc: &XRPLRpcClient) -> Result<ReserveAttestation, XRPLError> {
+    let report = build_reserve_report(agent, config, rpc).await?;
+    let report_json = serde_json::to_string(&report).map_err(|e| XRPLError::Other(e.to_string()))?;
+    let digest = to_hex(&Sha256::digest(report_json.as_bytes()));
+
+    let mut attestation = ReserveAttestation {
+        report,
+        digest,
+        signer: None,
+        public_key: None,
+        signature: None,
+        sign_error: None,
+        published: false,
+        publish_error: None,
+    };
+    match sign_report(report_json.as_bytes()) {
+        Ok((signer, public_key, signature)) => {
+            attestation.signer = Some(signer.clone());
+            attestation.public_key = Some(to_hex(&public_key));
+            attestation.signature = Some(to_hex(&signature));
+
+            if get_reserves_publish() {
+                let published = PublishedAttestation {
+                    report_json,
+                    digest: attestation.digest.clone(),
+                    signer,
+                    public_key,
+                    signature,
+                    healthy: attestation.report.healthy,
+                    generated_at: attestation.report.generated_at,
+                };
+                match publish_reserve_attestation(agent, config, &get_reserves_publish_method(), &published).await {
+                    Ok(()) => attestation.published = true,
+                    Err(e) => attestation.publish_error = Some(e.to_string()),
+                }
+            }
+        }
+        Err(e) => {
+            bridge_log_event("error", format!("❌ Could not sign reserve report: {}", e));
+            attestation.sign_error = Some(e);
+        }
+    }
+
+    let report = &attestation.report;
+    for line in report.lines.iter().filter(|line| line.backing_units.is_some() && !line.within_tolerance) {
+        let alert = format!(
+            "🚨 {} reserves short by {} units ({} backing vs {} liability, tolerance {} bps)",
+            line.asset,
+            line.shortfall_units,
+            line.backing_units.unwrap_or_default(),
+            line.liability_units.unwrap_or_default(),
+            report.tolerance_bps,
+        );
+        bridge_log_event("error", alert.clone());
+        record_error(&alert);
+    }
+    if !report.errors.is_empty() {
+        bridge_log_event("warn", format!("⚠️ Reserve report {} incomplete: {}", report.report_id, report.errors.join("; ")));
+    }
+    if let Some(e) = &attestation.publish_error {
+        bridge_log_event("error", format!("❌ Could not publish reserve attestation: {}", e));
+    }
+    bridge_log_event(
+        "reserves",
+        format!(
+            "🧾 Reserve attestation {} at ledger {} ({})",
+            report.report_id,
+            report.ledger_index,
+            if report.healthy { "healthy" } else { "unhealthy" }
+        ),
+    );
+
+    if let Err(e) = append_jsonl_record(RESERVES_HISTORY_FILE, &attestation) {
+        bridge_log_event("error", format!("Failed to write reserve attestation: {:?}", e));
+    }
+    if let Err(e) = persist_json_state(RESERVES_STATE_FILE, &attestation) {
+        bridge_log_event("error", format!("Failed to persist reserve attestation: {:?}", e));
+    }
+    *LATEST_ATTESTATION.write().unwrap() = Some(attestation.clone());
+    Ok(attestation)
+}
+
+pub async fn run_reserve_attestation(agent: Agent, config: BridgeConfig, rpc: XRPLRpcClient, interval_secs: u64) {
+    loop {
+        if let Err(e) = attest_reserves(&agent, &config, &rpc).await {
+            bridge_log_event("error", format!("❌ Reserve attestation failed: {}", e));
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
@@ -0,0 +1,397 @@
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::config::get_xrpl_rpc_url;
+use crate::xrpl::types::XRPLError;
+
+/// True if the URL's host is `localhost` or a loopback address.
+fn is_loopback_url(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    match parsed.host_str().map(|h| h.trim_start_matches('[').trim_end_matches(']')) {
+        Some(host) if host.eq_ignore_ascii_case("localhost") => true,
+        Some(host) => host.parse::<std::net::IpAddr>().map(|ip| ip.is_loopback()).unwrap_or(false),
+        None => false,
+    }
+}
+
+/// A signed transaction blob ready for submission.
+#[derive(Debug, Clone)]
+pub struct SignedXRPLTx {
+    pub tx_blob: String,
+    pub hash: String,
+    pub tx_json: Value,
+}
+
+/// Thin JSON-RPC client for a rippled / Clio node.
+/// Used for everything the WebSocket stream can't do: account queries, signing and submission.
+#[derive(Debug, Clone)]
+pub struct XRPLRpcClient {
+    url: String,
+    http: Client,
+}
+
+impl XRPLRpcClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            http: Client::new(),
+        }
+    }
+
+    /// Builds a client against `XRPL_RPC_URL`.
+    pub fn from_env() -> Self {
+        Self::new(&get_xrpl_rpc_url())
+    }
+
+    /// Sends one JSON-RPC request and returns the `result` object.
+    /// rippled reports errors inside `result`, so those are mapped to `XRPLError` here.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, XRPLError> {
+        let body = json!({
+            "method": method,
+            "params": [params],
+        });
+
+        let resp = self
+            .http
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| XRPLError::HttpRequestFailed(format!("{} failed: {}", method, e)))?;
+
+        if !resp.status().is_success() {
+            return Err(XRPLError::HttpRequestFailed(format!(
+                "{} returned status {}",
+                method,
+                resp.status()
+            )));
+        }
+
+        let mut json = resp.json::<Value>().await?;
+        let result = json
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| XRPLError::InvalidResponse(format!("{}: missing 'result'", method)))?;
+
+        if result["status"] == "error" {
+            let code = result["error"].as_str().unwrap_or("unknown");
+            let message = result["error_message"].as_str().unwrap_or("");
+            return Err(match code {
+                "txnNotFound" => XRPLError::TransactionNotFound(message.to_string()),
+                "actNotFound" => XRPLError::InvalidResponse(format!("account not found: {}", message)),
+                _ => XRPLError::InvalidResponse(format!("{}: {} {}", method, code, message)),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the `account_data` object for a validated account.
+    pub async fn account_info(&self, account: &str) -> Result<Value, XRPLError> {
+        let result = self
+            .request("account_info", json!({ "account": account, "ledger_index": "current" }))
+            .await?;
+
+        result
+            .get("account_data")
+            .cloned()
+            .ok_or_else(|| XRPLError::InvalidResponse("account_info: missing account_data".into()))
+    }
+
+    /// Returns all ledger objects of `object_type` owned by `account`, following pagination markers.
+    pub async fn account_objects(&self, account: &str, object_type: &str) -> Result<Vec<Value>, XRPLError> {
+        let mut objects = Vec::new();
+        let mut marker: Option<Value> = None;
+
+        loop {
+            let mut params = json!({
+                "account": account,
+                "type": object_type,
+                "ledger_index": "validated",
+                "limit": 400,
+            });
+            if let Some(m) = marker.take() {
+                params["marker"] = m;
+            }
+
+            let result = self.request("account_objects", params).await?;
+            if let Some(page) = result["account_objects"].as_array() {
+                objects.extend(page.iter().cloned());
+            }
+
+            match result.get("marker") {
+                Some(m) if !m.is_null() => marker = Some(m.clone()),
+                _ => break,
+            }
+        }
+
+        Ok(objects)
+    }
+
+    /// One page of `account`'s validated transactions in a ledger range (-1 leaves an end open),
+    /// newest first, and the marker for the next page.
+    pub async fn account_tx_page(
+        &self,
+        account: &str,
+        ledger_min: i64,
+        ledger_max: i64,
+        marker: Option<Value>,
+    ) -> Result<(Vec<Value>, Option<Value>), XRPLError> {
+        let mut params = json!({
+            "account": account,
+            "ledger_index_min": ledger_min,
+            "ledger_index_max": ledger_max,
+            "limit": 200,
+            "forward": false,
+        });
+        if let Some(m) = marker {
+            params["marker"] = m;
+        }
+
+        let result = self.request("account_tx", params).await?;
+        let page = result["transactions"].as_array().cloned().unwrap_or_default();
+        let next = result.get("marker").filter(|m| !m.is_null()).cloned();
+        Ok((page, next))
+    }
+
+    /// Returns every NFToken held by `account`, following pagination markers.
+    pub async fn account_nfts(&self, account: &str) -> Result<Vec<Value>, XRPLError> {
+        let mut nfts = Vec::new();
+        let mut marker: Option<Value> = None;
+
+        loop {
+            let mut params = json!({
+                "account": account,
+                "ledger_index": "validated",
+                "limit": 400,
+            });
+            if let Some(m) = marker.take() {
+                params["marker"] = m;
+            }
+
+            let result = self.request("account_nfts", params).await?;
+            if let Some(page) = result["account_nfts"].as_array() {
+                nfts.extend(page.iter().cloned());
+            }
+
+            match result.get("marker") {
+                Some(m) if !m.is_null() => marker = Some(m.clone()),
+                _ => break,
+            }
+        }
+
+        Ok(nfts)
+    }
+
+    /// Current owner and burn status of an NFToken (`nft_info`, served by Clio).
+    pub async fn nft_info(&self, nftoken_id: &str) -> Result<Value, XRPLError> {
+        self.request("nft_info", json!({ "nft_id": nftoken_id, "ledger_index": "validated" })).await
+    }
+
+    /// Trust lines of `account`, optionally only those with `peer`.
+    pub async fn account_lines(&self, account: &str, peer: Option<&str>) -> Result<Vec<Value>, XRPLError> {
+        let mut params = json!({ "account": account, "ledger_index": "validated" });
+        if let Some(peer) = peer {
+            params["peer"] = json!(peer);
+        }
+        let result = self.request("account_lines", params).await?;
+        Ok(result["lines"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Outstanding obligations of an issuing account, per currency.
+    pub async fn gateway_balances(&self, account: &str) -> Result<Value, XRPLError> {
+        self.request(
+            "gateway_balances",
+            json!({ "account": account, "ledger_index": "validated", "strict": true }),
+        )
+        .await
+    }
+
+    /// A single ledger object by type-specific selector, e.g. `{"payment_channel": id}`.
+    pub async fn ledger_entry(&self, selector: Value) -> Result<Value, XRPLError> {
+        let mut params = selector;
+        params["ledger_index"] = json!("validated");
+        let result = self.request("ledger_entry", params).await?;
+        Ok(result["node"].clone())
+    }
+
+    /// Checks an off-ledger payment channel claim signature (`channel_verify`).
+    pub async fn channel_verify(&self, channel_id: &str, amount_drops: u64, signature: &str, public_key: &str) -> Result<bool, XRPLError> {
+        let result = self
+            .request(
+                "channel_verify",
+                json!({
+                    "channel_id": channel_id,
+                    "amount": amount_drops.to_string(),
+                    "signature": signature,
+                    "public_key": public_key,
+                }),
+            )
+            .await?;
+        Ok(result["signature_verified"].as_bool().unwrap_or(false))
+    }
+
+    /// Order book for taking `taker_gets` in exchange for `taker_pays` (issue specs).
+    pub async fn book_offers(&self, taker_gets: &Value, taker_pays: &Value, taker: Option<&str>) -> Result<Vec<Value>, XRPLError> {
+        let mut params = json!({
+            "taker_gets": taker_gets,
+            "taker_pays": taker_pays,
+            "ledger_index": "current",
+            "limit": 50,
+        });
+        if let Some(taker) = taker {
+            params["taker"] = json!(taker);
+        }
+        let result = self.request("book_offers", params).await?;
+        Ok(result["offers"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// AMM pool for an asset pair; `None` when no pool exists.
+    pub async fn amm_info(&self, asset: &Value, asset2: &Value) -> Result<Option<Value>, XRPLError> {
+        match self
+            .request("amm_info", json!({ "asset": asset, "asset2": asset2, "ledger_index": "validated" }))
+            .await
+        {
+            Ok(result) => Ok(result.get("amm").cloned()),
+            Err(XRPLError::InvalidResponse(msg)) if msg.starts_with("account not found") => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// One-shot path search (`ripple_path_find`) for delivering `destination_amount`.
+    pub async fn ripple_path_find(
+        &self,
+        source: &str,
+        destination: &str,
+        destination_amount: &Value,
+        send_max: Option<&Value>,
+    ) -> Result<Vec<Value>, XRPLError> {
+        let mut params = json!({
+            "source_account": source,
+            "destination_account": destination,
+            "destination_amount": destination_amount,
+            "ledger_index": "current",
+        });
+        if let Some(send_max) = send_max {
+            params["send_max"] = send_max.clone();
+        }
+        let result = self.request("ripple_path_find", params).await?;
+        Ok(result["alternatives"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Index of the current open ledger (used for `LastLedgerSequence`).
+    pub async fn current_ledger_index(&self) -> Result<u32, XRPLError> {
+        let result = self.request("ledger_current", json!({})).await?;
+        result["ledger_current_index"]
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| XRPLError::InvalidResponse("ledger_current: missing index".into()))
+    }
+
+    /// Index of the most recent validated ledger.
+    pub async fn validated_ledger_index(&self) -> Result<u32, XRPLError> {
+        let result = self.request("ledger", json!({ "ledger_index": "validated" })).await?;
+        result["ledger_index"]
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| XRPLError::InvalidResponse("ledger: missing validated index".into()))
+    }
+
+    /// Base and per-object account reserve, in drops, from the last validated ledger.
+    pub async fn reserve_requirements(&self) -> Result<(u64, u64), XRPLError> {
+        let result = self.request("server_state", json!({})).await?;
+        let ledger = &result["state"]["validated_ledger"];
+        match (ledger["reserve_base"].as_u64(), ledger["reserve_inc"].as_u64()) {
+            (Some(base), Some(inc)) => Ok((base, inc)),
+            _ => Err(XRPLError::InvalidResponse("server_state: missing reserves".into())),
+        }
+    }
+
+    /// Secrets must never leave the host, so signing RPCs only go to a loopback node.
+    fn ensure_local_signer(&self) -> Result<(), XRPLError> {
+        if is_loopback_url(&self.url) {
+            Ok(())
+        } else {
+            Err(XRPLError::Other(format!(
+                "Refusing to send a signing secret to non-local node {}; point XRPL_RPC_URL at a local rippled",
+                self.url
+            )))
+        }
+    }
+
+    /// Signs a transaction on the node with the given secret (local nodes only).
+    pub async fn sign(&self, tx_json: &Value, secret: &str) -> Result<SignedXRPLTx, XRPLError> {
+        self.ensure_local_signer()?;
+        let result = self
+            .request(
+                "sign",
+                json!({ "tx_json": tx_json, "secret": secret, "offline": false }),
+            )
+            .await?;
+
+        Self::signed_from_result(&result)
+    }
+
+    /// Adds one multisig signature for `account` to a transaction (`sign_for`).
+    /// Run by co-signer processes against their own node.
+    pub async fn sign_for(&self, tx_json: &Value, account: &str, secret: &str) -> Result<SignedXRPLTx, XRPLError> {
+        self.ensure_local_signer()?;
+        let result = self
+            .request(
+                "sign_for",
+                json!({ "tx_json": tx_json, "account": account, "secret": secret }),
+            )
+            .await?;
+
+        Self::signed_from_result(&result)
+    }
+
+    /// Submits a transaction carrying a complete `Signers` array.
+    /// rippled sorts the signers and checks each signature before relaying.
+    pub async fn submit_multisigned(&self, tx_json: &Value) -> Result<Value, XRPLError> {
+        self.request("submit_multisigned", json!({ "tx_json": tx_json })).await
+    }
+
+    /// Submits a signed blob and returns the raw submit result (`engine_result`, etc.).
+    pub async fn submit_blob(&self, tx_blob: &str) -> Result<Value, XRPLError> {
+        self.request("submit", json!({ "tx_blob": tx_blob })).await
+    }
+
+    /// Looks up a transaction by hash.
+    pub async fn tx(&self, hash: &str) -> Result<Value, XRPLError> {
+        self.request("tx", json!({ "transaction": hash, "binary": false })).await
+    }
+
+    fn signed_from_result(result: &Value) -> Result<SignedXRPLTx, XRPLError> {
+        let tx_blob = result["tx_blob"]
+            .as_str()
+            .ok_or_else(|| XRPLError::InvalidResponse("sign: missing tx_blob".into()))?;
+        let tx_json = result["tx_json"].clone();
+        let hash = tx_json["hash"]
+            .as_str()
+            .ok_or_else(|| XRPLError::InvalidResponse("sign: missing hash".into()))?;
+
+        Ok(SignedXRPLTx {
+            tx_blob: tx_blob.to_string(),
+            hash: hash.to_string(),
+            tx_json,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_loopback_nodes_may_sign() {
+        assert!(is_loopback_url("http://127.0.0.1:5005"));
+        assert!(is_loopback_url("http://localhost:5005"));
+        assert!(is_loopback_url("http://[::1]:5005"));
+        assert!(!is_loopback_url("https://s.altnet.rippletest.net:51234"));
+        assert!(!is_loopback_url("http://10.0.0.5:5005"));
+        assert!(!is_loopback_url("not a url"));
+    }
+}
@@ -0,0 +1,559 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::RwLock;
+use std::time::SystemTime;
+
+use candid::CandidType;
+use ic_agent::Agent;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{get_bridge_address, get_screening_allow_list, get_screening_deny_list, get_screening_use_canister, BridgeConfig};
+use crate::ic_trigger::screen_xrpl_accounts;
+use crate::log::bridge_log_event;
+use crate::state::db::{append_jsonl_record, load_json_state, persist_json_state};
+use crate::xrpl::address::{decode_x_address, is_x_address};
+use crate::xrpl::approvals::flag_for_approval;
+use crate::xrpl::ingest::route_stream_transaction;
+use crate::xrpl::types::XRPLError;
+use crate::util::now_secs;
+
+const QUARANTINE_BOOK_FILE: &str = "quarantine.json";
+const SCREENING_AUDIT_FILE: &str = "screening_audit.jsonl";
+
+/// Sanctions status as reported by the compliance canister.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, CandidType)]
+pub enum SanctionsStatus {
+    Clear,
+    Flagged,
+    #[serde(rename = "Under_Review")]
+    UnderReview,
+    Blocked,
+}
+
+/// How an account takes part in a screened transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PartyRole {
+    Sender,
+    Destination,
+    /// Rippled through, or an offer/trust line touched on the way.
+    Path,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchSource {
+    DenyList,
+    Canister(SanctionsStatus),
+    /// Canister screening is on but couldn't be completed; held rather than credited unscreened.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreeningMatch {
+    pub account: String,
+    pub role: PartyRole,
+    pub source: MatchSource,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuarantineStatus {
+    Held,
+    /// Cleared by a reviewer and routed as usual.
+    Released,
+    /// Refused by a reviewer; never dispatched.
+    Rejected,
+}
+
+/// A transaction held back from dispatch because a counterparty matched screening.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub tx_hash: String,
+    pub tx_type: String,
+    pub sender: String,
+    pub matches: Vec<ScreeningMatch>,
+    pub status: QuarantineStatus,
+    /// The original stream message, routed unchanged on release; `null` for off-ledger
+    /// credits (channel claims), which are re-presented by the claimant instead.
+    pub message: Value,
+    pub reviewer: Option<String>,
+    pub review_note: Option<String>,
+    pub created_at: u64,
+    pub reviewed_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScreeningAuditRecord {
+    tx_hash: String,
+    event: String,
+    matches: Vec<ScreeningMatch>,
+    reviewer: Option<String>,
+    note: Option<String>,
+    at: u64,
+}
+
+/// What the ingester should do with a transaction after screening.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreeningOutcome {
+    Clear,
+    Quarantined,
+    /// Waiting on the compliance canister; routed (or quarantined) when it answers.
+    Deferred,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct QuarantineBook {
+    records: BTreeMap<String, QuarantineRecord>,
+}
+
+static QUARANTINE_BOOK: Lazy<RwLock<QuarantineBook>> = Lazy::new(|| {
+    let book = load_json_state::<QuarantineBook>(QUARANTINE_BOOK_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load quarantine book: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(book)
+});
+
+/// A deny or allow list, reloaded whenever its file changes.
+#[derive(Debug, Clone, Default)]
+struct ScreeningList {
+    path: Option<String>,
+    modified: Option<SystemTime>,
+    /// Classic address → reason.
+    entries: HashMap<String, String>,
+    loaded_at: u64,
+}
+
+#[derive(Debug, Default)]
+struct ScreeningLists {
+    deny: ScreeningList,
+    allow: ScreeningList,
+}
+
+static SCREENING_LISTS: Lazy<RwLock<ScreeningLists>> = Lazy::new(|| RwLock::new(ScreeningLists::default()));
+
+/// IC agent for compliance canister calls, set once the core loop has built it.
+static SCREENING_AGENT: OnceCell<(Agent, BridgeConfig)> = OnceCell::new();
+
+pub fn set_screening_agent(agent: Agent, config: BridgeConfig) {
+    let _ = SCREENING_AGENT.set((agent, config));
+}
+
+fn persist(book: &QuarantineBook) {
+    if let Err(e) = persist_json_state(QUARANTINE_BOOK_FILE, book) {
+        bridge_log_event("error", format!("Failed to persist quarantine book: {:?}", e));
+    }
+}
+
+fn audit(tx_hash: &str, event: &str, matches: &[ScreeningMatch], reviewer: Option<String>, note: Option<String>) {
+    let record = ScreeningAuditRecord {
+        tx_hash: tx_hash.to_string(),
+        event: event.to_string(),
+        matches: matches.to_vec(),
+        reviewer,
+        note,
+        at: now_secs(),
+    };
+    if let Err(e) = append_jsonl_record(SCREENING_AUDIT_FILE, &record) {
+        bridge_log_event("error", format!("Failed to write screening audit record: {:?}", e));
+    }
+}
+
+/// List entries as classic addresses; X-addresses are reduced to their account.
+fn normalize_entry(address: &str) -> Option<String> {
+    let address = address.trim().trim_matches('"');
+    if address.is_empty() || address.eq_ignore_ascii_case("address") {
+        return None;
+    }
+    if is_x_address(address) {
+        return decode_x_address(address).ok().map(|d| d.classic);
+    }
+    Some(address.to_string())
+}
+
+/// Parses a list file: a JSON array of addresses or `{address, reason}` objects,
+/// or CSV with the address in the first column and an optional reason in the second.
+fn parse_list(path: &str, raw: &str) -> Result<HashMap<String, String>, String> {
+    let mut entries = HashMap::new();
+
+    if path.ends_with(".json") {
+        let items: Vec<Value> = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+        for item in items {
+            let (address, reason) = match &item {
+                Value::String(address) => (address.as_str(), "listed"),
+                Value::Object(_) => (
+                    item["address"].as_str().ok_or("list entry without address")?,
+                    item["reason"].as_str().unwrap_or("listed"),
+                ),
+                _ => return Err(format!("unexpected list entry {}", item)),
+            };
+            if let Some(address) = normalize_entry(address) {
+                entries.insert(address, reason.to_string());
+            }
+        }
+    } else {
+        for line in raw.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let mut fields = line.splitn(2, ',');
+            let Some(address) = fields.next().and_then(normalize_entry) else {
+                continue;
+            };
+            let reason = fields
+                .next()
+                .map(|r| r.trim().trim_matches('"'))
+                .filter(|r| !r.is_empty())
+                .unwrap_or("listed");
+            entries.insert(address, reason.to_string());
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Reloads `list` if its configured path or the file's mtime changed. A file that
+/// fails to parse leaves the previous entries in place.
+fn refresh_list(list: &mut ScreeningList, path: Option<String>, name: &str, force: bool) {
+    let Some(path) = path else {
+        *list = ScreeningList::default();
+        return;
+    };
+
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if !force && list.path.as_deref() == Some(path.as_str()) && list.modified == modified {
+        return;
+    }
+
+    match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|raw| parse_list(&path, &raw)) {
+        Ok(entries) => {
+            bridge_log_event("screening", format!("📋 Loaded {} {} list entries from {}", entries.len(), name, path));
+            list.entries = entries;
+            list.loaded_at = now_secs();
+        }
+        Err(e) => bridge_log_event("error", format!("❌ Could not load {} list {}: {}", name, path, e)),
+    }
+    list.path = Some(path);
+    list.modified = modified;
+}
+
+fn refresh_lists(force: bool) {
+    let mut lists = SCREENING_LISTS.write().unwrap();
+    refresh_list(&mut lists.deny, get_screening_deny_list(), "deny", force);
+    refresh_list(&mut lists.allow, get_screening_allow_list(), "allow", force);
+}
+
+/// True if the account is on the deny list (re-read if its file changed).
+pub fn is_deny_listed(account: &str) -> bool {
+    refresh_lists(false);
+    SCREENING_LISTS.read().unwrap().deny.entries.contains_key(account)
+}
+
+/// Re-reads both list files now.
+pub fn reload_screening_lists() -> Value {
+    refresh_lists(true);
+    screening_summary()
+}
+
+pub fn screening_summary() -> Value {
+    let lists = SCREENING_LISTS.read().unwrap();
+    let held = QUARANTINE_BOOK
+        .read()
+        .unwrap()
+        .records
+        .values()
+        .filter(|r| r.status == QuarantineStatus::Held)
+        .count();
+    serde_json::json!({
+        "deny_list": { "path": lists.deny.path, "entries": lists.deny.entries.len(), "loaded_at": lists.deny.loaded_at },
+        "allow_list": { "path": lists.allow.path, "entries": lists.allow.entries.len(), "loaded_at": lists.allow.loaded_at },
+        "use_canister": get_screening_use_canister(),
+        "held": held,
+    })
+}
+
+/// Every account other than the bridge that the transaction names or moved value through.
+fn counterparties(tx: &Value, meta: &Value) -> Vec<(String, PartyRole)> {
+    let bridge = get_bridge_address().unwrap_or_default();
+    let mut parties: Vec<(String, PartyRole)> = Vec::new();
+    let mut add = |account: Option<&str>, role: PartyRole| {
+        if let Some(account) = account.filter(|a| !a.is_empty() && *a != bridge) {
+            if !parties.iter().any(|(a, _)| a == account) {
+                parties.push((account.to_string(), role));
+            }
+        }
+    };
+
+    add(tx["Account"].as_str(), PartyRole::Sender);
+    add(tx["Destination"].as_str(), PartyRole::Destination);
+
+    for step in tx["Paths"].as_array().into_iter().flatten().filter_map(Value::as_array).flatten() {
+        add(step["account"].as_str(), PartyRole::Path);
+        add(step["issuer"].as_str(), PartyRole::Path);
+    }
+
+    for node in meta["AffectedNodes"].as_array().into_iter().flatten() {
+        let Some(inner) = node.as_object().and_then(|o| o.values().next()) else {
+            continue;
+        };
+        let fields = if inner["FinalFields"].is_object() { &inner["FinalFields"] } else { &inner["NewFields"] };
+        add(fields["Account"].as_str(), PartyRole::Path);
+        add(fields["Owner"].as_str(), PartyRole::Path);
+        add(fields["HighLimit"]["issuer"].as_str(), PartyRole::Path);
+        add(fields["LowLimit"]["issuer"].as_str(), PartyRole::Path);
+    }
+
+    parties
+}
+
+fn quarantine(tx_hash: &str, tx: &Value, msg: &Value, matches: Vec<ScreeningMatch>) {
+    let tx_type = tx["TransactionType"].as_str().unwrap_or_default();
+    let sender = tx["Account"].as_str().unwrap_or_default();
+    hold(tx_hash, tx_type, sender, msg, matches);
+}
+
+fn hold(tx_hash: &str, tx_type: &str, sender: &str, msg: &Value, matches: Vec<ScreeningMatch>) {
+    let record = QuarantineRecord {
+        tx_hash: tx_hash.to_string(),
+        tx_type: tx_type.to_string(),
+        sender: sender.to_string(),
+        matches,
+        status: QuarantineStatus::Held,
+        message: msg.clone(),
+        reviewer: None,
+        review_note: None,
+        created_at: now_secs(),
+        reviewed_at: None,
+    };
+
+    {
+        let mut book = QUARANTINE_BOOK.write().unwrap();
+        if book.records.contains_key(tx_hash) {
+            return;
+        }
+        book.records.insert(tx_hash.to_string(), record.clone());
+        persist(&book);
+    }
+
+    audit(tx_hash, "quarantined", &record.matches, None, None);
+    bridge_log_event(
+        "screening",
+        format!("🚫 Quarantined {} from {}: {} screening match(es)", tx_hash, record.sender, record.matches.len()),
+    );
+}
+
+/// Screens an inbound transaction's sender, destination and path-through accounts.
+/// Deny-list hits are quarantined at once; with canister screening on, the remaining
+/// non-allow-listed accounts are checked asynchronously and the message routed afterwards.
+pub fn screen_transaction(tx_hash: &str, tx: &Value, meta: &Value, msg: &Value) -> ScreeningOutcome {
+    // Our own submissions aren't inbound credits.
+    if get_bridge_address().is_some_and(|bridge| tx["Account"].as_str() == Some(bridge.as_str())) {
+        return ScreeningOutcome::Clear;
+    }
+    // Re-delivered stream message for something already held.
+    if QUARANTINE_BOOK.read().unwrap().records.contains_key(tx_hash) {
+        return ScreeningOutcome::Quarantined;
+    }
+
+    refresh_lists(false);
+    let parties = counterparties(tx, meta);
+
+    let (matches, unscreened) = {
+        let lists = SCREENING_LISTS.read().unwrap();
+        let matches: Vec<ScreeningMatch> = parties
+            .iter()
+            .filter_map(|(account, role)| {
+                lists.deny.entries.get(account).map(|reason| ScreeningMatch {
+                    account: account.clone(),
+                    role: *role,
+                    source: MatchSource::DenyList,
+                    reason: reason.clone(),
+                })
+            })
+            .collect();
+        let unscreened: Vec<(String, PartyRole)> = parties
+            .iter()
+            .filter(|(account, _)| !lists.allow.entries.contains_key(account))
+            .cloned()
+            .collect();
+        (matches, unscreened)
+    };
+
+    if !matches.is_empty() {
+        quarantine(tx_hash, tx, msg, matches);
+        return ScreeningOutcome::Quarantined;
+    }
+    if !get_screening_use_canister() || unscreened.is_empty() {
+        return ScreeningOutcome::Clear;
+    }
+
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        quarantine(tx_hash, tx, msg, unavailable(&unscreened, "no async runtime for canister screening"));
+        return ScreeningOutcome::Quarantined;
+    };
+    runtime.spawn(screen_with_canister(tx_hash.to_string(), msg.clone(), unscreened));
+    ScreeningOutcome::Deferred
+}
+
+/// Screens the account behind an off-ledger credit (a payment channel claim), which never
+/// reaches `screen_transaction`. Matches are quarantined under `reference` and the credit is
+/// refused until a reviewer releases it; afterwards credits under that reference pass.
+/// `Ok(Some(reason))` means the account is only flagged: admit the credit, held for approval.
+pub async fn screen_off_ledger_source(reference: &str, tx_type: &str, account: &str) -> Result<Option<String>, XRPLError> {
+    match QUARANTINE_BOOK.read().unwrap().records.get(reference).map(|r| r.status) {
+        Some(QuarantineStatus::Released) => return Ok(None),
+        Some(status) => return Err(XRPLError::InvalidTransaction(format!("{} is quarantined ({:?})", reference, status))),
+        None => {}
+    }
+    let refused = |matches: Vec<ScreeningMatch>| {
+        hold(reference, tx_type, account, &Value::Null, matches);
+        Err(XRPLError::InvalidTransaction(format!("{} from {} is quarantined by screening", reference, account)))
+    };
+
+    refresh_lists(false);
+    let parties = vec![(account.to_string(), PartyRole::Sender)];
+    let (denied, allowed) = {
+        let lists = SCREENING_LISTS.read().unwrap();
+        (lists.deny.entries.get(account).cloned(), lists.allow.entries.contains_key(account))
+    };
+    if let Some(reason) = denied {
+        return refused(vec![ScreeningMatch { account: account.to_string(), role: PartyRole::Sender, source: MatchSource::DenyList, reason }]);
+    }
+    if !get_screening_use_canister() || allowed {
+        return Ok(None);
+    }
+
+    let Some((agent, config)) = SCREENING_AGENT.get() else {
+        return refused(unavailable(&parties, "compliance canister not ready"));
+    };
+    let status = match screen_xrpl_accounts(agent, config, &[account.to_string()]).await {
+        Ok(statuses) => statuses.into_iter().find(|(a, _)| a == account).map(|(_, s)| s),
+        Err(e) => return refused(unavailable(&parties, &format!("compliance canister error: {}", e))),
+    };
+    match status {
+        Some(SanctionsStatus::Clear) => Ok(None),
+        Some(SanctionsStatus::Flagged) => Ok(Some(format!("compliance canister flagged {} Sender", account))),
+        Some(status) => refused(vec![ScreeningMatch {
+            account: account.to_string(),
+            role: PartyRole::Sender,
+            source: MatchSource::Canister(status),
+            reason: format!("compliance canister: {:?}", status),
+        }]),
+        None => refused(unavailable(&parties, "compliance canister returned no status")),
+    }
+}
+
+/// Holds the transaction against its sender when screening couldn't be completed.
+fn unavailable(parties: &[(String, PartyRole)], reason: &str) -> Vec<ScreeningMatch> {
+    let party = parties.iter().find(|(_, role)| *role == PartyRole::Sender).or(parties.first());
+    party
+        .map(|(account, role)| ScreeningMatch {
+            account: account.clone(),
+            role: *role,
+            source: MatchSource::Unavailable,
+            reason: reason.to_string(),
+        })
+        .into_iter()
+        .collect()
+}
+
+async fn screen_with_canister(tx_hash: String, msg: Value, parties: Vec<(String, PartyRole)>) {
+    let tx = if msg["tx_json"].is_object() { &msg["tx_json"] } else { &msg["transaction"] };
+
+    let Some((agent, config)) = SCREENING_AGENT.get() else {
+        quarantine(&tx_hash, tx, &msg, unavailable(&parties, "compliance canister not ready"));
+        return;
+    };
+
+    let accounts: Vec<String> = parties.iter().map(|(account, _)| account.clone()).collect();
+    let statuses = match screen_xrpl_accounts(agent, config, &accounts).await {
+        Ok(statuses) => statuses,
+        Err(e) => {
+            bridge_log_event("error", format!("❌ Compliance screening of {} failed: {}", tx_hash, e));
+            quarantine(&tx_hash, tx, &msg, unavailable(&parties, &format!("compliance canister error: {}", e)));
+            return;
+        }
+    };
+
+    let matches: Vec<ScreeningMatch> = parties
+        .iter()
+        .filter_map(|(account, role)| {
+            // An account the canister didn't answer for counts as unscreened.
+            let status = statuses.iter().find(|(a, _)| a == account).map(|(_, s)| *s);
+            let (source, reason) = match status {
+                Some(SanctionsStatus::Clear) => return None,
+                Some(status) => (MatchSource::Canister(status), format!("compliance canister: {:?}", status)),
+                None => (MatchSource::Unavailable, "compliance canister returned no status".to_string()),
+            };
+            Some(ScreeningMatch { account: account.clone(), role: *role, source, reason })
+        })
+        .collect();
+
+    // Flagged (not blocked or under review) parties don't quarantine the transaction;
+    // its action is held for approval instead.
+    let flagged_only = matches.iter().all(|m| m.source == MatchSource::Canister(SanctionsStatus::Flagged));
+    if matches.is_empty() {
+        route_stream_transaction(&msg);
+    } else if flagged_only {
+        let flag = matches.iter().map(|m| format!("{} {:?}", m.account, m.role)).collect::<Vec<_>>().join(", ");
+        audit(&tx_hash, "flagged", &matches, None, None);
+        flag_for_approval(&tx_hash, &format!("compliance canister flagged {}", flag));
+        route_stream_transaction(&msg);
+    } else {
+        quarantine(&tx_hash, tx, &msg, matches);
+    }
+}
+
+fn review(tx_hash: &str, status: QuarantineStatus, reviewer: &str, note: Option<String>) -> Result<QuarantineRecord, XRPLError> {
+    let record = {
+        let mut book = QUARANTINE_BOOK.write().unwrap();
+        let record = book
+            .records
+            .get_mut(tx_hash)
+            .ok_or_else(|| XRPLError::Other(format!("Nothing quarantined for {}", tx_hash)))?;
+        if record.status != QuarantineStatus::Held {
+            return Err(XRPLError::InvalidTransaction(format!("{} is already {:?}", tx_hash, record.status)));
+        }
+        record.status = status;
+        record.reviewer = Some(reviewer.to_string());
+        record.review_note = note.clone();
+        record.reviewed_at = Some(now_secs());
+        let record = record.clone();
+        persist(&book);
+        record
+    };
+
+    let event = if status == QuarantineStatus::Released { "released" } else { "rejected" };
+    audit(tx_hash, event, &record.matches, Some(reviewer.to_string()), note);
+    bridge_log_event("screening", format!("🧑‍⚖️ {} {} {}", reviewer, event, tx_hash));
+    Ok(record)
+}
+
+/// Reviewer clears a held transaction; it is routed exactly as if it had passed screening.
+pub fn release_quarantined(tx_hash: &str, reviewer: &str, note: Option<String>) -> Result<QuarantineRecord, XRPLError> {
+    let record = review(tx_hash, QuarantineStatus::Released, reviewer, note)?;
+    if !record.message.is_null() {
+        route_stream_transaction(&record.message);
+    }
+    Ok(record)
+}
+
+/// Reviewer refuses a held transaction; it is never dispatched or refunded by the bridge.
+pub fn reject_quarantined(tx_hash: &str, reviewer: &str, note: Option<String>) -> Result<QuarantineRecord, XRPLError> {
+    review(tx_hash, QuarantineStatus::Rejected, reviewer, note)
+}
+
+pub fn get_quarantined(tx_hash: &str) -> Option<QuarantineRecord> {
+    QUARANTINE_BOOK.read().unwrap().records.get(tx_hash).cloned()
+}
+
+pub fn list_quarantined(status: Option<QuarantineStatus>) -> Vec<QuarantineRecord> {
+    QUARANTINE_BOOK
+        .read()
+        .unwrap()
+        .records
+        .values()
+        .filter(|r| status.is_none_or(|s| r.status == s))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::{get_ticket_low_watermark, get_ticket_target};
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::XRPLError;
+use crate::util::now_secs;
+
+const SEQUENCE_STATE_FILE: &str = "sequence.json";
+
+/// A lease whose ticket is still on-ledger after this long belongs to a job that died;
+/// `LastLedgerSequence` guarantees its transaction can no longer validate.
+/// Must outlive the co-signing window of multisigned jobs (~300 ledgers).
+const LEASE_TIMEOUT_SECS: u64 = 1800;
+
+const TICKET_CREATE_JOB_PREFIX: &str = "ticket-create-";
+
+/// The sequencing slot an outbound transaction was given.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SequenceSlot {
+    Ticket(u32),
+    Sequence(u32),
+}
+
+impl SequenceSlot {
+    /// Writes the `Sequence` / `TicketSequence` fields into a transaction.
+    pub fn apply_to(&self, tx_json: &mut Value) {
+        match self {
+            SequenceSlot::Ticket(ticket) => {
+                tx_json["Sequence"] = json!(0);
+                tx_json["TicketSequence"] = json!(ticket);
+            }
+            SequenceSlot::Sequence(seq) => {
+                tx_json["Sequence"] = json!(seq);
+                if let Some(obj) = tx_json.as_object_mut() {
+                    obj.remove("TicketSequence");
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotLease {
+    pub slot: SequenceSlot,
+    pub leased_at: u64,
+}
+
+/// Persisted allocator state for the bridge wallet.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SequenceState {
+    pub account: Option<String>,
+    pub next_sequence: Option<u32>,
+    /// Plain sequences below a leased one that no job holds; handed out before anything
+    /// else, since nothing after a gap can validate.
+    #[serde(default)]
+    pub free_sequences: BTreeSet<u32>,
+    pub free_tickets: BTreeSet<u32>,
+    pub leases: BTreeMap<String, SlotLease>, // job_id → slot
+    pub ticket_create_pending: bool,
+}
+
+static SEQUENCE_STATE: Lazy<Mutex<SequenceState>> = Lazy::new(|| {
+    let state = match load_json_state::<SequenceState>(SEQUENCE_STATE_FILE) {
+        Ok(Some(state)) => state,
+        Ok(None) => SequenceState::default(),
+        Err(e) => {
+            bridge_log_event("warn", format!("Could not load sequence state: {:?}", e));
+            SequenceState::default()
+        }
+    };
+    Mutex::new(state)
+});
+
+fn persist(state: &SequenceState) {
+    if let Err(e) = persist_json_state(SEQUENCE_STATE_FILE, state) {
+        bridge_log_event("error", format!("Failed to persist sequence state: {:?}", e));
+    }
+}
+
+/// Hands out a ticket if one is free, otherwise falls back to the next plain sequence.
+pub fn allocate_slot(job_id: &str) -> Result<SequenceSlot, XRPLError> {
+    lease_slot(job_id, true)
+}
+
+/// Always hands out a plain sequence (e.g. for `TicketCreate` itself).
+pub fn allocate_sequence(job_id: &str) -> Result<SequenceSlot, XRPLError> {
+    lease_slot(job_id, false)
+}
+
+fn lease_slot(job_id: &str, allow_ticket: bool) -> Result<SequenceSlot, XRPLError> {
+    let mut state = SEQUENCE_STATE.lock().unwrap();
+    let slot = state.lease(job_id, allow_ticket, now_secs())?;
+    persist(&state);
+    Ok(slot)
+}
+
+/// Ends a job's lease. Slots that were never applied on-ledger go back to the pool.
+pub fn release_slot(job_id: &str, consumed: bool) {
+    let mut state = SEQUENCE_STATE.lock().unwrap();
+    if state.release(job_id, consumed) {
+        persist(&state);
+    }
+}
+
+impl SequenceState {
+    fn lease(&mut self, job_id: &str, allow_ticket: bool, now: u64) -> Result<SequenceSlot, XRPLError> {
+        if let Some(existing) = self.leases.get(job_id) {
+            return Ok(existing.slot);
+        }
+
+        let gap = self.free_sequences.pop_first();
+        let ticket = if allow_ticket && gap.is_none() { self.free_tickets.pop_first() } else { None };
+
+        let slot = if let Some(seq) = gap {
+            bridge_log_event("sequence", format!("Filling sequence gap {} with {}", seq, job_id));
+            SequenceSlot::Sequence(seq)
+        } else if let Some(ticket) = ticket {
+            SequenceSlot::Ticket(ticket)
+        } else if let Some(seq) = self.next_sequence {
+            self.next_sequence = Some(seq + 1);
+            if allow_ticket {
+                bridge_log_event("sequence", format!("No free tickets, using plain sequence {} for {}", seq, job_id));
+            }
+            SequenceSlot::Sequence(seq)
+        } else {
+            return Err(XRPLError::TransactionInvalidSequence(
+                "Sequence allocator not synced with ledger".into(),
+            ));
+        };
+
+        self.leases.insert(job_id.to_string(), SlotLease { slot, leased_at: now });
+        Ok(slot)
+    }
+
+    /// Returns false if the job held no lease.
+    fn release(&mut self, job_id: &str, consumed: bool) -> bool {
+        let Some(lease) = self.leases.remove(job_id) else {
+            return false;
+        };
+
+        if !consumed {
+            match lease.slot {
+                SequenceSlot::Ticket(ticket) => {
+                    self.free_tickets.insert(ticket);
+                    bridge_log_event("sequence", format!("♻️ Reclaimed ticket {} from {}", ticket, job_id));
+                }
+                SequenceSlot::Sequence(seq) => {
+                    if self.next_sequence == Some(seq + 1) {
+                        self.next_sequence = Some(seq);
+                    } else {
+                        // A later sequence is already out; resync before handing out more.
+                        self.next_sequence = None;
+                        self.free_sequences.clear();
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Rebuilds the pools from the ledger's account `Sequence` and Ticket objects.
+    fn apply_ledger(&mut self, account: &str, ledger_sequence: u32, ledger_tickets: &BTreeSet<u32>, now: u64) {
+        if self.account.as_deref() != Some(account) {
+            self.leases.clear();
+            self.account = Some(account.to_string());
+        }
+
+        // Drop leases whose slot is gone from the ledger or whose job has died.
+        self.leases.retain(|job_id, lease| match lease.slot {
+            SequenceSlot::Ticket(ticket) => {
+                let live = ledger_tickets.contains(&ticket) && now.saturating_sub(lease.leased_at) < LEASE_TIMEOUT_SECS;
+                if !live {
+                    bridge_log_event("sequence", format!("Expired lease on ticket {} ({})", ticket, job_id));
+                }
+                live
+            }
+            SequenceSlot::Sequence(seq) => seq >= ledger_sequence && now.saturating_sub(lease.leased_at) < LEASE_TIMEOUT_SECS,
+        });
+
+        let leased_tickets: BTreeSet<u32> = self
+            .leases
+            .values()
+            .filter_map(|l| match l.slot {
+                SequenceSlot::Ticket(t) => Some(t),
+                _ => None,
+            })
+            .collect();
+        let leased_sequences: BTreeSet<u32> = self
+            .leases
+            .values()
+            .filter_map(|l| match l.slot {
+                SequenceSlot::Sequence(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+
+        // Re-derived rather than trusted, so a crash mid-replenish cannot leave it stuck.
+        self.ticket_create_pending = self.has_ticket_create_lease();
+
+        self.free_tickets = ledger_tickets.difference(&leased_tickets).copied().collect();
+
+        // A sequence released unused below a later lease leaves the ledger `Sequence`
+        // (and any other unheld one up to that lease) to be handed out again.
+        let next = leased_sequences.last().map_or(ledger_sequence, |s| s + 1).max(ledger_sequence);
+        self.free_sequences = (ledger_sequence..next).filter(|s| !leased_sequences.contains(s)).collect();
+        self.next_sequence = Some(next);
+    }
+
+    /// A `TicketCreate` is in flight exactly while its job holds a lease.
+    fn has_ticket_create_lease(&self) -> bool {
+        self.leases.keys().any(|job_id| job_id.starts_with(TICKET_CREATE_JOB_PREFIX))
+    }
+}
+
+/// Returns the slot currently leased to a job, if any.
+pub fn leased_slot(job_id: &str) -> Option<SequenceSlot> {
+    let state = SEQUENCE_STATE.lock().unwrap();
+    state.leases.get(job_id).map(|l| l.slot)
+}
+
+/// Number of tickets available to hand out right now.
+pub fn free_ticket_count() -> usize {
+    SEQUENCE_STATE.lock().unwrap().free_tickets.len()
+}
+
+/// Snapshot of the allocator (for status / admin output).
+pub fn get_sequence_state() -> SequenceState {
+    SEQUENCE_STATE.lock().unwrap().clone()
+}
+
+/// Rebuilds allocator state from the ledger: account `Sequence` plus owned Ticket objects.
+pub async fn sync_with_ledger(rpc: &XRPLRpcClient, account: &str) -> Result<(), XRPLError> {
+    let account_data = rpc.account_info(account).await?;
+    let ledger_sequence = account_data["Sequence"]
+        .as_u64()
+        .ok_or_else(|| XRPLError::InvalidResponse("account_info: missing Sequence".into()))? as u32;
+
+    let ledger_tickets: BTreeSet<u32> = rpc
+        .account_objects(account, "ticket")
+        .await?
+        .iter()
+        .filter_map(|obj| obj["TicketSequence"].as_u64().map(|t| t as u32))
+        .collect();
+
+    let mut state = SEQUENCE_STATE.lock().unwrap();
+    state.apply_ledger(account, ledger_sequence, &ledger_tickets, now_secs());
+    persist(&state);
+
+    bridge_log_event(
+        "sequence",
+        format!(
+            "🔄 Synced {}: next sequence {:?}, {} free tickets, {} leases",
+            account,
+            state.next_sequence,
+            state.free_tickets.len(),
+            state.leases.len()
+        ),
+    );
+    Ok(())
+}
+
+/// How many tickets to create, if the pool has dropped below the low watermark.
+pub fn tickets_to_create() -> Option<u32> {
+    let state = SEQUENCE_STATE.lock().unwrap();
+    if state.ticket_create_pending {
+        return None;
+    }
+
+    let free = state.free_tickets.len() as u32;
+    if free >= get_ticket_low_watermark() {
+        return None;
+    }
+
+    let leased = state
+        .leases
+        .values()
+        .filter(|l| matches!(l.slot, SequenceSlot::Ticket(_)))
+        .count() as u32;
+
+    let wanted = get_ticket_target().saturating_sub(free + leased);
+    if wanted == 0 { None } else { Some(wanted) }
+}
+
+/// Builds an unsigned `TicketCreate` for the bridge account.
+pub fn build_ticket_create(account: &str, count: u32) -> Value {
+    json!({
+        "TransactionType": "TicketCreate",
+        "Account": account,
+        "TicketCount": count.min(250),
+    })
+}
+
+/// Tops up the ticket pool with a single `TicketCreate` (sent on a plain sequence).
+pub async fn replenish_tickets(rpc: &XRPLRpcClient, account: &str) -> Result<(), XRPLError> {
+    let Some(count) = tickets_to_create() else {
+        return Ok(());
+    };
+
+    {
+        let mut state = SEQUENCE_STATE.lock().unwrap();
+        state.ticket_create_pending = true;
+        persist(&state);
+    }
+
+    bridge_log_event("sequence", format!("🎟️ Creating {} tickets for {}", count, account));
+
+    let job_id = format!("{}{}", TICKET_CREATE_JOB_PREFIX, now_secs());
+    let result = crate::xrpl::outbound::submit_with_sequence(rpc, &job_id, build_ticket_create(account, count)).await;
+
+    {
+        let mut state = SEQUENCE_STATE.lock().unwrap();
+        state.ticket_create_pending = false;
+        persist(&state);
+    }
+
+    result?;
+    sync_with_ledger(rpc, account).await
+}
+
+/// Background loop: keep the allocator synced and the ticket pool topped up.
+pub async fn run_ticket_maintenance(rpc: XRPLRpcClient, account: String, interval_secs: u64) {
+    loop {
+        if let Err(e) = sync_with_ledger(&rpc, &account).await {
+            bridge_log_event("warn", format!("Sequence sync failed: {}", e));
+        } else if let Err(e) = replenish_tickets(&rpc, &account).await {
+            bridge_log_event("warn", format!("Ticket replenishment failed: {}", e));
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synced(next_sequence: u32, tickets: &[u32]) -> SequenceState {
+        SequenceState {
+            account: Some("rBridge".into()),
+            next_sequence: Some(next_sequence),
+            free_tickets: tickets.iter().copied().collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn leases_prefer_tickets_and_are_idempotent() {
+        let mut state = synced(100, &[7, 9]);
+        assert_eq!(state.lease("a", true, 0).unwrap(), SequenceSlot::Ticket(7));
+        assert_eq!(state.lease("a", true, 0).unwrap(), SequenceSlot::Ticket(7));
+        assert_eq!(state.lease("b", false, 0).unwrap(), SequenceSlot::Sequence(100));
+        assert_eq!(state.next_sequence, Some(101));
+    }
+
+    #[test]
+    fn unused_ticket_returns_to_pool() {
+        let mut state = synced(100, &[7]);
+        state.lease("a", true, 0).unwrap();
+        assert!(state.release("a", false));
+        assert!(state.free_tickets.contains(&7));
+        assert!(!state.release("a", false));
+    }
+
+    #[test]
+    fn consumed_ticket_is_not_reused() {
+        let mut state = synced(100, &[7]);
+        state.lease("a", true, 0).unwrap();
+        state.release("a", true);
+        assert!(state.free_tickets.is_empty());
+    }
+
+    #[test]
+    fn unused_sequence_rewinds_only_when_last() {
+        let mut state = synced(100, &[]);
+        state.lease("a", false, 0).unwrap();
+        state.release("a", false);
+        assert_eq!(state.next_sequence, Some(100));
+
+        state.lease("a", false, 0).unwrap();
+        state.lease("b", false, 0).unwrap();
+        state.release("a", false);
+        assert_eq!(state.next_sequence, None);
+        assert!(state.lease("c", false, 0).is_err());
+    }
+
+    #[test]
+    fn sync_hands_out_an_unused_sequence_below_a_lease() {
+        let mut state = synced(100, &[7]);
+        state.lease("a", false, 0).unwrap();
+        state.lease("b", false, 0).unwrap();
+        state.release("a", false);
+
+        // The ledger is still at 100: "b" (101) can't validate until 100 is used.
+        state.apply_ledger("rBridge", 100, &BTreeSet::from([7]), 0);
+        assert_eq!(state.next_sequence, Some(102));
+        assert_eq!(state.lease("c", true, 0).unwrap(), SequenceSlot::Sequence(100));
+        assert_eq!(state.lease("d", true, 0).unwrap(), SequenceSlot::Ticket(7));
+        assert_eq!(state.lease("e", false, 0).unwrap(), SequenceSlot::Sequence(102));
+
+        // With no lease outstanding there is no gap to fill.
+        let mut idle = synced(100, &[]);
+        idle.apply_ledger("rBridge", 105, &BTreeSet::new(), 0);
+        assert!(idle.free_sequences.is_empty());
+        assert_eq!(idle.next_sequence, Some(105));
+    }
+
+    #[test]
+    fn ticket_create_pending_follows_lease() {
+        let mut state = synced(100, &[]);
+        assert!(!state.has_ticket_create_lease());
+        state.lease("ticket-create-1", false, 0).unwrap();
+        assert!(state.has_ticket_create_lease());
+        state.release("ticket-create-1", true);
+        assert!(!state.has_ticket_create_lease());
+    }
+}
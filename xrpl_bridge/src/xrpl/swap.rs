@@ -0,0 +1,527 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::RwLock;
+
+use ic_agent::Agent;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use candid::{CandidType, Principal};
+
+use crate::config::{get_bridge_address, BridgeConfig};
+use crate::ic_trigger::report_swap_fill;
+use crate::log::bridge_log_event;
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::xrpl::multisig::submit_transfer;
+use crate::xrpl::params::currency_allowed;
+use crate::xrpl::refunds::{record_refund, RefundDecision, RefundSource};
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::xrpl::types::{XRPLAmount, XRPLError, XRPLValue};
+
+/// `OfferCreate` flag: take what crosses now, never rest on the book.
+const TF_IMMEDIATE_OR_CANCEL: u32 = 0x0002_0000;
+
+/// `OfferCreate` flag: spend all of `TakerGets` even if that returns more than `TakerPays`,
+/// so `TakerPays` acts as the slippage floor rather than the amount received.
+const TF_SELL: u32 = 0x0008_0000;
+
+/// `Payment` flag: allow delivering less than `Amount` (bounded by `DeliverMin`).
+const TF_PARTIAL_PAYMENT: u32 = 0x0002_0000;
+
+/// Hard ceiling on the slippage a caller may ask for (10%).
+const MAX_SLIPPAGE_BPS: u32 = 1_000;
+
+const SWAP_FILLS_FILE: &str = "swap_fills.json";
+const UNREPORTED_FILLS_FILE: &str = "swap_fills_unreported.json";
+
+/// Fills by swap id, so a retried swap reports the first fill instead of trading again.
+static SWAP_FILLS: Lazy<RwLock<BTreeMap<String, SwapFill>>> = Lazy::new(|| {
+    let fills = load_json_state::<BTreeMap<String, SwapFill>>(SWAP_FILLS_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load swap fills: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(fills)
+});
+
+/// Swap ids whose fill the swap canister has not accepted yet.
+static UNREPORTED_FILLS: Lazy<RwLock<BTreeSet<String>>> = Lazy::new(|| {
+    let ids = load_json_state::<BTreeSet<String>>(UNREPORTED_FILLS_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load unreported swap fills: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(ids)
+});
+
+/// A currency on XRPL, without an amount.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Issue {
+    XRP,
+    IOU { currency: String, issuer: String },
+}
+
+impl Issue {
+    pub fn of(amount: &XRPLAmount) -> Self {
+        match amount {
+            XRPLAmount::XRP { .. } => Issue::XRP,
+            XRPLAmount::IOU { currency, issuer, .. } => Issue::IOU {
+                currency: currency.clone(),
+                issuer: issuer.clone(),
+            },
+        }
+    }
+
+    /// Parses `XRP` or `CUR.rIssuer`, as written in SWAP memos.
+    pub fn from_code(text: &str) -> Option<Self> {
+        if text.eq_ignore_ascii_case("XRP") {
+            return Some(Issue::XRP);
+        }
+        let (currency, issuer) = text.split_once('.')?;
+        if currency.is_empty() || !issuer.starts_with('r') {
+            return None;
+        }
+        Some(Issue::IOU { currency: currency.to_string(), issuer: issuer.to_string() })
+    }
+
+    /// `XRP` or the issued currency's code.
+    pub fn currency_code(&self) -> &str {
+        match self {
+            Issue::XRP => "XRP",
+            Issue::IOU { currency, .. } => currency,
+        }
+    }
+
+    /// Issue spec as `book_offers` / `amm_info` expect it.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Issue::XRP => json!({ "currency": "XRP" }),
+            Issue::IOU { currency, issuer } => json!({ "currency": currency, "issuer": issuer }),
+        }
+    }
+
+    /// Builds an amount of this issue. XRP values are in drops; both round down.
+    pub fn amount(&self, value: XRPLValue) -> XRPLAmount {
+        match self {
+            Issue::XRP => XRPLAmount::XRP { drops: value.floor_u64() },
+            Issue::IOU { currency, issuer } => XRPLAmount::IOU {
+                currency: currency.clone(),
+                issuer: issuer.clone(),
+                value: if value.is_positive() { value.to_iou_string() } else { "0".to_string() },
+            },
+        }
+    }
+}
+
+/// Best expected output for spending `amount_in`, by venue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapQuote {
+    pub amount_in: XRPLValue,
+    pub book_out: Option<XRPLValue>,
+    pub amm_out: Option<XRPLValue>,
+    pub best_out: XRPLValue,
+    pub source: String,
+}
+
+/// One swap for the bridge wallet to run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapRequest {
+    pub swap_id: String,
+    /// Most the bridge will spend.
+    pub send: XRPLAmount,
+    /// What should come out.
+    pub receive: Issue,
+    /// `None` keeps the proceeds in the bridge wallet (`OfferCreate`);
+    /// `Some` delivers them to that account (cross-currency `Payment`).
+    pub destination: Option<String>,
+    pub destination_tag: Option<u32>,
+    pub max_slippage_bps: u32,
+    /// ICP principal the swap canister credits with the proceeds.
+    #[serde(default)]
+    pub credit_to: Option<Principal>,
+    /// Inbound payment that funded the swap; returned if the trade does not fill.
+    #[serde(default)]
+    pub refund: Option<RefundSource>,
+}
+
+/// What the swap actually did on-ledger, reported back to the swap canister.
+#[derive(Debug, Clone, Serialize, Deserialize, CandidType)]
+pub struct SwapFill {
+    pub swap_id: String,
+    pub tx_hash: String,
+    pub spent: XRPLAmount,
+    pub received: XRPLAmount,
+    pub quoted_out: String,
+    pub venue: String,
+    pub credit_to: Option<Principal>,
+}
+
+/// Walks the order book for `out` paid with `amount_in` of `input`.
+pub async fn quote_book(rpc: &XRPLRpcClient, input: &Issue, out: &Issue, amount_in: XRPLValue) -> Result<Option<XRPLValue>, XRPLError> {
+    let offers = rpc
+        .book_offers(&out.to_json(), &input.to_json(), get_bridge_address().as_deref())
+        .await?;
+    Ok(fill_from_book(&offers, amount_in))
+}
+
+/// Walks offers best-first, spending `amount_in`; `None` when the book is too thin.
+fn fill_from_book(offers: &[Value], amount_in: XRPLValue) -> Option<XRPLValue> {
+    let mut remaining = amount_in;
+    let mut received = XRPLValue::ZERO;
+
+    for offer in offers {
+        // Funded amounts are present when the offer owner can't cover the whole offer.
+        let gets = offer.get("taker_gets_funded").unwrap_or(&offer["TakerGets"]);
+        let pays = offer.get("taker_pays_funded").unwrap_or(&offer["TakerPays"]);
+        let (Some(gets), Some(pays)) = (XRPLAmount::from_json(gets), XRPLAmount::from_json(pays)) else {
+            continue;
+        };
+        let (gets, pays) = (gets.value(), pays.value());
+        if !gets.is_positive() || !pays.is_positive() {
+            continue;
+        }
+
+        if pays >= remaining {
+            received += (gets * remaining).checked_div(pays)?;
+            remaining = XRPLValue::ZERO;
+            break;
+        }
+        received += gets;
+        remaining -= pays;
+    }
+
+    // Not enough depth to fill the whole amount.
+    (!remaining.is_positive()).then_some(received)
+}
+
+/// Constant-product output from the AMM pool for the pair, after its trading fee.
+pub async fn quote_amm(rpc: &XRPLRpcClient, input: &Issue, out: &Issue, amount_in: XRPLValue) -> Result<Option<XRPLValue>, XRPLError> {
+    let Some(amm) = rpc.amm_info(&input.to_json(), &out.to_json()).await? else {
+        return Ok(None);
+    };
+
+    let (Some(pool_a), Some(pool_b)) = (XRPLAmount::from_json(&amm["amount"]), XRPLAmount::from_json(&amm["amount2"])) else {
+        return Ok(None);
+    };
+    let (reserve_in, reserve_out) = if Issue::of(&pool_a) == *input {
+        (pool_a.value(), pool_b.value())
+    } else {
+        (pool_b.value(), pool_a.value())
+    };
+    let trading_fee = amm["trading_fee"].as_u64().unwrap_or(0);
+    Ok(amm_output(reserve_in, reserve_out, trading_fee, amount_in))
+}
+
+/// Constant-product output; `trading_fee` is in units of 1/100,000.
+fn amm_output(reserve_in: XRPLValue, reserve_out: XRPLValue, trading_fee: u64, amount_in: XRPLValue) -> Option<XRPLValue> {
+    if !reserve_in.is_positive() || !reserve_out.is_positive() {
+        return None;
+    }
+    let kept = XRPLValue::new(100_000i128 - trading_fee.min(100_000) as i128, -5);
+    let effective_in = amount_in * kept;
+    (reserve_out * effective_in).checked_div(reserve_in + effective_in)
+}
+
+/// Least acceptable output: `best_out` less `slippage_bps`.
+fn min_output(best_out: XRPLValue, slippage_bps: u32) -> XRPLValue {
+    best_out * XRPLValue::new(10_000i128 - slippage_bps.min(10_000) as i128, -4)
+}
+
+/// Quotes against both venues and keeps the better one.
+pub async fn quote_swap(rpc: &XRPLRpcClient, send: &XRPLAmount, receive: &Issue) -> Result<SwapQuote, XRPLError> {
+    let input = Issue::of(send);
+    if input == *receive {
+        return Err(XRPLError::InvalidTransaction("Swap needs two different currencies".into()));
+    }
+    let amount_in = send.value();
+
+    let book_out = quote_book(rpc, &input, receive, amount_in).await?;
+    let amm_out = quote_amm(rpc, &input, receive, amount_in).await?;
+
+    let (best_out, source) = match (book_out, amm_out) {
+        (Some(b), Some(a)) if a > b => (a, "amm"),
+        (Some(b), _) => (b, "book"),
+        (None, Some(a)) => (a, "amm"),
+        (None, None) => return Err(XRPLError::Other("No liquidity for this pair".into())),
+    };
+
+    Ok(SwapQuote {
+        amount_in,
+        book_out,
+        amm_out,
+        best_out,
+        source: source.to_string(),
+    })
+}
+
+/// The fill already recorded for `swap_id`, if that swap has run.
+pub fn recorded_fill(swap_id: &str) -> Option<SwapFill> {
+    SWAP_FILLS.read().unwrap().get(swap_id).cloned()
+}
+
+/// Records a fill; it stays unreported until the swap canister accepts it.
+fn record_fill(fill: &SwapFill) {
+    let mut fills = SWAP_FILLS.write().unwrap();
+    fills.insert(fill.swap_id.clone(), fill.clone());
+    if let Err(e) = persist_json_state(SWAP_FILLS_FILE, &*fills) {
+        bridge_log_event("error", format!("❌ Could not persist swap fill {}: {:?}", fill.swap_id, e));
+    }
+    set_unreported(&fill.swap_id, true);
+}
+
+fn set_unreported(swap_id: &str, unreported: bool) {
+    let mut ids = UNREPORTED_FILLS.write().unwrap();
+    let changed = if unreported { ids.insert(swap_id.to_string()) } else { ids.remove(swap_id) };
+    if changed {
+        if let Err(e) = persist_json_state(UNREPORTED_FILLS_FILE, &*ids) {
+            bridge_log_event("error", format!("❌ Could not persist unreported swap fills: {:?}", e));
+        }
+    }
+}
+
+/// Reports a fill to the swap canister. A failed report stays pending for `run_swap_reporting`.
+pub async fn report_fill(agent: &Agent, config: &BridgeConfig, fill: &SwapFill) -> anyhow::Result<()> {
+    report_swap_fill(agent, config, fill).await?;
+    set_unreported(&fill.swap_id, false);
+    Ok(())
+}
+
+/// Re-sends fills the swap canister has not accepted yet.
+pub async fn run_swap_reporting(agent: Agent, config: BridgeConfig, interval_secs: u64) {
+    loop {
+        let pending: Vec<SwapFill> = UNREPORTED_FILLS
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|swap_id| recorded_fill(swap_id))
+            .collect();
+        for fill in pending {
+            if let Err(e) = report_fill(&agent, &config, &fill).await {
+                bridge_log_event("warn", format!("⚠️ Swap fill report for {} failed again: {}", fill.swap_id, e));
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
+
+/// Returns a swap's input when the trade definitely did not happen: refused before
+/// submission, or validated without crossing (e.g. `tecKILLED`). Other errors leave the
+/// outcome open, so nothing is refunded. Returns whether the failure was final.
+pub fn refund_unfilled_swap(request: &SwapRequest, err: &XRPLError) -> bool {
+    if !matches!(err, XRPLError::TransactionRejected(_) | XRPLError::InvalidTransaction(_)) {
+        return false;
+    }
+    match &request.refund {
+        Some(source) => {
+            let record = record_refund(source.clone(), RefundDecision::Refund("swap did not fill"));
+            bridge_log_event("swap", format!("↩️ Swap {} did not fill ({:?}); refund {:?}", request.swap_id, err, record.status));
+        }
+        None => bridge_log_event("warn", format!("Swap {} did not fill and has no payment to refund: {:?}", request.swap_id, err)),
+    }
+    true
+}
+
+/// Quotes, then executes within `max_slippage_bps` of the quote and returns the actual fill.
+/// A swap id that already filled returns that fill without trading again.
+pub async fn execute_swap(rpc: &XRPLRpcClient, request: &SwapRequest) -> Result<SwapFill, XRPLError> {
+    if let Some(fill) = recorded_fill(&request.swap_id) {
+        return Ok(fill);
+    }
+    let account = get_bridge_address().ok_or_else(|| XRPLError::Other("XRPL_BRIDGE_ADDRESS not set".into()))?;
+    if let Some(code) = [Issue::of(&request.send), request.receive.clone()]
+        .iter()
+        .map(Issue::currency_code)
+        .find(|code| !currency_allowed(code))
+    {
+        return Err(XRPLError::InvalidTransaction(format!("Currency {} is not allowed", code)));
+    }
+    if request.max_slippage_bps > MAX_SLIPPAGE_BPS {
+        return Err(XRPLError::InvalidTransaction(format!(
+            "Slippage {} bps exceeds the {} bps limit",
+            request.max_slippage_bps, MAX_SLIPPAGE_BPS
+        )));
+    }
+
+    let quote = quote_swap(rpc, &request.send, &request.receive).await?;
+    let min_out = min_output(quote.best_out, request.max_slippage_bps);
+    let job_id = format!("swap-{}", request.swap_id);
+
+    let (tx, venue) = match &request.destination {
+        None => (
+            json!({
+                "TransactionType": "OfferCreate",
+                "TakerGets": request.send.to_json(),
+                "TakerPays": request.receive.amount(min_out).to_json(),
+                "Flags": TF_IMMEDIATE_OR_CANCEL | TF_SELL,
+            }),
+            quote.source.clone(),
+        ),
+        Some(destination) => {
+            let target = request.receive.amount(quote.best_out);
+            let alternatives = rpc
+                .ripple_path_find(&account, destination, &target.to_json(), Some(&request.send.to_json()))
+                .await?;
+            let paths = alternatives
+                .first()
+                .map(|alt| alt["paths_computed"].clone())
+                .filter(|p| p.as_array().is_some_and(|a| !a.is_empty()));
+
+            let mut payment = json!({
+                "TransactionType": "Payment",
+                "Destination": destination,
+                "Amount": target.to_json(),
+                "SendMax": request.send.to_json(),
+                "DeliverMin": request.receive.amount(min_out).to_json(),
+                "Flags": TF_PARTIAL_PAYMENT,
+            });
+            if let Some(paths) = paths {
+                payment["Paths"] = paths;
+            }
+            if let Some(tag) = request.destination_tag {
+                payment["DestinationTag"] = json!(tag);
+            }
+            (payment, "path".to_string())
+        }
+    };
+
+    let validated = submit_transfer(rpc, &job_id, tx).await?;
+    let meta = &validated["meta"];
+    let tx_hash = validated["hash"].as_str().unwrap_or_default().to_string();
+
+    let input = Issue::of(&request.send);
+    let fee_drops = validated["Fee"].as_str().and_then(|f| f.parse::<u64>().ok()).unwrap_or(0);
+    let mut spent = -balance_change(meta, &account, &input);
+    if input == Issue::XRP {
+        spent -= XRPLValue::from_drops(fee_drops);
+    }
+    let received = match &request.destination {
+        Some(_) => XRPLAmount::from_json(&meta["delivered_amount"]).map(|a| a.value()).unwrap_or(XRPLValue::ZERO),
+        None => balance_change(meta, &account, &request.receive),
+    };
+
+    if !received.is_positive() {
+        return Err(XRPLError::TransactionRejected(format!("{} crossed nothing", tx_hash)));
+    }
+
+    bridge_log_event(
+        "swap",
+        format!("🔄 {} spent {} for {} via {} ({})", request.swap_id, spent, received, venue, tx_hash),
+    );
+
+    let fill = SwapFill {
+        swap_id: request.swap_id.clone(),
+        tx_hash,
+        spent: input.amount(spent),
+        received: request.receive.amount(received),
+        quoted_out: quote.best_out.to_string(),
+        venue,
+        credit_to: request.credit_to,
+    };
+    record_fill(&fill);
+    Ok(fill)
+}
+
+/// Net change of `account`'s holdings of `issue` in a transaction's metadata
+/// (XRP in drops, fee included).
+pub fn balance_change(meta: &Value, account: &str, issue: &Issue) -> XRPLValue {
+    let Some(nodes) = meta["AffectedNodes"].as_array() else {
+        return XRPLValue::ZERO;
+    };
+    let mut change = XRPLValue::ZERO;
+
+    for node in nodes {
+        let Some((_, inner)) = node.as_object().and_then(|o| o.iter().next()) else {
+            continue;
+        };
+        let fields = if inner["FinalFields"].is_object() { &inner["FinalFields"] } else { &inner["NewFields"] };
+        let previous = &inner["PreviousFields"];
+
+        match (issue, inner["LedgerEntryType"].as_str()) {
+            (Issue::XRP, Some("AccountRoot")) if fields["Account"] == account => {
+                let parse = |v: &Value| v.as_str().and_then(XRPLValue::parse);
+                if let (Some(after), Some(before)) = (parse(&fields["Balance"]), parse(&previous["Balance"])) {
+                    change += after - before;
+                }
+            }
+            (Issue::IOU { currency, issuer }, Some("RippleState")) => {
+                if fields["Balance"]["currency"] != currency.as_str() {
+                    continue;
+                }
+                let low = fields["LowLimit"]["issuer"].as_str().unwrap_or_default();
+                let high = fields["HighLimit"]["issuer"].as_str().unwrap_or_default();
+                let (is_low, other) = if low == account {
+                    (true, high)
+                } else if high == account {
+                    (false, low)
+                } else {
+                    continue;
+                };
+                // Lines to the issuer count; when we are the issuer, every line does.
+                if other != issuer && account != issuer {
+                    continue;
+                }
+
+                let parse = |v: &Value| v["value"].as_str().and_then(XRPLValue::parse);
+                let after = parse(&fields["Balance"]).unwrap_or(XRPLValue::ZERO);
+                let before = parse(&previous["Balance"]).unwrap_or(if inner["NewFields"].is_object() { XRPLValue::ZERO } else { after });
+                // RippleState balances are from the low account's side.
+                let delta = after - before;
+                change += if is_low { delta } else { -delta };
+            }
+            _ => {}
+        }
+    }
+
+    change
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(text: &str) -> XRPLValue {
+        XRPLValue::parse(text).unwrap()
+    }
+
+    #[test]
+    fn memo_issue_codes_parse() {
+        assert_eq!(Issue::from_code("XRP"), Some(Issue::XRP));
+        assert_eq!(
+            Issue::from_code("USD.rIssuer"),
+            Some(Issue::IOU { currency: "USD".into(), issuer: "rIssuer".into() })
+        );
+        assert_eq!(Issue::from_code("USD"), None);
+        assert_eq!(Issue::from_code(".rIssuer"), None);
+        assert_eq!(Issue::from_code("USD.Issuer"), None);
+    }
+
+    #[test]
+    fn book_fill_walks_offers_and_needs_depth() {
+        let usd = |value: &str| json!({ "currency": "USD", "issuer": "rIssuer", "value": value });
+        let offers = vec![
+            json!({ "TakerGets": usd("10"), "TakerPays": "20000000" }),
+            json!({ "TakerGets": usd("10"), "TakerPays": "25000000" }),
+        ];
+        assert_eq!(fill_from_book(&offers, v("30000000")), Some(v("14")));
+        assert_eq!(fill_from_book(&offers, v("50000000")), None);
+    }
+
+    #[test]
+    fn amm_output_applies_fee() {
+        // 1% fee: 100 in of which 99 counts, against 1000/1000.
+        let out = amm_output(v("1000"), v("1000"), 1_000, v("100")).unwrap();
+        assert_eq!(out.to_iou_string(), "90.08189262966333");
+        assert!(amm_output(XRPLValue::ZERO, v("1000"), 0, v("1")).is_none());
+    }
+
+    #[test]
+    fn slippage_floor_and_amounts_round_down() {
+        assert_eq!(min_output(v("200"), 50), v("199"));
+        assert_eq!(Issue::XRP.amount(v("1234.9")), XRPLAmount::XRP { drops: 1234 });
+        let usd = Issue::IOU { currency: "USD".into(), issuer: "rIssuer".into() };
+        assert_eq!(usd.amount(v("0.1") + v("0.2")).to_json()["value"], "0.3");
+    }
+}
@@ -1,15 +1,45 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
 use crate::xrpl::types::{VerifiedXRPLTx, XRPLActionType};
-use crate::ic_trigger::{handle_tip, handle_nft_sale, handle_token_swap};
+use crate::ic_trigger::{
+    handle_tip, handle_wrap_deposit, handle_nft_sale, handle_token_swap, mirror_nft_to_xrpl, unmirror_nft_on_icp, fetch_axia_nft,
+    fetch_assets_for_nft,
+};
 use crate::log::bridge_log_event;
 use crate::xrpl::verifier::log_verification;
 use anyhow::Result;
 use candid::Nat;
-use crate::xrpl::types::{XRPLMirrorStatus, MirrorError};
-use std::collections::HashMap;
-use std::sync::RwLock;
-use once_cell::sync::Lazy;
+use crate::xrpl::types::{XRPLMirrorStatus, MirrorError, XRPLAmount};
+use crate::state::db::{load_json_state, persist_json_state};
+use crate::state::queue::{enqueue_action, NFTSaleDetails, PendingAction, QueueError};
 use ic_agent::Agent;
-use crate::config::BridgeConfig;
+use crate::config::{get_bridge_address, get_iou_currency, get_iou_decimals, BridgeConfig};
+use candid::Principal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::state::mirror_registry::{
+    get_mirror, get_mirror_by_nftoken, list_mirrors, request_mirror, set_icp_marked, set_xrpl_ref, transition_mirror,
+    MirrorRecord, MirrorState, XRPLMirrorRef,
+};
+use crate::xrpl::multisig::submit_transfer;
+use crate::xrpl::outbound::submit_and_wait;
+use crate::xrpl::approvals::admit_action;
+use crate::xrpl::client::track_account;
+use crate::xrpl::issuer::iou_value_to_units;
+use crate::xrpl::linking::linked_principal;
+use crate::xrpl::rpc::XRPLRpcClient;
+use crate::util::now_secs;
+
+/// XLS-20 mint flags: the bridge (issuer) may burn, holders may transfer.
+const TF_BURNABLE: u32 = 0x0000_0001;
+const TF_TRANSFERABLE: u32 = 0x0000_0008;
+
+/// XRPL caps `URI` at 256 bytes and `TransferFee` at 50% (50000).
+const MAX_URI_BYTES: usize = 256;
+const MAX_TRANSFER_FEE: u16 = 50_000;
 
 /// Dispatches a verified XRPL transaction to the appropriate handler.
 pub async fn dispatch_verified_tx(
@@ -27,9 +57,9 @@ pub async fn dispatch_verified_tx(
                     .expect("Missing UUID in XRPL memo (TIP)");
 
                 if let Err(e) = handle_tip(
-                    &agent,
-                    &config,
-                    artist.clone(),
+                    agent,
+                    config,
+                    *artist,
                     tx.amount.clone(),
                     uuid,
                 ).await {
@@ -48,9 +78,9 @@ pub async fn dispatch_verified_tx(
                     .expect("Missing UUID in XRPL memo (NFTSale)");
 
                 if let Err(e) = handle_nft_sale(
-                    &agent,
-                    &config,
-                    artist.clone(),
+                    agent,
+                    config,
+                    *artist,
                     nft_id.to_string(),
                     tx.amount.clone(),
                     uuid,
@@ -70,9 +100,9 @@ pub async fn dispatch_verified_tx(
                     .expect("Missing UUID in XRPL memo (TokenSwap)");
 
                 if let Err(e) = handle_token_swap(
-                    &agent,
-                    &config,
-                    artist.clone(),
+                    agent,
+                    config,
+                    *artist,
                     tx.amount.clone(),
                     uuid,
                 ).await {
@@ -82,61 +112,654 @@ pub async fn dispatch_verified_tx(
                 bridge_log_event("error", "Missing artist Principal for token swap".into());
             }
         }
+
+        XRPLActionType::WrapXRP => {
+            println!("🎯 Dispatching wXRP deposit...");
+
+            if let Some(principal) = &tx.memo.artist {
+                if let Err(e) = handle_wrap_deposit(
+                    agent,
+                    config,
+                    *principal,
+                    tx.amount.clone(),
+                    tx.tx_hash.clone(),
+                ).await {
+                    bridge_log_event("error", format!("Failed to mint wXRP: {}", e));
+                }
+            } else {
+                bridge_log_event("error", "Missing principal for wXRP deposit".into());
+            }
+        }
     }
 }
 
-/// Registers an Axia asset (e.g., NFT or token) on XRPL by initiating a mirror.
-/// This could mint a side-chain representation or IOU depending on config/purpose.
-pub fn register_axia_asset_on_xrpl(
-    _asset_id: Nat,
+/// Collection taxon and royalty (`TransferFee`, in 1/100000 units) for a minted NFToken.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct NFTMintOptions {
+    pub taxon: u32,
+    pub transfer_fee: u16,
+}
+
+/// Registers an Axia asset on XRPL by minting an XLS-20 NFToken for it.
+/// The ICP side is only marked as mirrored once the mint has validated.
+pub async fn register_axia_asset_on_xrpl(
+    agent: &Agent,
+    config: &BridgeConfig,
+    rpc: &XRPLRpcClient,
+    asset_id: Nat,
     artist_principal: String,
     metadata_uri: String,
-    mirror_type: String, // e.g., "IOU", "NFT"
+    options: NFTMintOptions,
 ) -> Result<XRPLMirrorStatus, MirrorError> {
+    let NFTMintOptions { taxon, transfer_fee } = options;
+
     // Validate input
     if artist_principal.is_empty() || metadata_uri.is_empty() {
         return Err(MirrorError::InvalidParameters("Missing metadata or artist".into()));
     }
+    Principal::from_text(&artist_principal)
+        .map_err(|_| MirrorError::InvalidArtist(artist_principal.clone()))?;
+    if metadata_uri.len() > MAX_URI_BYTES {
+        return Err(MirrorError::InvalidMetadata(format!("URI longer than {} bytes", MAX_URI_BYTES)));
+    }
+    if transfer_fee > MAX_TRANSFER_FEE {
+        return Err(MirrorError::InvalidParameters(format!("Transfer fee {} exceeds 50000", transfer_fee)));
+    }
+
+    let asset_key = asset_id.0.to_string();
+    request_mirror(&asset_key, &artist_principal, "NFT", Some(metadata_uri.clone()))?;
+    transition_mirror(&asset_key, MirrorState::Minting, None, Some(format!("taxon {}, transfer fee {}", taxon, transfer_fee)))?;
+
+    let mint = build_nftoken_mint(&metadata_uri, taxon, transfer_fee);
+    let job_id = format!("nft-mint-{}", asset_key);
+
+    let validated = match submit_and_wait(rpc, &job_id, mint).await {
+        Ok(tx) => tx,
+        Err(e) => {
+            transition_mirror(&asset_key, MirrorState::Failed, None, Some(e.to_string()))?;
+            return Err(MirrorError::NetworkError(format!("NFTokenMint failed: {}", e)));
+        }
+    };
+
+    let mint_tx_hash = validated["hash"].as_str().unwrap_or_default().to_string();
+    let Some(nftoken_id) = extract_minted_nftoken_id(&validated["meta"]) else {
+        // The token exists on-ledger but we can't name it; leave it for manual reconciliation.
+        transition_mirror(&asset_key, MirrorState::Failed, Some(mint_tx_hash.clone()), Some("NFTokenID missing from metadata".into()))?;
+        return Err(MirrorError::Internal(format!("No NFTokenID in metadata of {}", mint_tx_hash)));
+    };
 
-    // [🔁 Placeholder for real XRPL IOU minting logic via external trigger]
-    // e.g., Call `mirror_nft_to_xrpl` from ic_trigger with agent + config
+    bridge_log_event("mirror", format!("🖼️ Minted {} for asset {} ({})", nftoken_id, asset_key, mint_tx_hash));
+
+    set_xrpl_ref(&asset_key, XRPLMirrorRef::NFToken { nftoken_id: nftoken_id.clone() })?;
+    let record = transition_mirror(&asset_key, MirrorState::Mirrored, Some(mint_tx_hash), None)?;
+
+    // Only now that the NFToken exists on a validated ledger do we tell the ICP side.
+    mirror_nft_to_xrpl(agent, config, asset_id)
+        .await
+        .map_err(|e| MirrorError::NetworkError(format!("Minted {} but markAsMirrored failed: {}", nftoken_id, e)))?;
+
+    set_icp_marked(&asset_key, true)?;
+
+    Ok(record.to_status())
+}
 
-    // Simulate a successful mirror for now
-    Ok(XRPLMirrorStatus {
-        mirrored: true,
-        pending: false,
-        tx_hash: Some("SIMULATED_XRPL_TX_HASH_1234".to_string()),
-        mirror_type: Some(mirror_type),
+/// Builds an unsigned `NFTokenMint` carrying the asset metadata URI (hex-encoded).
+pub fn build_nftoken_mint(metadata_uri: &str, taxon: u32, transfer_fee: u16) -> Value {
+    let uri_hex: String = metadata_uri.bytes().map(|b| format!("{:02X}", b)).collect();
+
+    json!({
+        "TransactionType": "NFTokenMint",
+        "NFTokenTaxon": taxon,
+        "TransferFee": transfer_fee,
+        "Flags": TF_BURNABLE | TF_TRANSFERABLE,
+        "URI": uri_hex,
     })
 }
 
+/// Pulls the new NFTokenID out of a validated mint's metadata.
+/// Uses the `nftoken_id` field when the server provides it, otherwise diffs the NFTokenPages.
+pub fn extract_minted_nftoken_id(meta: &Value) -> Option<String> {
+    if let Some(id) = meta["nftoken_id"].as_str() {
+        return Some(id.to_string());
+    }
+
+    let token_ids = |fields: &Value| -> Vec<String> {
+        fields["NFTokens"]
+            .as_array()
+            .map(|tokens| {
+                tokens
+                    .iter()
+                    .filter_map(|t| t["NFToken"]["NFTokenID"].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
 
-/// Burns or deactivates the XRPL-mirrored version of an Axia asset.
-/// Does not delete original asset on ICP; just severs XRPL tie.
-pub fn burn_xrpl_mirrored_token(tx_hash: &str) -> Result<(), MirrorError> {
-    if tx_hash.is_empty() {
-        return Err(MirrorError::InvalidParameters("Missing tx_hash".into()));
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+
+    for node in meta["AffectedNodes"].as_array()? {
+        let (kind, inner) = node.as_object()?.iter().next()?;
+        if inner["LedgerEntryType"] != "NFTokenPage" {
+            continue;
+        }
+        match kind.as_str() {
+            "CreatedNode" => after.extend(token_ids(&inner["NewFields"])),
+            // Unchanged pages omit PreviousFields.NFTokens
+            "ModifiedNode" if inner["PreviousFields"].get("NFTokens").is_some() => {
+                before.extend(token_ids(&inner["PreviousFields"]));
+                after.extend(token_ids(&inner["FinalFields"]));
+            }
+            "DeletedNode" => before.extend(token_ids(&inner["FinalFields"])),
+            _ => {}
+        }
     }
 
-    // [🚫 Placeholder: XRPL bridge logic to invalidate or burn mirrored token]
+    after.into_iter().find(|id| !before.contains(id))
+}
 
-    println!("🔥 XRPL mirrored token invalidated: {}", tx_hash);
-    Ok(())
+/// How to take a mirrored NFToken back when a third party holds it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BurnRequest {
+    /// Current holder, if known; otherwise looked up with `nft_info`.
+    pub holder: Option<String>,
+    /// XRP (drops) offered to the holder in a buy-back offer.
+    pub buyback_drops: Option<u64>,
 }
 
-/// Temporary in-memory mirror status store (to be replaced by persistent ICP call)
-static MIRROR_STATUS_STORE: Lazy<RwLock<HashMap<Nat, XRPLMirrorStatus>>> = Lazy::new(|| {
-    RwLock::new(HashMap::new())
+/// Un-mirrors an Axia asset: burns its NFToken on XRPL, then unlocks the ICP original.
+/// If a third party holds the token, a buy-back offer is placed instead and the burn
+/// completes from `run_mirror_maintenance` once the offer is accepted.
+pub async fn burn_xrpl_mirrored_token(
+    agent: &Agent,
+    config: &BridgeConfig,
+    rpc: &XRPLRpcClient,
+    asset_id: Nat,
+    request: BurnRequest,
+) -> Result<XRPLMirrorStatus, MirrorError> {
+    let asset_key = asset_id.0.to_string();
+    let record = get_mirror(&asset_key)
+        .ok_or_else(|| MirrorError::NotFound(format!("No mirror record for asset {}", asset_key)))?;
+    let nftoken_id = mirrored_nftoken_id(&record)?;
+
+    if record.state == MirrorState::Mirrored {
+        transition_mirror(&asset_key, MirrorState::BurnPending, None, None)?;
+    } else if record.state != MirrorState::BurnPending {
+        return Err(MirrorError::InvalidStatus(format!("Asset {} is {:?}", asset_key, record.state)));
+    }
+
+    if bridge_holds_nftoken(rpc, &nftoken_id).await? {
+        return burn_and_unlock(agent, config, rpc, &asset_key, &nftoken_id).await;
+    }
+
+    let holder = match request.holder {
+        Some(holder) => holder,
+        None => {
+            let info = rpc
+                .nft_info(&nftoken_id)
+                .await
+                .map_err(|e| MirrorError::NetworkError(format!("Cannot locate holder of {}: {}", nftoken_id, e)));
+            let info = match info {
+                Ok(info) => info,
+                Err(e) => return abandon_burn(&asset_key, e),
+            };
+            if info["is_burned"] == true {
+                // Someone else burned it (the holder can); only the ICP side is left.
+                transition_mirror(&asset_key, MirrorState::Burned, None, Some("already burned on XRPL".into()))?;
+                return unlock_on_icp(agent, config, &asset_key).await;
+            }
+            match info["owner"].as_str() {
+                Some(owner) => owner.to_string(),
+                None => {
+                    let e = MirrorError::NetworkError(format!("nft_info for {} has no owner", nftoken_id));
+                    return abandon_burn(&asset_key, e);
+                }
+            }
+        }
+    };
+
+    let Some(amount) = request.buyback_drops else {
+        let e = MirrorError::InvalidParameters(format!("{} is held by {}; a buy-back amount is required", nftoken_id, holder));
+        return abandon_burn(&asset_key, e);
+    };
+
+    let offer = json!({
+        "TransactionType": "NFTokenCreateOffer",
+        "NFTokenID": nftoken_id,
+        "Owner": holder,
+        "Amount": amount.to_string(),
+        "Flags": 0,
+    });
+    let job_id = format!("nft-buyback-{}", asset_key);
+
+    let validated = match submit_transfer(rpc, &job_id, offer).await {
+        Ok(tx) => tx,
+        Err(e) => return abandon_burn(&asset_key, MirrorError::NetworkError(format!("Buy-back offer failed: {}", e))),
+    };
+    let offer_index = created_offer_index(&validated["meta"]).unwrap_or_default();
+
+    bridge_log_event(
+        "mirror",
+        format!("🤝 Buy-back offer {} for {} sent to {} ({} drops)", offer_index, nftoken_id, holder, amount),
+    );
+
+    let record = get_mirror(&asset_key)
+        .ok_or_else(|| MirrorError::NotFound(format!("No mirror record for asset {}", asset_key)))?;
+    Ok(record.to_status())
+}
+
+/// Puts a mirror back to `Mirrored` when no burn or buy-back got under way.
+fn abandon_burn(asset_key: &str, error: MirrorError) -> Result<XRPLMirrorStatus, MirrorError> {
+    transition_mirror(asset_key, MirrorState::Mirrored, None, Some(format!("burn abandoned: {:?}", error)))?;
+    Err(error)
+}
+
+/// Burns a token the bridge holds, records it, then unlocks the ICP original.
+async fn burn_and_unlock(
+    agent: &Agent,
+    config: &BridgeConfig,
+    rpc: &XRPLRpcClient,
+    asset_key: &str,
+    nftoken_id: &str,
+) -> Result<XRPLMirrorStatus, MirrorError> {
+    let burn = json!({
+        "TransactionType": "NFTokenBurn",
+        "NFTokenID": nftoken_id,
+    });
+    let job_id = format!("nft-burn-{}", asset_key);
+
+    let validated = submit_and_wait(rpc, &job_id, burn)
+        .await
+        .map_err(|e| MirrorError::NetworkError(format!("NFTokenBurn failed: {}", e)))?;
+    let burn_tx_hash = validated["hash"].as_str().unwrap_or_default().to_string();
+
+    bridge_log_event("mirror", format!("🔥 Burned mirrored token {} ({})", nftoken_id, burn_tx_hash));
+    transition_mirror(asset_key, MirrorState::Burned, Some(burn_tx_hash), None)?;
+
+    unlock_on_icp(agent, config, asset_key).await
+}
+
+/// Tells the NFT canister the asset is no longer mirrored. Only valid once the XRPL side is burned.
+async fn unlock_on_icp(agent: &Agent, config: &BridgeConfig, asset_key: &str) -> Result<XRPLMirrorStatus, MirrorError> {
+    let record = get_mirror(asset_key)
+        .ok_or_else(|| MirrorError::NotFound(format!("No mirror record for asset {}", asset_key)))?;
+    if record.state != MirrorState::Burned {
+        return Err(MirrorError::InvalidStatus(format!("Asset {} is still live on XRPL", asset_key)));
+    }
+
+    let nft_id = asset_key
+        .parse::<u128>()
+        .map(Nat::from)
+        .map_err(|_| MirrorError::InvalidAssetId(asset_key.to_string()))?;
+
+    unmirror_nft_on_icp(agent, config, nft_id)
+        .await
+        .map_err(|e| MirrorError::NetworkError(format!("Burned on XRPL but markAsUnmirrored failed: {}", e)))?;
+
+    set_icp_marked(asset_key, false)?;
+    Ok(record.to_status())
+}
+
+fn mirrored_nftoken_id(record: &MirrorRecord) -> Result<String, MirrorError> {
+    match &record.xrpl_ref {
+        Some(XRPLMirrorRef::NFToken { nftoken_id }) => Ok(nftoken_id.clone()),
+        _ => Err(MirrorError::InvalidMirrorType(format!("Asset {} is not mirrored as an NFToken", record.asset_id))),
+    }
+}
+
+async fn bridge_holds_nftoken(rpc: &XRPLRpcClient, nftoken_id: &str) -> Result<bool, MirrorError> {
+    let account = get_bridge_address()
+        .ok_or_else(|| MirrorError::InternalError("XRPL_BRIDGE_ADDRESS not set".into()))?;
+    let nfts = rpc
+        .account_nfts(&account)
+        .await
+        .map_err(|e| MirrorError::NetworkError(format!("account_nfts failed: {}", e)))?;
+
+    Ok(nfts
+        .iter()
+        .any(|n| n["NFTokenID"].as_str().is_some_and(|id| id.eq_ignore_ascii_case(nftoken_id))))
+}
+
+/// Ledger index of the `NFTokenOffer` created by a validated `NFTokenCreateOffer`.
+fn created_offer_index(meta: &Value) -> Option<String> {
+    if let Some(id) = meta["offer_id"].as_str() {
+        return Some(id.to_string());
+    }
+    meta["AffectedNodes"].as_array()?.iter().find_map(|node| {
+        let created = &node["CreatedNode"];
+        (created["LedgerEntryType"] == "NFTokenOffer")
+            .then(|| created["LedgerIndex"].as_str().map(str::to_string))
+            .flatten()
+    })
+}
+
+/// One pass over mirrors whose two sides disagree:
+/// - mirrored on XRPL but not yet locked on ICP → retry `markAsMirrored`
+/// - burn pending and the buy-back landed in the bridge wallet → burn
+/// - burned on XRPL but still locked on ICP → retry `markAsUnmirrored`
+pub async fn settle_mirror_locks(agent: &Agent, config: &BridgeConfig, rpc: &XRPLRpcClient) {
+    for record in list_mirrors(None) {
+        let asset_key = record.asset_id.clone();
+        let result = match record.state {
+            MirrorState::Mirrored if !record.icp_marked => match asset_key.parse::<u128>() {
+                Ok(id) => match mirror_nft_to_xrpl(agent, config, Nat::from(id)).await {
+                    Ok(()) => set_icp_marked(&asset_key, true).map(|_| record.to_status()),
+                    Err(e) => Err(MirrorError::NetworkError(e.to_string())),
+                },
+                Err(_) => Err(MirrorError::InvalidAssetId(asset_key.clone())),
+            },
+            MirrorState::BurnPending => match mirrored_nftoken_id(&record) {
+                Ok(nftoken_id) => match bridge_holds_nftoken(rpc, &nftoken_id).await {
+                    Ok(true) => burn_and_unlock(agent, config, rpc, &asset_key, &nftoken_id).await,
+                    Ok(false) => continue,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            },
+            MirrorState::Burned if record.icp_marked => unlock_on_icp(agent, config, &asset_key).await,
+            _ => continue,
+        };
+
+        if let Err(e) = result {
+            bridge_log_event("warn", format!("Mirror maintenance for asset {} failed: {:?}", asset_key, e));
+        }
+    }
+}
+
+/// Background loop around `settle_mirror_locks`.
+pub async fn run_mirror_maintenance(agent: Agent, config: BridgeConfig, rpc: XRPLRpcClient, interval_secs: u64) {
+    track_mirrored_holders(&rpc).await;
+    loop {
+        settle_mirror_locks(&agent, &config, &rpc).await;
+        release_linked_sales();
+        tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+    }
+}
+
+/// Subscribes to the current holder of every live mirrored NFToken, so trades between
+/// third parties reach the bridge. Holders found later come from ingested sales.
+pub async fn track_mirrored_holders(rpc: &XRPLRpcClient) {
+    for record in list_mirrors(None).into_iter().filter(|r| r.state.is_live_on_xrpl()) {
+        let Some(XRPLMirrorRef::NFToken { nftoken_id }) = &record.xrpl_ref else { continue };
+        match rpc.nft_info(nftoken_id).await {
+            Ok(info) => {
+                if let Some(owner) = info["owner"].as_str() {
+                    track_account(owner);
+                }
+            }
+            Err(e) => bridge_log_event("warn", format!("Cannot locate holder of {}: {:?}", nftoken_id, e)),
+        }
+    }
+}
+
+/// `lsfSellNFToken` on an `NFTokenOffer` ledger entry.
+const LSF_SELL_NFTOKEN: u64 = 0x0000_0001;
+
+/// Reads an NFToken's `TransferFee` out of its ID (bytes 2..4).
+pub fn nftoken_transfer_fee(nftoken_id: &str) -> u16 {
+    nftoken_id
+        .get(4..8)
+        .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+        .unwrap_or(0)
+}
+
+/// Works out seller, buyer, price and broker fee from a validated `NFTokenAcceptOffer`.
+/// Direct mode consumes one offer and the submitter is the counterparty;
+/// brokered mode consumes both and the submitter is the broker.
+pub fn parse_nft_accept_offer(tx: &Value, meta: &Value) -> Option<NFTSaleDetails> {
+    let mut sell_offer = None;
+    let mut buy_offer = None;
+
+    for node in meta["AffectedNodes"].as_array()? {
+        let deleted = &node["DeletedNode"];
+        if deleted["LedgerEntryType"] != "NFTokenOffer" {
+            continue;
+        }
+        let index = deleted["LedgerIndex"].as_str();
+        let fields = &deleted["FinalFields"];
+        let is_sell = fields["Flags"].as_u64().unwrap_or(0) & LSF_SELL_NFTOKEN != 0;
+
+        if is_sell && index == tx["NFTokenSellOffer"].as_str() {
+            sell_offer = Some(fields);
+        } else if !is_sell && index == tx["NFTokenBuyOffer"].as_str() {
+            buy_offer = Some(fields);
+        }
+    }
+
+    let submitter = tx["Account"].as_str()?;
+    let (seller, buyer, price, broker) = match (sell_offer, buy_offer) {
+        (Some(sell), Some(buy)) => (sell["Owner"].as_str()?, buy["Owner"].as_str()?, &buy["Amount"], Some(submitter)),
+        (Some(sell), None) => (sell["Owner"].as_str()?, submitter, &sell["Amount"], None),
+        (None, Some(buy)) => (submitter, buy["Owner"].as_str()?, &buy["Amount"], None),
+        (None, None) => return None,
+    };
+
+    let nftoken_id = meta["nftoken_id"]
+        .as_str()
+        .or_else(|| sell_offer.or(buy_offer).and_then(|o| o["NFTokenID"].as_str()))?
+        .to_string();
+
+    Some(NFTSaleDetails {
+        transfer_fee: nftoken_transfer_fee(&nftoken_id),
+        nftoken_id,
+        seller: seller.to_string(),
+        buyer: buyer.to_string(),
+        price: XRPLAmount::from_json(price)?,
+        broker: broker.map(str::to_string),
+        broker_fee: broker.and_then(|_| XRPLAmount::from_json(&tx["NFTokenBrokerFee"])),
+    })
+}
+
+/// A marketplace sale whose buyer has no linked principal yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParkedSale {
+    pub tx_hash: String,
+    pub nft_id: String,
+    /// Account that submitted the trade, counted against limits on release.
+    pub submitter: String,
+    pub sale: NFTSaleDetails,
+    pub parked_at: u64,
+}
+
+const PARKED_SALES_FILE: &str = "nft_sales_awaiting_link.json";
+
+/// Sales waiting for their buyer to link an ICP principal, by tx hash.
+static PARKED_SALES: Lazy<RwLock<BTreeMap<String, ParkedSale>>> = Lazy::new(|| {
+    let sales = load_json_state::<BTreeMap<String, ParkedSale>>(PARKED_SALES_FILE)
+        .unwrap_or_else(|e| {
+            bridge_log_event("error", format!("Could not load parked NFT sales: {:?}", e));
+            None
+        })
+        .unwrap_or_default();
+    RwLock::new(sales)
 });
 
+fn persist_parked(sales: &BTreeMap<String, ParkedSale>) {
+    if let Err(e) = persist_json_state(PARKED_SALES_FILE, sales) {
+        bridge_log_event("error", format!("Failed to persist parked NFT sales: {:?}", e));
+    }
+}
+
+/// `NFTSale.price` for a trade: drops for XRP, IOU units (`XRPL_IOU_DECIMALS`) for the
+/// bridge's own IOU. Other issued currencies have no ICP-side unit and get `None`.
+fn sale_price(price: &XRPLAmount, bridge: Option<&str>) -> Option<Nat> {
+    match price {
+        XRPLAmount::XRP { drops } => Some(Nat::from(*drops)),
+        XRPLAmount::IOU { currency, issuer, value } if *currency == get_iou_currency() && Some(issuer.as_str()) == bridge => {
+            iou_value_to_units(value, get_iou_decimals()).map(Nat::from)
+        }
+        XRPLAmount::IOU { .. } => None,
+    }
+}
+
+fn sale_action(tx_hash: &str, nft_id: Nat, buyer: Principal, sale: NFTSaleDetails) -> Option<PendingAction> {
+    let Some(price) = sale_price(&sale.price, get_bridge_address().as_deref()) else {
+        bridge_log_event("error", format!("NFT sale {} has a price the bridge can't credit: {:?}", tx_hash, sale.price));
+        return None;
+    };
+    Some(PendingAction::NFTSale {
+        nft_id,
+        buyer,
+        price,
+        tx_hash: tx_hash.to_string(),
+        uuid: tx_hash.to_string(),
+        sale: Some(Box::new(sale)),
+    })
+}
+
+/// Turns a marketplace trade of a mirrored NFToken into a queued `NFTSale`.
+/// Trades of tokens the bridge didn't mint are ignored; trades to a buyer without a
+/// linked principal are parked until the link exists.
+pub fn ingest_nft_accept_offer(tx_hash: &str, tx: &Value, meta: &Value) -> Option<PendingAction> {
+    let sale = parse_nft_accept_offer(tx, meta)?;
+    let record = get_mirror_by_nftoken(&sale.nftoken_id)?;
+    if record.state != MirrorState::Mirrored && record.state != MirrorState::BurnPending {
+        return None;
+    }
+
+    let nft_id = match record.asset_id.parse::<u128>() {
+        Ok(id) => Nat::from(id),
+        Err(_) => {
+            bridge_log_event("error", format!("Mirror {} has a non-numeric asset id", record.asset_id));
+            return None;
+        }
+    };
+
+    bridge_log_event(
+        "nft_sale",
+        format!("🛒 {} sold {} → {} for {:?} ({})", sale.nftoken_id, sale.seller, sale.buyer, sale.price, tx_hash),
+    );
+    // The new holder's resales must reach the bridge too.
+    track_account(&sale.buyer);
+
+    match linked_principal(&sale.buyer) {
+        Some(buyer) => sale_action(tx_hash, nft_id, buyer, sale),
+        None => {
+            bridge_log_event("warn", format!("⏸️ NFT buyer {} has no linked principal; sale {} waits for the link", sale.buyer, tx_hash));
+            let mut sales = PARKED_SALES.write().unwrap();
+            sales.insert(
+                tx_hash.to_string(),
+                ParkedSale {
+                    tx_hash: tx_hash.to_string(),
+                    nft_id: nft_id.0.to_string(),
+                    submitter: tx["Account"].as_str().unwrap_or_default().to_string(),
+                    sale,
+                    parked_at: now_secs(),
+                },
+            );
+            persist_parked(&sales);
+            None
+        }
+    }
+}
+
+/// Queues parked sales whose buyer has linked a principal since.
+pub fn release_linked_sales() {
+    let ready: Vec<ParkedSale> = {
+        let mut sales = PARKED_SALES.write().unwrap();
+        let ready: Vec<String> = sales
+            .values()
+            .filter(|parked| linked_principal(&parked.sale.buyer).is_some())
+            .map(|parked| parked.tx_hash.clone())
+            .collect();
+        if ready.is_empty() {
+            return;
+        }
+        let ready = ready.iter().filter_map(|hash| sales.remove(hash)).collect();
+        persist_parked(&sales);
+        ready
+    };
+
+    for parked in ready {
+        let Some(buyer) = linked_principal(&parked.sale.buyer) else { continue };
+        let Ok(nft_id) = parked.nft_id.parse::<u128>() else { continue };
+        let Some(action) = sale_action(&parked.tx_hash, Nat::from(nft_id), buyer, parked.sale) else {
+            continue;
+        };
+        let Some(action) = admit_action(&parked.submitter, action, None) else { continue };
+        match enqueue_action(action) {
+            Ok(()) => bridge_log_event("nft_sale", format!("▶️ Buyer linked; queued sale {}", parked.tx_hash)),
+            Err(QueueError::AlreadyExists) => {}
+            Err(e) => bridge_log_event("error", format!("Failed to queue {}: {:?}", parked.tx_hash, e)),
+        }
+    }
+}
+
+/// Sales still waiting for their buyer to link, for the admin API.
+pub fn list_parked_sales() -> Vec<ParkedSale> {
+    PARKED_SALES.read().unwrap().values().cloned().collect()
+}
+
 /// Returns mirror status info for a given Axia asset NFT.
 pub fn get_mirror_status_for_asset(nft_id: Nat) -> Result<XRPLMirrorStatus, MirrorError> {
-    let store = MIRROR_STATUS_STORE.read().map_err(|_| MirrorError::InternalError("Lock error".into()))?;
+    get_mirror(&nft_id.0.to_string())
+        .map(|record| record.to_status())
+        .ok_or_else(|| MirrorError::NotFound("No mirror info found for asset".into()))
+}
+
+/// One disagreement between the mirror registry and the ICP canisters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorDiscrepancy {
+    pub asset_id: String,
+    pub state: MirrorState,
+    pub issue: String,
+}
 
-    match store.get(&nft_id) {
-        Some(status) => Ok(status.clone()),
-        None => Err(MirrorError::NotFound("No mirror info found for asset".into())),
+/// Cross-checks every non-terminal mirror against the `nft` and `asset_registry` canisters.
+/// Read-only: discrepancies are logged and returned for an operator to resolve.
+pub async fn reconcile_mirrors(agent: &Agent, config: &BridgeConfig) -> Vec<MirrorDiscrepancy> {
+    let mut issues = Vec::new();
+
+    let records: Vec<MirrorRecord> = list_mirrors(None)
+        .into_iter()
+        .filter(|r| r.state != MirrorState::Burned || r.icp_marked)
+        .collect();
+
+    for record in records {
+        let mut flag = |issue: String| {
+            bridge_log_event("mirror", format!("⚠️ Asset {}: {}", record.asset_id, issue));
+            issues.push(MirrorDiscrepancy {
+                asset_id: record.asset_id.clone(),
+                state: record.state,
+                issue,
+            });
+        };
+
+        let Ok(nft_id) = record.asset_id.parse::<u128>().map(Nat::from) else {
+            flag("asset id is not numeric".into());
+            continue;
+        };
+
+        match fetch_axia_nft(agent, config, nft_id.clone()).await {
+            Ok(nft) => {
+                if nft.creator.to_text() != record.artist && nft.owner.to_text() != record.artist {
+                    flag(format!("artist {} is neither creator nor owner on ICP", record.artist));
+                }
+                if record.state == MirrorState::Mirrored && !nft.is_active {
+                    flag("NFT is deactivated on ICP while mirrored".into());
+                }
+            }
+            Err(e) => flag(format!("nft canister lookup failed: {}", e)),
+        }
+
+        match fetch_assets_for_nft(agent, config, nft_id).await {
+            Ok(assets) if assets.iter().any(|a| a.is_active) => {}
+            Ok(_) => flag("no active asset_registry entry for this NFT".into()),
+            Err(e) => flag(format!("asset_registry lookup failed: {}", e)),
+        }
+
+        if record.state == MirrorState::Mirrored && !record.icp_marked {
+            flag("minted on XRPL but never marked as mirrored on ICP".into());
+        }
+        if record.state == MirrorState::Burned && record.icp_marked {
+            flag("burned on XRPL but still locked on ICP".into());
+        }
+        if record.state.is_live_on_xrpl() && record.state != MirrorState::Minting && record.xrpl_ref.is_none() {
+            flag("no XRPL identifier recorded".into());
+        }
     }
+
+    issues
 }
 
 /// Verifies that the XRPL transaction correctly represents a mirror of the intended asset.
@@ -148,7 +771,7 @@ pub fn verify_xrpl_mirror_tx(tx: &VerifiedXRPLTx) -> Result<(), MirrorError> {
     }
 
     // Check minimum amount if applicable
-    if tx.amount == Nat::from(0u8) {
+    if tx.amount == 0u8 {
         return Err(MirrorError::InvalidParameters("Amount must be non-zero".into()));
     }
 
@@ -156,4 +779,29 @@ pub fn verify_xrpl_mirror_tx(tx: &VerifiedXRPLTx) -> Result<(), MirrorError> {
     println!("✅ Mirror tx verified: UUID = {:?}", tx.memo.uuid);
 
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sale_prices_carry_iou_amounts() {
+        let bridge = Some("rBridge");
+        assert_eq!(sale_price(&XRPLAmount::XRP { drops: 2_500_000 }, bridge), Some(Nat::from(2_500_000u64)));
+
+        let iou = |currency: &str, issuer: &str, value: &str| XRPLAmount::IOU {
+            currency: currency.into(),
+            issuer: issuer.into(),
+            value: value.into(),
+        };
+        let ours = get_iou_currency();
+        let scale = 10u128.pow(get_iou_decimals());
+        assert_eq!(sale_price(&iou(&ours, "rBridge", "12.5"), bridge), Some(Nat::from(12 * scale + scale / 2)));
+        assert_eq!(sale_price(&iou(&ours, "rBridge", "not a number"), bridge), None);
+
+        // Foreign currencies, or our code from another issuer, have no ICP-side units.
+        assert_eq!(sale_price(&iou("USD", "rIssuer", "12.5"), bridge), None);
+        assert_eq!(sale_price(&iou(&ours, "rIssuer", "12.5"), bridge), None);
+        assert_eq!(sale_price(&iou(&ours, "rBridge", "12.5"), None), None);
+    }
+}
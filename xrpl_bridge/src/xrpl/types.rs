@@ -3,12 +3,12 @@ use std::io;
 use candid::Principal;
 use tokio_tungstenite::tungstenite::Error as WsError;
 use std::time::Duration;
-use candid::{Nat};
+use candid::{CandidType, Nat};
 
 
 #[derive(Debug)]
 pub enum XRPLError {
-    WebSocketError(WsError),
+    WebSocketError(Box<WsError>),
     HttpError(reqwest::Error),
     IoError(io::Error),
     InvalidResponse(String),
@@ -44,7 +44,7 @@ impl std::error::Error for XRPLError {}
 
 impl From<WsError> for XRPLError {
     fn from(e: WsError) -> Self {
-        XRPLError::WebSocketError(e)
+        XRPLError::WebSocketError(Box::new(e))
     }
 }
 
@@ -80,12 +80,16 @@ use serde::Deserialize;
 pub enum XRPLCommand {
     #[serde(rename = "subscribe")]
     Subscribe {
+        #[serde(skip_serializing_if = "Option::is_none")]
         streams: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         accounts: Option<Vec<String>>,
     },
     #[serde(rename = "unsubscribe")]
     Unsubscribe {
+        #[serde(skip_serializing_if = "Option::is_none")]
         streams: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         accounts: Option<Vec<String>>,
     },
     #[serde(rename = "ping")]
@@ -140,6 +144,7 @@ pub enum XRPLActionType {
     Tip,
     NFTSale,
     TokenSwap,
+    WrapXRP,
 }
 
 #[derive(Debug, Clone)]
@@ -148,6 +153,8 @@ pub struct ParsedMemo {
     pub artist: Option<Principal>,
     pub nft_id: Option<Nat>,
     pub uuid: Option<String>,
+    /// What a SWAP memo wants back: `XRP` or `CUR.rIssuer`.
+    pub receive: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -215,8 +222,331 @@ pub struct TipRequest {
     pub destination_tag: Option<u32>,
 }
 
+/// An XRPL `Amount`: XRP in drops, or an issued-currency (IOU) amount.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CandidType)]
+#[serde(tag = "kind")]
+pub enum XRPLAmount {
+    XRP { drops: u64 },
+    IOU { currency: String, issuer: String, value: String },
+}
+
+impl XRPLAmount {
+    /// Parses the wire format: a drops string, or `{currency, issuer, value}`.
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        if let Some(drops) = value.as_str() {
+            return drops.parse().ok().map(|drops| XRPLAmount::XRP { drops });
+        }
+        Some(XRPLAmount::IOU {
+            currency: value["currency"].as_str()?.to_string(),
+            issuer: value["issuer"].as_str()?.to_string(),
+            value: value["value"].as_str()?.to_string(),
+        })
+    }
+
+    /// Back to the wire format.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            XRPLAmount::XRP { drops } => serde_json::json!(drops.to_string()),
+            XRPLAmount::IOU { currency, issuer, value } => serde_json::json!({
+                "currency": currency,
+                "issuer": issuer,
+                "value": value,
+            }),
+        }
+    }
+
+    pub fn drops(&self) -> Option<u64> {
+        match self {
+            XRPLAmount::XRP { drops } => Some(*drops),
+            XRPLAmount::IOU { .. } => None,
+        }
+    }
+
+    /// Numeric value: drops for XRP, the decimal value for an IOU (zero if unparsable).
+    pub fn value(&self) -> XRPLValue {
+        match self {
+            XRPLAmount::XRP { drops } => XRPLValue::from_drops(*drops),
+            XRPLAmount::IOU { value, .. } => XRPLValue::parse(value).unwrap_or(XRPLValue::ZERO),
+        }
+    }
+}
+
+/// Exact decimal for amount math: `mantissa × 10^exponent`, kept to 18 significant digits
+/// (XRPL itself keeps 16). Every operation rounds toward zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct XRPLValue {
+    mantissa: i128,
+    exponent: i32,
+}
+
+/// Significant digits kept by `XRPLValue`.
+const VALUE_DIGITS: u32 = 18;
+
+/// Significant digits XRPL accepts in an IOU value.
+const IOU_DIGITS: u32 = 16;
+
+fn digit_count(n: i128) -> u32 {
+    n.unsigned_abs().checked_ilog10().map_or(1, |d| d + 1)
+}
+
+impl XRPLValue {
+    pub const ZERO: XRPLValue = XRPLValue { mantissa: 0, exponent: 0 };
+
+    pub fn new(mantissa: i128, exponent: i32) -> Self {
+        let (mut mantissa, mut exponent) = (mantissa, exponent);
+        while digit_count(mantissa) > VALUE_DIGITS {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        if mantissa == 0 {
+            return Self::ZERO;
+        }
+        while mantissa % 10 == 0 {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        Self { mantissa, exponent }
+    }
+
+    pub fn from_drops(drops: u64) -> Self {
+        Self::new(drops as i128, 0)
+    }
+
+    /// Parses `"123"`, `"-0.05"` or `"1.5e-7"`. Digits beyond the kept precision are dropped.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (number, exp) = match text.split_once(['e', 'E']) {
+            Some((number, exp)) => (number, exp.parse::<i32>().ok()?),
+            None => (text, 0),
+        };
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (int_part, frac_part) = number.split_once('.').unwrap_or((number, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+
+        let (mut mantissa, mut exponent) = (0i128, exp);
+        for (i, c) in int_part.chars().chain(frac_part.chars()).enumerate() {
+            let digit = c.to_digit(10)? as i128;
+            let in_fraction = i >= int_part.len();
+            if digit_count(mantissa) < VALUE_DIGITS {
+                mantissa = mantissa * 10 + digit;
+                if in_fraction {
+                    exponent -= 1;
+                }
+            } else if !in_fraction {
+                exponent += 1;
+            }
+        }
+        Some(Self::new(if negative { -mantissa } else { mantissa }, exponent))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.mantissa > 0
+    }
+
+    /// Whole drops (or units), rounded down; negatives become zero.
+    pub fn floor_u64(&self) -> u64 {
+        if self.mantissa <= 0 {
+            return 0;
+        }
+        if self.exponent < 0 {
+            let scale = 10i128.checked_pow(self.exponent.unsigned_abs()).unwrap_or(i128::MAX);
+            return u64::try_from(self.mantissa / scale).unwrap_or(u64::MAX);
+        }
+        10i128
+            .checked_pow(self.exponent as u32)
+            .and_then(|scale| self.mantissa.checked_mul(scale))
+            .and_then(|v| u64::try_from(v).ok())
+            .unwrap_or(u64::MAX)
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        // Widen the numerator so the quotient keeps full precision.
+        let shift = 36u32.saturating_sub(digit_count(self.mantissa));
+        let numerator = self.mantissa * 10i128.pow(shift);
+        Some(Self::new(numerator / rhs.mantissa, self.exponent - rhs.exponent - shift as i32))
+    }
+
+    /// The value as XRPL accepts it for an IOU: at most 16 significant digits.
+    pub fn to_iou_string(&self) -> String {
+        let (mut mantissa, mut exponent) = (self.mantissa, self.exponent);
+        while digit_count(mantissa) > IOU_DIGITS {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        Self::new(mantissa, exponent).to_string()
+    }
+}
+
+impl fmt::Display for XRPLValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        match self.exponent {
+            0 => write!(f, "{}{}", sign, digits),
+            e if e > 0 && digits.len() as i32 + e <= 20 => write!(f, "{}{}{}", sign, digits, "0".repeat(e as usize)),
+            e if e < 0 && -e <= 20 => {
+                let places = (-e) as usize;
+                if digits.len() > places {
+                    let (int, frac) = digits.split_at(digits.len() - places);
+                    write!(f, "{}{}.{}", sign, int, frac)
+                } else {
+                    write!(f, "{}0.{}{}", sign, "0".repeat(places - digits.len()), digits)
+                }
+            }
+            e => write!(f, "{}{}e{}", sign, digits, e),
+        }
+    }
+}
+
+impl From<XRPLValue> for String {
+    fn from(value: XRPLValue) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for XRPLValue {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        XRPLValue::parse(&text).ok_or_else(|| format!("invalid decimal {}", text))
+    }
+}
+
+impl Ord for XRPLValue {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (*self - *other).mantissa.cmp(&0)
+    }
+}
+
+impl PartialOrd for XRPLValue {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::ops::Add for XRPLValue {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        if self.is_zero() {
+            return rhs;
+        }
+        if rhs.is_zero() {
+            return self;
+        }
+        let (high, low) = if self.exponent >= rhs.exponent { (self, rhs) } else { (rhs, self) };
+        let gap = (high.exponent - low.exponent) as u32;
+        // Scale the larger-exponent side up as far as i128 allows, then the other side down.
+        let up = gap.min(36 - digit_count(high.mantissa));
+        let down = gap - up;
+        let high_mantissa = high.mantissa * 10i128.pow(up);
+        let low_mantissa = 10i128.checked_pow(down).map_or(0, |scale| low.mantissa / scale);
+        Self::new(high_mantissa + low_mantissa, high.exponent - up as i32)
+    }
+}
+
+impl std::ops::AddAssign for XRPLValue {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::SubAssign for XRPLValue {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::ops::Neg for XRPLValue {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { mantissa: -self.mantissa, exponent: self.exponent }
+    }
+}
+
+impl std::ops::Sub for XRPLValue {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl std::ops::Mul for XRPLValue {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.mantissa * rhs.mantissa, self.exponent + rhs.exponent)
+    }
+}
+
+impl std::iter::Sum for XRPLValue {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, v| acc + v)
+    }
+}
+
 pub struct XRPLSubmitResult {
     pub tx_hash: String,
     pub status: String, // e.g., "submitted", "confirmed", etc.
     pub ledger_index: u64,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(text: &str) -> XRPLValue {
+        XRPLValue::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_decimals() {
+        assert_eq!(v("123").to_string(), "123");
+        assert_eq!(v("-0.050").to_string(), "-0.05");
+        assert_eq!(v("1.5e-7").to_string(), "0.00000015");
+        assert_eq!(v("2500e3").to_string(), "2500000");
+        assert_eq!(v("1e-90").to_string(), "1e-90");
+        assert!(XRPLValue::parse("1.2.3").is_none());
+        assert!(XRPLValue::parse("").is_none());
+    }
+
+    #[test]
+    fn arithmetic_is_exact_where_floats_are_not() {
+        assert_eq!(v("0.1") + v("0.2"), v("0.3"));
+        assert_eq!(v("1000000") - v("0.000001"), v("999999.999999"));
+        assert_eq!(v("1.25") * v("4"), v("5"));
+        assert_eq!(v("10").checked_div(v("4")).unwrap(), v("2.5"));
+        assert!(v("1").checked_div(XRPLValue::ZERO).is_none());
+        assert!(v("0.3") > v("0.29999999"));
+        assert_eq!(vec![v("1.1"), v("2.2")].into_iter().sum::<XRPLValue>(), v("3.3"));
+    }
+
+    #[test]
+    fn rounds_toward_zero() {
+        assert_eq!(v("2").checked_div(v("3")).unwrap().to_iou_string(), "0.6666666666666666");
+        assert_eq!(v("1999.9").floor_u64(), 1999);
+        assert_eq!(v("-5").floor_u64(), 0);
+        assert_eq!(v("12345678901234567891").to_iou_string(), "12345678901234560000");
+    }
+
+    #[test]
+    fn amounts_expose_their_value() {
+        assert_eq!(XRPLAmount::XRP { drops: 1_500_000 }.value(), v("1500000"));
+        let iou = XRPLAmount::IOU { currency: "USD".into(), issuer: "rI".into(), value: "12.5".into() };
+        assert_eq!(iou.value(), v("12.5"));
+    }
+}
@@ -3,13 +3,21 @@ use once_cell::sync::Lazy;
 use std::sync::Mutex;
 use candid::{Principal, Nat};
 use std::env;
+use crate::xrpl::params::min_tip_drops;
+use crate::xrpl::address::normalize_destination;
 use crate::xrpl::types::{VerifiedXRPLTx, XRPLActionType, CandidateXRPLTx, ParsedMemo, VerifierError};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // In-memory replay cache (replace with persistent state later)
 static REPLAY_CACHE: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
 
-pub fn verify_candidate_tx(tx: CandidateXRPLTx) -> Result<VerifiedXRPLTx, VerifierError> {
+pub fn verify_candidate_tx(mut tx: CandidateXRPLTx) -> Result<VerifiedXRPLTx, VerifierError> {
+    // Step 0: X-addresses carry the destination tag; route on the classic address + tag
+    let (destination, destination_tag) = normalize_destination(&tx.destination, tx.destination_tag)
+        .map_err(|e| VerifierError::InvalidDestination(format!("{}: {}", tx.destination, e)))?;
+    tx.destination = destination;
+    tx.destination_tag = destination_tag;
+
     // Step 1: Replay protection
     if is_replay(&tx.tx_hash) {
         return Err(VerifierError::ReplayDetected(tx.tx_hash.clone()));
@@ -22,7 +30,7 @@ pub fn verify_candidate_tx(tx: CandidateXRPLTx) -> Result<VerifiedXRPLTx, Verifi
     let memo = parse_memo(&tx.memo)?;
 
     // Step 4: Amount threshold enforcement
-    let expected_min = Nat::from(1000u64); // Can be made dynamic per `action`
+    let expected_min = Nat::from(min_tip_drops()); // Can be made dynamic per `action`
     if !validate_amount(&tx, expected_min.clone()) {
         return Err(VerifierError::InsufficientAmount(tx.amount.clone(), expected_min));
     }
@@ -69,6 +77,7 @@ pub fn parse_tag(tx: &CandidateXRPLTx) -> Option<XRPLActionType> {
         Some(1001) => Some(XRPLActionType::Tip),
         Some(2001) => Some(XRPLActionType::NFTSale),
         Some(3001) => Some(XRPLActionType::TokenSwap),
+        Some(4001) => Some(XRPLActionType::WrapXRP),
         _ => None,
     }
 }
@@ -76,7 +85,7 @@ pub fn parse_tag(tx: &CandidateXRPLTx) -> Option<XRPLActionType> {
 pub fn parse_memo(memo: &str) -> Result<ParsedMemo, VerifierError> {
     let parts: Vec<&str> = memo.split('|').collect();
 
-    if parts.len() < 1 {
+    if parts.is_empty() {
         return Err(VerifierError::InvalidMemoFormat);
     }
 
@@ -84,15 +93,17 @@ pub fn parse_memo(memo: &str) -> Result<ParsedMemo, VerifierError> {
         "TIP" => XRPLActionType::Tip,
         "NFT" => XRPLActionType::NFTSale,
         "SWAP" => XRPLActionType::TokenSwap,
+        "WRAP" => XRPLActionType::WrapXRP,
         _ => return Err(VerifierError::UnknownAction),
     };
 
     let mut artist = None;
     let mut nft_id = None;
     let mut uuid = None;
+    let mut receive = None;
 
     for part in parts.iter().skip(1) {
-        if let Some(stripped) = part.strip_prefix("ARTIST:") {
+        if let Some(stripped) = part.strip_prefix("ARTIST:").or_else(|| part.strip_prefix("PRINCIPAL:")) {
             artist = Principal::from_text(stripped).ok();
         } else if let Some(stripped) = part.strip_prefix("NFT:") {
             if let Ok(parsed) = stripped.parse::<u128>() {
@@ -100,6 +111,8 @@ pub fn parse_memo(memo: &str) -> Result<ParsedMemo, VerifierError> {
             }
         } else if let Some(stripped) = part.strip_prefix("UUID:") {
             uuid = Some(stripped.to_string());
+        } else if let Some(stripped) = part.strip_prefix("RECEIVE:") {
+            receive = Some(stripped.to_string());
         }
     }
 
@@ -108,6 +121,7 @@ pub fn parse_memo(memo: &str) -> Result<ParsedMemo, VerifierError> {
         artist,
         nft_id,
         uuid,
+        receive,
     })
 }
 
@@ -116,9 +130,17 @@ pub fn validate_amount(tx: &CandidateXRPLTx, expected_min: Nat) -> bool {
 }
 
 /// NOTE: This assumes the bridge address is stored in env (or config file in the future).
+/// Accepts the classic address or an X-address for this bridge's network.
 pub fn is_bridge_destination(addr: &str) -> bool {
     if let Ok(bridge_addr) = env::var("XRPL_BRIDGE_ADDRESS") {
-        addr.eq_ignore_ascii_case(&bridge_addr)
+        let classic = match normalize_destination(addr, None) {
+            Ok((classic, _)) => classic,
+            Err(e) => {
+                println!("⚠️ Rejected destination {}: {}", addr, e);
+                return false;
+            }
+        };
+        classic.eq_ignore_ascii_case(&bridge_addr)
     } else {
         println!("⚠️ Bridge address not set in XRPL_BRIDGE_ADDRESS");
         false
@@ -135,5 +157,5 @@ pub fn log_verification(tx: &VerifiedXRPLTx) {
         "uuid": tx.memo.uuid,
     });
 
-    println!("📒 VerifiedTxLog: {}", log_line.to_string());
+    println!("📒 VerifiedTxLog: {}", log_line);
 }
\ No newline at end of file